@@ -11,8 +11,7 @@ fn main() {
     let mut options = CopyOptions::new();
     options.overwrite = true;
 
-    let mut paths_to_copy = Vec::new();
-    paths_to_copy.push(PathBuf::from("./assets"));
+    let paths_to_copy = vec![PathBuf::from("./assets")];
 
     let out_dir = {
         if let Ok(target) = env::var("CARGO_TARGET_DIR") {
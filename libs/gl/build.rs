@@ -16,7 +16,12 @@ fn main() {
         (4, 5),
         gl_generator::Profile::Core,
         gl_generator::Fallbacks::All,
-        [],
+        [
+            "GL_ARB_gl_spirv",
+            "GL_ARB_texture_filter_anisotropic",
+            "GL_EXT_texture_compression_s3tc",
+            "GL_EXT_texture_sRGB",
+        ],
     );
 
     if let Ok(_) = var("CARGO_FEATURE_DEBUG") {
@@ -0,0 +1,116 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use proc_macro2::{Span, TokenStream};
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, Ident, LitStr};
+
+#[derive(Clone, Copy)]
+pub enum Layout {
+    Std140,
+    Std430,
+}
+
+impl Layout {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Std140 => "Std140",
+            Self::Std430 => "Std430",
+        }
+    }
+}
+
+pub fn derive(input: DeriveInput, layout: Layout) -> syn::Result<TokenStream> {
+    let trait_name = layout.name();
+    if !crate::vertex_layout::has_repr_c(&input)? {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("{} can only be derived for #[repr(C)] structs", trait_name),
+        ));
+    }
+    if !input.generics.params.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.generics,
+            format!("{} can't be derived for generic structs", trait_name),
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    format!("{} needs named fields", trait_name),
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                format!("{} can only be derived for structs", trait_name),
+            ))
+        }
+    };
+    if fields.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            format!("{} needs at least one field", trait_name),
+        ));
+    }
+
+    let name = &input.ident;
+    let layout_trait = Ident::new(trait_name, Span::call_site());
+    let layout_name = trait_name.to_lowercase();
+    let path = quote!(::gl_playground::block_layout);
+
+    let aligns = fields.iter().map(|field| {
+        let ty = &field.ty;
+        quote!(<#ty as #path::#layout_trait>::ALIGN)
+    });
+    let align = match layout {
+        // Structs are rounded up to a vec4 in std140
+        Layout::Std140 => quote!(#path::max_align(&[16, #(#aligns),*])),
+        Layout::Std430 => quote!(#path::max_align(&[#(#aligns),*])),
+    };
+
+    let checks = fields.iter().map(|field| {
+        let field_name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        let message = LitStr::new(
+            &format!(
+                "`{}::{}` isn't at its {} offset, add padding before it",
+                name, field_name, layout_name
+            ),
+            Span::call_site(),
+        );
+
+        quote! {
+            let offset = #path::align_to(end, <#ty as #path::#layout_trait>::ALIGN);
+            assert!(::core::mem::offset_of!(#name, #field_name) == offset, #message);
+            end = offset + ::core::mem::size_of::<#ty>();
+        }
+    });
+    let size_message = LitStr::new(
+        &format!(
+            "The size of `{}` isn't a multiple of its {} alignment, add padding at the end",
+            name, layout_name
+        ),
+        Span::call_site(),
+    );
+
+    Ok(quote! {
+        unsafe impl #path::#layout_trait for #name {
+            const ALIGN: usize = #align;
+        }
+
+        const _: () = {
+            let mut end = 0;
+            #(#checks)*
+            assert!(
+                ::core::mem::size_of::<#name>() == #path::align_to(end, <#name as #path::#layout_trait>::ALIGN),
+                #size_message
+            );
+        };
+    })
+}
@@ -0,0 +1,42 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use proc_macro::TokenStream;
+use syn::{parse_macro_input, DeriveInput};
+
+mod block_layout;
+mod vertex_layout;
+
+/// Implements `gl_playground::vertex::VertexLayout` for a `#[repr(C)]` struct.
+///
+/// Every field becomes an attribute, at the location of its index unless
+/// overridden with `#[vertex(location = N)]`.
+/// Integer fields are passed as integers unless marked `#[vertex(normalized)]`.
+#[proc_macro_derive(VertexLayout, attributes(vertex))]
+pub fn derive_vertex_layout(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    vertex_layout::derive(input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Implements `gl_playground::block_layout::Std140` for a `#[repr(C)]` struct.
+///
+/// Fails to compile unless every field sits at its std140 offset and the size is a
+/// multiple of the std140 alignment, padding has to be added with `Pad<N>` fields.
+#[proc_macro_derive(Std140)]
+pub fn derive_std140(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    block_layout::derive(input, block_layout::Layout::Std140)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Like [`Std140`](derive@Std140), with the std430 rules of shader storage blocks
+#[proc_macro_derive(Std430)]
+pub fn derive_std430(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
+    block_layout::derive(input, block_layout::Layout::Std430)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
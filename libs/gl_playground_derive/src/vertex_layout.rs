@@ -0,0 +1,95 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Data, DeriveInput, Fields, LitInt};
+
+pub fn derive(input: DeriveInput) -> syn::Result<TokenStream> {
+    if !has_repr_c(&input)? {
+        return Err(syn::Error::new_spanned(
+            &input.ident,
+            "VertexLayout can only be derived for #[repr(C)] structs",
+        ));
+    }
+
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &input.ident,
+                    "VertexLayout needs named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                &input.ident,
+                "VertexLayout can only be derived for structs",
+            ))
+        }
+    };
+
+    let name = &input.ident;
+    let mut attributes = Vec::new();
+    for (index, field) in fields.iter().enumerate() {
+        let mut location = index as u32;
+        let mut normalized = false;
+
+        for attr in field.attrs.iter().filter(|a| a.path().is_ident("vertex")) {
+            attr.parse_nested_meta(|meta| {
+                if meta.path.is_ident("location") {
+                    location = meta.value()?.parse::<LitInt>()?.base10_parse()?;
+                    Ok(())
+                } else if meta.path.is_ident("normalized") {
+                    normalized = true;
+                    Ok(())
+                } else {
+                    Err(meta.error("expected `location = N` or `normalized`"))
+                }
+            })?;
+        }
+
+        let field_name = field.ident.as_ref().unwrap();
+        let ty = &field.ty;
+        attributes.push(quote! {
+            ::gl_playground::vertex::VertexAttribute {
+                location: #location,
+                components: <#ty as ::gl_playground::vertex::AttributeFormat>::COMPONENTS,
+                data_type: <#ty as ::gl_playground::vertex::AttributeFormat>::DATA_TYPE,
+                normalized: #normalized,
+                integer: <#ty as ::gl_playground::vertex::AttributeFormat>::INTEGER && !#normalized,
+                offset: ::core::mem::offset_of!(#name, #field_name) as u32,
+            }
+        });
+    }
+
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics ::gl_playground::vertex::VertexLayout for #name #ty_generics #where_clause {
+            const ATTRIBUTES: &'static [::gl_playground::vertex::VertexAttribute] = &[
+                #(#attributes),*
+            ];
+        }
+    })
+}
+
+pub fn has_repr_c(input: &DeriveInput) -> syn::Result<bool> {
+    let mut repr_c = false;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("repr")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("C") {
+                repr_c = true;
+            }
+            // Skip over the arguments of things like `align(N)`
+            if meta.input.peek(syn::token::Paren) {
+                let _content;
+                syn::parenthesized!(_content in meta.input);
+            }
+            Ok(())
+        })?;
+    }
+
+    Ok(repr_c)
+}
@@ -0,0 +1,125 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::mem::size_of;
+
+use glam::{IVec2, IVec3, IVec4, Mat2, Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+pub use gl_playground_derive::{Std140, Std430};
+
+/// A type laid out the way GLSL's std140 rules (uniform blocks) lay it out.
+///
+/// Usually derived with `#[derive(Std140)]`, which checks every field offset at compile time.
+///
+/// # Safety
+///
+/// `ALIGN` has to be the std140 base alignment, and `size_of::<Self>()` the std140 size.
+pub unsafe trait Std140: bytemuck::Pod {
+    const ALIGN: usize;
+}
+
+/// Like [`Std140`], with the std430 rules of shader storage blocks.
+///
+/// # Safety
+///
+/// `ALIGN` has to be the std430 base alignment, and `size_of::<Self>()` the std430 size.
+pub unsafe trait Std430: bytemuck::Pod {
+    const ALIGN: usize;
+}
+
+/// Explicit padding bytes for derived layouts
+#[repr(transparent)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pad<const N: usize>([u8; N]);
+
+impl<const N: usize> Default for Pad<N> {
+    fn default() -> Self {
+        Self([0; N])
+    }
+}
+
+unsafe impl<const N: usize> bytemuck::Zeroable for Pad<N> {}
+unsafe impl<const N: usize> bytemuck::Pod for Pad<N> {}
+
+/// Rounds `offset` up to a multiple of `align`
+pub const fn align_to(offset: usize, align: usize) -> usize {
+    offset.div_ceil(align) * align
+}
+
+/// The largest of `aligns`, used by the derives
+pub const fn max_align(aligns: &[usize]) -> usize {
+    let mut max = 1;
+    let mut i = 0;
+    while i < aligns.len() {
+        if aligns[i] > max {
+            max = aligns[i];
+        }
+        i += 1;
+    }
+
+    max
+}
+
+macro_rules! block_layout {
+    ($ty:ty, $align:expr) => {
+        block_layout!($ty, $align, $align);
+    };
+    ($ty:ty, $std140:expr, $std430:expr) => {
+        unsafe impl Std140 for $ty {
+            const ALIGN: usize = $std140;
+        }
+        unsafe impl Std430 for $ty {
+            const ALIGN: usize = $std430;
+        }
+    };
+}
+
+block_layout!(f32, 4);
+block_layout!(i32, 4);
+block_layout!(u32, 4);
+block_layout!(Vec2, 8);
+block_layout!(IVec2, 8);
+block_layout!(UVec2, 8);
+// A vec3 is 12 bytes, a following scalar fits in its last 4
+block_layout!(Vec3, 16);
+block_layout!(IVec3, 16);
+block_layout!(UVec3, 16);
+block_layout!(Vec4, 16);
+block_layout!(IVec4, 16);
+block_layout!(UVec4, 16);
+block_layout!(Quat, 16);
+// Four vec4 columns
+block_layout!(Mat4, 16);
+
+// A std140 mat2 has 16-byte columns, glam's are 8
+unsafe impl Std430 for Mat2 {
+    const ALIGN: usize = 8;
+}
+
+unsafe impl<const N: usize> Std140 for Pad<N> {
+    const ALIGN: usize = 1;
+}
+
+unsafe impl<const N: usize> Std430 for Pad<N> {
+    const ALIGN: usize = 1;
+}
+
+unsafe impl<T: Std140, const N: usize> Std140 for [T; N] {
+    const ALIGN: usize = {
+        assert!(
+            size_of::<T>().is_multiple_of(16),
+            "std140 array elements are padded to 16 bytes, use vec4s or padded structs"
+        );
+        align_to(T::ALIGN, 16)
+    };
+}
+
+unsafe impl<T: Std430, const N: usize> Std430 for [T; N] {
+    const ALIGN: usize = {
+        assert!(
+            size_of::<T>().is_multiple_of(T::ALIGN),
+            "std430 array elements are padded to their alignment, e.g. vec3s to 16 bytes"
+        );
+        T::ALIGN
+    };
+}
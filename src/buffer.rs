@@ -0,0 +1,413 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    marker::PhantomData,
+    mem::{size_of, size_of_val},
+    ops::{Deref, DerefMut, Range},
+};
+
+/// An immutable-storage buffer object holding `len` elements of `T`.
+///
+/// All offsets and ranges are in elements, not bytes.
+pub struct Buffer<'a, T: bytemuck::Pod> {
+    gl: &'a gl::Gl,
+    id: u32,
+    len: usize,
+    flags: u32,
+    persistent_ptr: Option<*mut T>,
+    persistent_access: u32,
+    label: Option<&'a str>,
+    _marker: PhantomData<T>,
+}
+
+impl<'a, T: bytemuck::Pod> Buffer<'a, T> {
+    /// Creates a buffer initialized with `data`.
+    ///
+    /// `flags` are the `glNamedBufferStorage` flags, e.g. `gl::DYNAMIC_STORAGE_BIT`
+    /// to allow [`Buffer::set_data`] or `gl::MAP_WRITE_BIT` to allow mapping for writes.
+    pub fn from_data(
+        gl: &'a gl::Gl,
+        data: &[T],
+        flags: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, BufferError> {
+        Self::create(gl, data.len(), data.as_ptr().cast(), flags, label)
+    }
+
+    /// Creates a buffer with room for `len` zeroed elements
+    pub fn with_len(
+        gl: &'a gl::Gl,
+        len: usize,
+        flags: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, BufferError> {
+        let zeroed = vec![T::zeroed(); len];
+        Self::create(gl, len, zeroed.as_ptr().cast(), flags, label)
+    }
+
+    /// Overwrites the elements starting at `offset`, needs `gl::DYNAMIC_STORAGE_BIT`
+    pub fn set_data(&self, offset: usize, data: &[T]) -> Result<(), BufferError> {
+        self.require_flags(gl::DYNAMIC_STORAGE_BIT, "DYNAMIC_STORAGE_BIT")?;
+        self.check_range(&(offset..offset + data.len()))?;
+
+        unsafe {
+            self.gl.NamedBufferSubData(
+                self.id,
+                (offset * size_of::<T>()) as isize,
+                size_of_val(data) as isize,
+                data.as_ptr().cast(),
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Reads the whole buffer back
+    pub fn read(&self) -> Vec<T> {
+        self.read_range(0..self.len)
+            .expect("The whole buffer is always in range")
+    }
+
+    /// Reads `range` back
+    pub fn read_range(&self, range: Range<usize>) -> Result<Vec<T>, BufferError> {
+        self.check_range(&range)?;
+
+        let mut data = vec![T::zeroed(); range.len()];
+        unsafe {
+            self.gl.GetNamedBufferSubData(
+                self.id,
+                (range.start * size_of::<T>()) as isize,
+                (range.len() * size_of::<T>()) as isize,
+                data.as_mut_ptr().cast(),
+            );
+        }
+
+        Ok(data)
+    }
+
+    /// Copies `range` of this buffer into `target` starting at `target_offset`
+    pub fn copy_to(
+        &self,
+        target: &Buffer<T>,
+        range: Range<usize>,
+        target_offset: usize,
+    ) -> Result<(), BufferError> {
+        self.check_range(&range)?;
+        target.check_range(&(target_offset..target_offset + range.len()))?;
+
+        unsafe {
+            self.gl.CopyNamedBufferSubData(
+                self.id,
+                target.id,
+                (range.start * size_of::<T>()) as isize,
+                (target_offset * size_of::<T>()) as isize,
+                (range.len() * size_of::<T>()) as isize,
+            );
+        }
+
+        Ok(())
+    }
+
+    /// Maps `range` for reading until the returned guard is dropped.
+    ///
+    /// `access` is a combination of `gl::MAP_READ_BIT` and the other `glMapNamedBufferRange`
+    /// flags, it must include `gl::MAP_READ_BIT`, which must be in the storage flags.
+    pub fn map_range(
+        &mut self,
+        range: Range<usize>,
+        access: u32,
+    ) -> Result<MappedBuffer<'_, 'a, T>, BufferError> {
+        if access & gl::MAP_READ_BIT == 0 {
+            return Err(BufferError::MissingAccess("MAP_READ_BIT"));
+        }
+        self.map_guard(range, access)
+    }
+
+    /// Maps `range` for writing until the returned guard is dropped, like
+    /// [`Buffer::map_range`] but `access` must include `gl::MAP_WRITE_BIT`
+    pub fn map_range_mut(
+        &mut self,
+        range: Range<usize>,
+        access: u32,
+    ) -> Result<MappedBufferMut<'_, 'a, T>, BufferError> {
+        if access & gl::MAP_WRITE_BIT == 0 {
+            return Err(BufferError::MissingAccess("MAP_WRITE_BIT"));
+        }
+        self.map_guard(range, access).map(MappedBufferMut)
+    }
+
+    /// Maps the whole buffer for as long as it lives, see [`Buffer::persistent`].
+    ///
+    /// The buffer must have been created with `gl::MAP_PERSISTENT_BIT` (and
+    /// `gl::MAP_COHERENT_BIT` for a coherent mapping).
+    /// Without `gl::MAP_COHERENT_BIT` in `access`, writes have to be made visible
+    /// with [`Buffer::flush_mapped_range`] (and `gl::MAP_FLUSH_EXPLICIT_BIT`).
+    pub fn map_persistent(&mut self, access: u32) -> Result<(), BufferError> {
+        if self.persistent_ptr.is_some() {
+            return Err(BufferError::AlreadyMapped);
+        }
+        self.require_flags(gl::MAP_PERSISTENT_BIT, "MAP_PERSISTENT_BIT")?;
+        if access & gl::MAP_COHERENT_BIT != 0 {
+            self.require_flags(gl::MAP_COHERENT_BIT, "MAP_COHERENT_BIT")?;
+        }
+        self.check_access(access)?;
+
+        let ptr = self.map(&(0..self.len), access | gl::MAP_PERSISTENT_BIT)?;
+        self.persistent_ptr = Some(ptr);
+        self.persistent_access = access;
+
+        Ok(())
+    }
+
+    pub fn unmap_persistent(&mut self) {
+        if self.persistent_ptr.take().is_some() {
+            unsafe {
+                self.gl.UnmapNamedBuffer(self.id);
+            }
+        }
+    }
+
+    /// The persistently mapped contents, see [`Buffer::map_persistent`]
+    ///
+    /// # Safety
+    ///
+    /// The GPU may write the memory at any time. The caller has to make sure it
+    /// doesn't while the slice is alive, e.g. with a fence, and that its writes are
+    /// visible (`gl::MAP_COHERENT_BIT` or a memory barrier).
+    pub unsafe fn persistent(&self) -> Result<&[T], BufferError> {
+        let ptr = self.persistent_ptr(gl::MAP_READ_BIT, "MAP_READ_BIT")?;
+        Ok(std::slice::from_raw_parts(ptr, self.len))
+    }
+
+    /// The persistently mapped contents, for writing
+    ///
+    /// # Safety
+    ///
+    /// The GPU may read or write the memory at any time. The caller has to make sure
+    /// it doesn't access the memory while the slice is alive, e.g. with a fence.
+    pub unsafe fn persistent_mut(&mut self) -> Result<&mut [T], BufferError> {
+        let ptr = self.persistent_ptr(gl::MAP_WRITE_BIT, "MAP_WRITE_BIT")?;
+        Ok(std::slice::from_raw_parts_mut(ptr, self.len))
+    }
+
+    /// Makes writes to `range` of a non-coherent mapping visible to the GPU
+    pub fn flush_mapped_range(&self, range: Range<usize>) -> Result<(), BufferError> {
+        self.check_range(&range)?;
+
+        unsafe {
+            self.gl.FlushMappedNamedBufferRange(
+                self.id,
+                (range.start * size_of::<T>()) as isize,
+                (range.len() * size_of::<T>()) as isize,
+            );
+        }
+
+        Ok(())
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    pub fn len(&self) -> usize {
+        self.len
+    }
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+    /// Size in bytes
+    pub fn size(&self) -> usize {
+        self.len * size_of::<T>()
+    }
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
+
+impl<'a, T: bytemuck::Pod> Buffer<'a, T> {
+    fn create(
+        gl: &'a gl::Gl,
+        len: usize,
+        data: *const std::ffi::c_void,
+        flags: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, BufferError> {
+        if len == 0 || size_of::<T>() == 0 {
+            return Err(BufferError::Empty);
+        }
+
+        let mut id = 0;
+        unsafe {
+            gl.CreateBuffers(1, &mut id);
+            tracing::trace!(
+                "Created Buffer ({}) with {} elements of {} bytes",
+                id,
+                len,
+                size_of::<T>()
+            );
+
+            if let Some(label) = label {
+                gl.ObjectLabel(gl::BUFFER, id, label.len() as i32, label.as_ptr().cast());
+                tracing::trace!("Adding label to Buffer ({}): {}", id, label);
+            }
+
+            gl.NamedBufferStorage(id, (len * size_of::<T>()) as isize, data, flags);
+        }
+
+        Ok(Self {
+            gl,
+            id,
+            len,
+            flags,
+            persistent_ptr: None,
+            persistent_access: 0,
+            label,
+            _marker: PhantomData,
+        })
+    }
+
+    fn map_guard(
+        &mut self,
+        range: Range<usize>,
+        access: u32,
+    ) -> Result<MappedBuffer<'_, 'a, T>, BufferError> {
+        if self.persistent_ptr.is_some() {
+            return Err(BufferError::AlreadyMapped);
+        }
+        self.check_range(&range)?;
+        self.check_access(access)?;
+
+        let ptr = self.map(&range, access)?;
+        Ok(MappedBuffer {
+            buffer: self,
+            ptr,
+            len: range.len(),
+        })
+    }
+
+    fn persistent_ptr(&self, access: u32, name: &'static str) -> Result<*mut T, BufferError> {
+        let ptr = self.persistent_ptr.ok_or(BufferError::NotMapped)?;
+        if self.persistent_access & access == 0 {
+            return Err(BufferError::MissingAccess(name));
+        }
+
+        Ok(ptr)
+    }
+
+    fn map(&self, range: &Range<usize>, access: u32) -> Result<*mut T, BufferError> {
+        let ptr = unsafe {
+            self.gl.MapNamedBufferRange(
+                self.id,
+                (range.start * size_of::<T>()) as isize,
+                (range.len() * size_of::<T>()) as isize,
+                access,
+            )
+        };
+        if ptr.is_null() {
+            return Err(BufferError::MapFailed);
+        }
+
+        Ok(ptr.cast())
+    }
+
+    fn check_range(&self, range: &Range<usize>) -> Result<(), BufferError> {
+        if range.start > range.end || range.end > self.len {
+            return Err(BufferError::OutOfBounds {
+                range: range.clone(),
+                len: self.len,
+            });
+        }
+
+        Ok(())
+    }
+
+    fn check_access(&self, access: u32) -> Result<(), BufferError> {
+        if access & gl::MAP_READ_BIT != 0 {
+            self.require_flags(gl::MAP_READ_BIT, "MAP_READ_BIT")?;
+        }
+        if access & gl::MAP_WRITE_BIT != 0 {
+            self.require_flags(gl::MAP_WRITE_BIT, "MAP_WRITE_BIT")?;
+        }
+
+        Ok(())
+    }
+
+    fn require_flags(&self, flags: u32, name: &'static str) -> Result<(), BufferError> {
+        if self.flags & flags != flags {
+            return Err(BufferError::MissingFlag(name));
+        }
+
+        Ok(())
+    }
+}
+
+impl<T: bytemuck::Pod> Drop for Buffer<'_, T> {
+    fn drop(&mut self) {
+        self.unmap_persistent();
+        unsafe {
+            self.gl.DeleteBuffers(1, &self.id);
+        }
+    }
+}
+
+/// A range of a [`Buffer`] mapped for reading, unmapped on drop
+pub struct MappedBuffer<'b, 'a, T: bytemuck::Pod> {
+    buffer: &'b Buffer<'a, T>,
+    ptr: *mut T,
+    len: usize,
+}
+
+impl<T: bytemuck::Pod> Deref for MappedBuffer<'_, '_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        unsafe { std::slice::from_raw_parts(self.ptr, self.len) }
+    }
+}
+
+/// A range of a [`Buffer`] mapped for writing, unmapped on drop. Without
+/// `gl::MAP_READ_BIT` the contents read through it are undefined.
+pub struct MappedBufferMut<'b, 'a, T: bytemuck::Pod>(MappedBuffer<'b, 'a, T>);
+
+impl<T: bytemuck::Pod> Deref for MappedBufferMut<'_, '_, T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T: bytemuck::Pod> DerefMut for MappedBufferMut<'_, '_, T> {
+    fn deref_mut(&mut self) -> &mut [T] {
+        unsafe { std::slice::from_raw_parts_mut(self.0.ptr, self.0.len) }
+    }
+}
+
+impl<T: bytemuck::Pod> Drop for MappedBuffer<'_, '_, T> {
+    fn drop(&mut self) {
+        unsafe {
+            self.buffer.gl.UnmapNamedBuffer(self.buffer.id);
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum BufferError {
+    #[error("Buffers can't be empty")]
+    Empty,
+    #[error("Range {range:?} is out of bounds for a buffer of {len} elements")]
+    OutOfBounds { range: Range<usize>, len: usize },
+    #[error("Buffer wasn't created with {0}")]
+    MissingFlag(&'static str),
+    #[error("Buffer is already mapped")]
+    AlreadyMapped,
+    #[error("Buffer isn't mapped")]
+    NotMapped,
+    #[error("Buffer wasn't mapped with {0}")]
+    MissingAccess(&'static str),
+    #[error("Failed to map buffer")]
+    MapFailed,
+}
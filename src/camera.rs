@@ -28,6 +28,8 @@ pub struct Camera {
     first_click: bool,
 
     aspect_ratio: f32,
+    near: f32,
+    far: f32,
 
     view_matrix: Mat4,
     proj_matrix: Mat4,
@@ -120,8 +122,60 @@ impl Camera {
         self.proj_view_matrix
     }
 
+    pub fn position(&self) -> Vec3 {
+        self.position
+    }
+
+    pub fn front(&self) -> Vec3 {
+        self.front
+    }
+
+    /// Vertical field of view in degrees
+    pub fn fov(&self) -> f32 {
+        self.zoom
+    }
+
+    pub fn aspect_ratio(&self) -> f32 {
+        self.aspect_ratio
+    }
+
+    pub fn clip_planes(&self) -> (f32, f32) {
+        (self.near, self.far)
+    }
+
     pub fn set_position(&mut self, position: Vec3) {
         self.position = position;
+
+        self.update_camera_matrices();
+    }
+
+    /// Points the camera along `direction`, roll is lost as the camera always stays upright
+    pub fn set_direction(&mut self, direction: Vec3) {
+        let direction = direction.normalize();
+        self.yaw = direction.z.atan2(direction.x).to_degrees();
+        self.pitch = direction.y.asin().to_degrees().clamp(-89.0, 89.0);
+
+        self.update_camera_vectors();
+    }
+
+    /// Sets the vertical field of view in degrees
+    pub fn set_fov(&mut self, fov: f32) {
+        self.zoom = fov;
+
+        self.update_camera_matrices();
+    }
+
+    pub fn set_aspect_ratio(&mut self, aspect_ratio: f32) {
+        self.aspect_ratio = aspect_ratio;
+
+        self.update_camera_matrices();
+    }
+
+    pub fn set_clip_planes(&mut self, near: f32, far: f32) {
+        self.near = near;
+        self.far = far;
+
+        self.update_camera_matrices();
     }
 }
 
@@ -154,7 +208,12 @@ impl Camera {
     }
 
     fn generate_proj_matrix(&self) -> Mat4 {
-        Mat4::perspective_rh_gl(self.zoom.to_radians(), self.aspect_ratio, 0.1, 100.0)
+        Mat4::perspective_rh_gl(
+            self.zoom.to_radians(),
+            self.aspect_ratio,
+            self.near,
+            self.far,
+        )
     }
 }
 
@@ -183,6 +242,8 @@ impl Default for Camera {
             first_click: true,
 
             aspect_ratio: 16.0 / 9.0,
+            near: 0.1,
+            far: 100.0,
 
             // Changed in update_camera_matrices
             view_matrix: Mat4::ZERO,
@@ -0,0 +1,309 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{fs, path::Path};
+
+use ddsfile::{Caps2, D3D10ResourceDimension, Dds, DxgiFormat, FourCC, MiscFlag, PixelFormatFlags};
+use image::ImageError;
+
+use crate::texture::{mip_levels, ColorSpace, TextureError};
+
+const KTX2_MAGIC: [u8; 12] = [
+    0xAB, 0x4B, 0x54, 0x58, 0x20, 0x32, 0x30, 0xBB, 0x0D, 0x0A, 0x1A, 0x0A,
+];
+const DDS_MAGIC: [u8; 4] = *b"DDS ";
+
+/// A block-compressed (BCn) format, uploaded as stored with `glCompressedTextureSubImage2D`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CompressedFormat {
+    pub internal_format: u32,
+    /// Bytes per 4 x 4 block
+    pub block_size: usize,
+    pub channels: u8,
+}
+
+impl CompressedFormat {
+    const fn new(internal_format: u32, block_size: usize, channels: u8) -> Self {
+        Self {
+            internal_format,
+            block_size,
+            channels,
+        }
+    }
+
+    pub const BC1_RGB: Self = Self::new(gl::COMPRESSED_RGB_S3TC_DXT1_EXT, 8, 3);
+    pub const BC1_RGB_SRGB: Self = Self::new(gl::COMPRESSED_SRGB_S3TC_DXT1_EXT, 8, 3);
+    pub const BC1_RGBA: Self = Self::new(gl::COMPRESSED_RGBA_S3TC_DXT1_EXT, 8, 4);
+    pub const BC1_RGBA_SRGB: Self = Self::new(gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT1_EXT, 8, 4);
+    pub const BC2: Self = Self::new(gl::COMPRESSED_RGBA_S3TC_DXT3_EXT, 16, 4);
+    pub const BC2_SRGB: Self = Self::new(gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT3_EXT, 16, 4);
+    pub const BC3: Self = Self::new(gl::COMPRESSED_RGBA_S3TC_DXT5_EXT, 16, 4);
+    pub const BC3_SRGB: Self = Self::new(gl::COMPRESSED_SRGB_ALPHA_S3TC_DXT5_EXT, 16, 4);
+    pub const BC4: Self = Self::new(gl::COMPRESSED_RED_RGTC1, 8, 1);
+    pub const BC4_SIGNED: Self = Self::new(gl::COMPRESSED_SIGNED_RED_RGTC1, 8, 1);
+    pub const BC5: Self = Self::new(gl::COMPRESSED_RG_RGTC2, 16, 2);
+    pub const BC5_SIGNED: Self = Self::new(gl::COMPRESSED_SIGNED_RG_RGTC2, 16, 2);
+    pub const BC6H: Self = Self::new(gl::COMPRESSED_RGB_BPTC_UNSIGNED_FLOAT, 16, 3);
+    pub const BC6H_SIGNED: Self = Self::new(gl::COMPRESSED_RGB_BPTC_SIGNED_FLOAT, 16, 3);
+    pub const BC7: Self = Self::new(gl::COMPRESSED_RGBA_BPTC_UNORM, 16, 4);
+    pub const BC7_SRGB: Self = Self::new(gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM, 16, 4);
+
+    pub fn from_ktx2(format: ktx2::Format) -> Result<Self, TextureError> {
+        use ktx2::Format;
+
+        Ok(match format {
+            Format::BC1_RGB_UNORM_BLOCK => Self::BC1_RGB,
+            Format::BC1_RGB_SRGB_BLOCK => Self::BC1_RGB_SRGB,
+            Format::BC1_RGBA_UNORM_BLOCK => Self::BC1_RGBA,
+            Format::BC1_RGBA_SRGB_BLOCK => Self::BC1_RGBA_SRGB,
+            Format::BC2_UNORM_BLOCK => Self::BC2,
+            Format::BC2_SRGB_BLOCK => Self::BC2_SRGB,
+            Format::BC3_UNORM_BLOCK => Self::BC3,
+            Format::BC3_SRGB_BLOCK => Self::BC3_SRGB,
+            Format::BC4_UNORM_BLOCK => Self::BC4,
+            Format::BC4_SNORM_BLOCK => Self::BC4_SIGNED,
+            Format::BC5_UNORM_BLOCK => Self::BC5,
+            Format::BC5_SNORM_BLOCK => Self::BC5_SIGNED,
+            Format::BC6H_UFLOAT_BLOCK => Self::BC6H,
+            Format::BC6H_SFLOAT_BLOCK => Self::BC6H_SIGNED,
+            Format::BC7_UNORM_BLOCK => Self::BC7,
+            Format::BC7_SRGB_BLOCK => Self::BC7_SRGB,
+            _ => {
+                return Err(TextureError::UnsupportedCompressedFormat(format!(
+                    "KTX2 {format:?}"
+                )))
+            }
+        })
+    }
+
+    /// `color_space` only applies to the typeless formats, the others say which they are
+    pub fn from_dxgi(format: DxgiFormat, color_space: ColorSpace) -> Result<Self, TextureError> {
+        let srgb = color_space == ColorSpace::Srgb;
+        Ok(match format {
+            DxgiFormat::BC1_Typeless if srgb => Self::BC1_RGBA_SRGB,
+            DxgiFormat::BC1_Typeless | DxgiFormat::BC1_UNorm => Self::BC1_RGBA,
+            DxgiFormat::BC1_UNorm_sRGB => Self::BC1_RGBA_SRGB,
+            DxgiFormat::BC2_Typeless if srgb => Self::BC2_SRGB,
+            DxgiFormat::BC2_Typeless | DxgiFormat::BC2_UNorm => Self::BC2,
+            DxgiFormat::BC2_UNorm_sRGB => Self::BC2_SRGB,
+            DxgiFormat::BC3_Typeless if srgb => Self::BC3_SRGB,
+            DxgiFormat::BC3_Typeless | DxgiFormat::BC3_UNorm => Self::BC3,
+            DxgiFormat::BC3_UNorm_sRGB => Self::BC3_SRGB,
+            DxgiFormat::BC4_Typeless | DxgiFormat::BC4_UNorm => Self::BC4,
+            DxgiFormat::BC4_SNorm => Self::BC4_SIGNED,
+            DxgiFormat::BC5_Typeless | DxgiFormat::BC5_UNorm => Self::BC5,
+            DxgiFormat::BC5_SNorm => Self::BC5_SIGNED,
+            DxgiFormat::BC6H_Typeless | DxgiFormat::BC6H_UF16 => Self::BC6H,
+            DxgiFormat::BC6H_SF16 => Self::BC6H_SIGNED,
+            DxgiFormat::BC7_Typeless if srgb => Self::BC7_SRGB,
+            DxgiFormat::BC7_Typeless | DxgiFormat::BC7_UNorm => Self::BC7,
+            DxgiFormat::BC7_UNorm_sRGB => Self::BC7_SRGB,
+            _ => {
+                return Err(TextureError::UnsupportedCompressedFormat(format!(
+                    "DDS {format:?}"
+                )))
+            }
+        })
+    }
+
+    /// The formats of DDS files without a DX10 header, which can't say whether they're sRGB
+    fn from_fourcc(
+        fourcc: u32,
+        alpha: bool,
+        color_space: ColorSpace,
+    ) -> Result<Self, TextureError> {
+        let srgb = color_space == ColorSpace::Srgb;
+        Ok(match fourcc {
+            FourCC::DXT1 if alpha && srgb => Self::BC1_RGBA_SRGB,
+            FourCC::DXT1 if alpha => Self::BC1_RGBA,
+            FourCC::DXT1 if srgb => Self::BC1_RGB_SRGB,
+            FourCC::DXT1 => Self::BC1_RGB,
+            FourCC::DXT3 if srgb => Self::BC2_SRGB,
+            FourCC::DXT3 => Self::BC2,
+            FourCC::DXT5 if srgb => Self::BC3_SRGB,
+            FourCC::DXT5 => Self::BC3,
+            FourCC::ATI1 | FourCC::BC4_UNORM => Self::BC4,
+            FourCC::BC4_SNORM => Self::BC4_SIGNED,
+            FourCC::ATI2 => Self::BC5,
+            FourCC::BC5_SNORM => Self::BC5_SIGNED,
+            _ => {
+                let name = fourcc.to_le_bytes().map(char::from);
+                return Err(TextureError::UnsupportedCompressedFormat(format!(
+                    "DDS FourCC {:?}",
+                    String::from_iter(name)
+                )));
+            }
+        })
+    }
+
+    /// The size of a level in bytes, partial blocks at the edges are stored whole
+    pub fn level_size(&self, (width, height): (u32, u32)) -> usize {
+        let blocks = |pixels: u32| pixels.div_ceil(4).max(1) as usize;
+        blocks(width) * blocks(height) * self.block_size
+    }
+}
+
+/// The stored mip levels of a 2D KTX2 or DDS texture, from the full size down
+#[derive(Clone, Debug)]
+pub struct CompressedImage {
+    size: (u32, u32),
+    format: CompressedFormat,
+    levels: Vec<Vec<u8>>,
+}
+
+impl CompressedImage {
+    /// Checks that there's a level of the right size for every mip
+    pub fn new(
+        size: (u32, u32),
+        format: CompressedFormat,
+        levels: Vec<Vec<u8>>,
+    ) -> Result<Self, TextureError> {
+        if levels.is_empty() || levels.len() as u32 > mip_levels(size) {
+            return Err(TextureError::WrongSizedData);
+        }
+        for (level, data) in levels.iter().enumerate() {
+            if data.len() != format.level_size(level_size(size, level as u32)) {
+                return Err(TextureError::WrongSizedData);
+            }
+        }
+
+        Ok(Self {
+            size,
+            format,
+            levels,
+        })
+    }
+
+    /// Parses a KTX2 or DDS file, told apart by their magic numbers. `color_space`
+    /// only applies to DDS files that don't say, see [`CompressedFormat::from_dxgi`].
+    pub fn from_memory(data: &[u8], color_space: ColorSpace) -> Result<Self, TextureError> {
+        if data.starts_with(&KTX2_MAGIC) {
+            Self::from_ktx2(data)
+        } else if data.starts_with(&DDS_MAGIC) {
+            Self::from_dds(data, color_space)
+        } else {
+            Err(TextureError::UnknownContainer)
+        }
+    }
+
+    pub fn from_file(path: &Path, color_space: ColorSpace) -> Result<Self, TextureError> {
+        let data = fs::read(path).map_err(ImageError::from)?;
+
+        Self::from_memory(&data, color_space)
+    }
+
+    pub fn from_ktx2(data: &[u8]) -> Result<Self, TextureError> {
+        let reader = ktx2::Reader::new(data)?;
+        let header = reader.header();
+
+        if let Some(scheme) = header.supercompression_scheme {
+            return Err(TextureError::UnsupportedCompressedFormat(format!(
+                "KTX2 with {scheme:?} supercompression"
+            )));
+        }
+        if header.face_count != 1 {
+            return Err(TextureError::Not2d(format!("{} faces", header.face_count)));
+        }
+        if header.layer_count > 0 {
+            return Err(TextureError::Not2d(format!(
+                "{} layers",
+                header.layer_count
+            )));
+        }
+        if header.pixel_depth > 0 {
+            return Err(TextureError::Not2d(format!(
+                "a depth of {}",
+                header.pixel_depth
+            )));
+        }
+        if header.pixel_height == 0 {
+            return Err(TextureError::Not2d("no height".to_owned()));
+        }
+
+        let format = header.format.ok_or_else(|| {
+            TextureError::UnsupportedCompressedFormat("KTX2 without a Vulkan format".to_owned())
+        })?;
+        let format = CompressedFormat::from_ktx2(format)?;
+        let levels = reader.levels().map(|level| level.data.to_vec()).collect();
+
+        Self::new((header.pixel_width, header.pixel_height), format, levels)
+    }
+
+    pub fn from_dds(data: &[u8], color_space: ColorSpace) -> Result<Self, TextureError> {
+        let dds = Dds::read(data)?;
+
+        let cubemap = match &dds.header10 {
+            Some(header10) => header10.misc_flag.contains(MiscFlag::TEXTURECUBE),
+            None => dds.header.caps2.contains(Caps2::CUBEMAP),
+        };
+        if cubemap {
+            return Err(TextureError::Not2d("6 faces".to_owned()));
+        }
+        if let Some(header10) = &dds.header10 {
+            if header10.resource_dimension != D3D10ResourceDimension::Texture2D {
+                return Err(TextureError::Not2d(format!(
+                    "a {:?} resource dimension",
+                    header10.resource_dimension
+                )));
+            }
+        }
+        if dds.get_depth() > 1 {
+            return Err(TextureError::Not2d(format!(
+                "a depth of {}",
+                dds.get_depth()
+            )));
+        }
+        if dds.get_num_array_layers() > 1 {
+            let layers = dds.get_num_array_layers();
+            return Err(TextureError::Not2d(format!("{layers} layers")));
+        }
+
+        let format = match (&dds.header10, &dds.header.spf.fourcc) {
+            (Some(header10), _) => CompressedFormat::from_dxgi(header10.dxgi_format, color_space)?,
+            (None, Some(FourCC(fourcc))) => {
+                let alpha = dds
+                    .header
+                    .spf
+                    .flags
+                    .contains(PixelFormatFlags::ALPHA_PIXELS);
+                CompressedFormat::from_fourcc(*fourcc, alpha, color_space)?
+            }
+            (None, None) => {
+                return Err(TextureError::UnsupportedCompressedFormat(
+                    "uncompressed DDS".to_owned(),
+                ))
+            }
+        };
+
+        let size = (dds.get_width(), dds.get_height());
+        let mut rest = dds.data.as_slice();
+        let mut levels = Vec::new();
+        for level in 0..dds.get_num_mipmap_levels().max(1) {
+            let length = format.level_size(level_size(size, level));
+            if rest.len() < length {
+                return Err(TextureError::WrongSizedData);
+            }
+            let (data, next) = rest.split_at(length);
+            levels.push(data.to_vec());
+            rest = next;
+        }
+
+        Self::new(size, format, levels)
+    }
+
+    /// The size of level 0
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+    pub fn format(&self) -> CompressedFormat {
+        self.format
+    }
+    pub fn levels(&self) -> &[Vec<u8>] {
+        &self.levels
+    }
+}
+
+/// The size of mip `level` of an image of `size`
+pub fn level_size((width, height): (u32, u32), level: u32) -> (u32, u32) {
+    ((width >> level).max(1), (height >> level).max(1))
+}
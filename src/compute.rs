@@ -0,0 +1,146 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::mem::size_of;
+
+use crate::{
+    buffer::Buffer,
+    preprocessor::{Defines, ShaderSource},
+    program::{Program, ProgramError},
+    spirv::SpirvShader,
+};
+
+/// The layout `glDispatchComputeIndirect` reads from `GL_DISPATCH_INDIRECT_BUFFER`
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, bytemuck::Pod, bytemuck::Zeroable)]
+pub struct DispatchIndirectCommand {
+    pub num_groups_x: u32,
+    pub num_groups_y: u32,
+    pub num_groups_z: u32,
+}
+
+impl DispatchIndirectCommand {
+    pub fn new(groups: [u32; 3]) -> Self {
+        let [num_groups_x, num_groups_y, num_groups_z] = groups;
+        Self {
+            num_groups_x,
+            num_groups_y,
+            num_groups_z,
+        }
+    }
+}
+
+/// A program with a single compute shader
+pub struct ComputeProgram<'a> {
+    gl: &'a gl::Gl,
+    program: Program<'a>,
+    local_size: [u32; 3],
+}
+
+impl<'a> ComputeProgram<'a> {
+    pub fn from_source(
+        gl: &'a gl::Gl,
+        source: &str,
+        defines: &Defines,
+        label: Option<&'a str>,
+    ) -> Result<Self, ProgramError> {
+        Self::from_preprocessed(gl, &source.into(), defines, label)
+    }
+
+    pub fn from_preprocessed(
+        gl: &'a gl::Gl,
+        source: &ShaderSource,
+        defines: &Defines,
+        label: Option<&'a str>,
+    ) -> Result<Self, ProgramError> {
+        let program = Program::link(gl, &[(gl::COMPUTE_SHADER, source)], defines, label, None)?;
+
+        Ok(Self::from_program(gl, program))
+    }
+
+    /// Needs `GL_ARB_gl_spirv`, see [`Program::from_spirv`]
+    pub fn from_spirv(
+        gl: &'a gl::Gl,
+        shader: &SpirvShader,
+        label: Option<&'a str>,
+    ) -> Result<Self, ProgramError> {
+        let program = Program::from_spirv(gl, &[(gl::COMPUTE_SHADER, shader)], label)?;
+
+        Ok(Self::from_program(gl, program))
+    }
+
+    fn from_program(gl: &'a gl::Gl, program: Program<'a>) -> Self {
+        let mut local_size = [0; 3];
+        unsafe {
+            gl.GetProgramiv(
+                program.id(),
+                gl::COMPUTE_WORK_GROUP_SIZE,
+                local_size.as_mut_ptr(),
+            );
+        }
+        let local_size = local_size.map(|size| size as u32);
+        tracing::trace!(
+            "Created ComputeProgram ({}) with local size {:?}",
+            program.id(),
+            local_size
+        );
+
+        Self {
+            gl,
+            program,
+            local_size,
+        }
+    }
+
+    /// How many work groups cover `invocations`, rounding up
+    pub fn groups_for(&self, invocations: [u32; 3]) -> [u32; 3] {
+        [0, 1, 2].map(|i| invocations[i].div_ceil(self.local_size[i]))
+    }
+
+    /// Binds the program and runs `groups` work groups
+    pub unsafe fn dispatch(&self, groups: [u32; 3]) {
+        self.program.bind();
+        self.gl.DispatchCompute(groups[0], groups[1], groups[2]);
+    }
+
+    /// Binds the program and runs the work groups from `commands[index]`
+    pub unsafe fn dispatch_indirect(
+        &self,
+        commands: &Buffer<DispatchIndirectCommand>,
+        index: usize,
+    ) {
+        assert!(index < commands.len(), "Indirect command out of bounds");
+
+        self.program.bind();
+        self.gl
+            .BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, commands.id());
+        self.gl
+            .DispatchComputeIndirect((index * size_of::<DispatchIndirectCommand>()) as isize);
+        self.gl.BindBuffer(gl::DISPATCH_INDIRECT_BUFFER, 0);
+    }
+
+    /// Makes the shader's writes visible, `barriers` are `glMemoryBarrier` bits
+    /// like `gl::SHADER_STORAGE_BARRIER_BIT` describing how they'll be read next
+    pub unsafe fn memory_barrier(&self, barriers: u32) {
+        self.gl.MemoryBarrier(barriers);
+    }
+
+    /// Like [`ComputeProgram::memory_barrier`], only for fragment shader reads of
+    /// what's being rendered
+    pub unsafe fn memory_barrier_by_region(&self, barriers: u32) {
+        self.gl.MemoryBarrierByRegion(barriers);
+    }
+
+    pub fn program(&self) -> &Program<'a> {
+        &self.program
+    }
+    pub fn local_size(&self) -> [u32; 3] {
+        self.local_size
+    }
+    pub fn id(&self) -> u32 {
+        self.program.id()
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.program.label()
+    }
+}
@@ -0,0 +1,278 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{fmt, path::Path};
+
+use image::DynamicImage;
+use tracing::instrument;
+
+use crate::{
+    compute::ComputeProgram,
+    preprocessor::Defines,
+    program::ProgramError,
+    sampler::{SamplerDesc, Target, Wrap},
+    texture::{
+        mip_levels, open_image, with_tight_unpacking, ColorSpace, PixelFormat, Texture,
+        TextureError,
+    },
+};
+
+const EQUIRECT_TO_CUBE: &str = include_str!("shaders/equirect_to_cube.comp");
+
+/// The faces in layer order, as passed to [`Cubemap::from_images`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CubeFace {
+    PositiveX,
+    NegativeX,
+    PositiveY,
+    NegativeY,
+    PositiveZ,
+    NegativeZ,
+}
+
+impl CubeFace {
+    pub const ALL: [Self; 6] = [
+        Self::PositiveX,
+        Self::NegativeX,
+        Self::PositiveY,
+        Self::NegativeY,
+        Self::PositiveZ,
+        Self::NegativeZ,
+    ];
+}
+
+impl fmt::Display for CubeFace {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::PositiveX => write!(f, "+X"),
+            Self::NegativeX => write!(f, "-X"),
+            Self::PositiveY => write!(f, "+Y"),
+            Self::NegativeY => write!(f, "-Y"),
+            Self::PositiveZ => write!(f, "+Z"),
+            Self::NegativeZ => write!(f, "-Z"),
+        }
+    }
+}
+
+/// A `GL_TEXTURE_CUBE_MAP` with a full mip chain, sampled with a direction
+pub struct Cubemap<'a> {
+    gl: &'a gl::Gl,
+    id: u32,
+    size: u32,
+    internal_format: u32,
+    levels: u32,
+    label: Option<&'a str>,
+}
+
+impl<'a> Cubemap<'a> {
+    /// Creates the storage for `size` x `size` faces and enables seamless filtering
+    fn new(gl: &'a gl::Gl, size: u32, internal_format: u32, label: Option<&'a str>) -> Self {
+        let levels = mip_levels((size, size));
+        let mut id = 0;
+
+        unsafe {
+            gl.CreateTextures(gl::TEXTURE_CUBE_MAP, 1, &mut id);
+            tracing::trace!(
+                "Created Cubemap ({}) ({} x {}) with internal format {:#x}",
+                id,
+                size,
+                size,
+                internal_format
+            );
+
+            if let Some(label) = label {
+                gl.ObjectLabel(gl::TEXTURE, id, label.len() as i32, label.as_ptr().cast());
+                tracing::trace!("Adding label to Cubemap ({}): {}", id, label);
+            }
+
+            SamplerDesc::default()
+                .with_wrap(Wrap::ClampToEdge)
+                .apply(gl, Target::Texture(id));
+            gl.TextureStorage2D(id, levels as i32, internal_format, size as i32, size as i32);
+
+            // Filters across face edges, it's global state but there's no reason to turn it off
+            gl.Enable(gl::TEXTURE_CUBE_MAP_SEAMLESS);
+        }
+
+        Self {
+            gl,
+            id,
+            size,
+            internal_format,
+            levels,
+            label,
+        }
+    }
+
+    /// Uploads six square faces of the same size and type, in [`CubeFace::ALL`] order
+    pub fn from_images(
+        gl: &'a gl::Gl,
+        faces: &[DynamicImage; 6],
+        color_space: ColorSpace,
+        label: Option<&'a str>,
+    ) -> Result<Self, CubemapError> {
+        let size = faces[0].width();
+        let format = PixelFormat::of(&faces[0], color_space)?;
+        for (face, image) in CubeFace::ALL.into_iter().zip(faces) {
+            if (image.width(), image.height()) != (size, size) {
+                return Err(CubemapError::FaceSize {
+                    face,
+                    size: (image.width(), image.height()),
+                    expected: size,
+                });
+            }
+            if PixelFormat::of(image, color_space)? != format {
+                return Err(CubemapError::FaceFormat(face));
+            }
+        }
+
+        let cubemap = Self::new(gl, size, format.internal_format, label);
+        unsafe {
+            with_tight_unpacking(gl, || {
+                for (layer, image) in faces.iter().enumerate() {
+                    gl.TextureSubImage3D(
+                        cubemap.id,
+                        0,
+                        0,
+                        0,
+                        layer as i32,
+                        size as i32,
+                        size as i32,
+                        1,
+                        format.format,
+                        format.data_type,
+                        image.as_bytes().as_ptr().cast(),
+                    );
+                }
+            });
+            gl.GenerateTextureMipmap(cubemap.id);
+        }
+
+        Ok(cubemap)
+    }
+
+    #[instrument(skip(gl))]
+    pub fn from_files<P: AsRef<Path> + fmt::Debug>(
+        gl: &'a gl::Gl,
+        faces: &[P; 6],
+        color_space: ColorSpace,
+        label: Option<&'a str>,
+    ) -> Result<Self, CubemapError> {
+        let mut images = Vec::with_capacity(6);
+        for path in faces {
+            images.push(open_image(path.as_ref())?);
+        }
+        let images: [DynamicImage; 6] = images.try_into().expect("There are six faces");
+
+        Self::from_images(gl, &images, color_space, label)
+    }
+
+    /// Projects an equirectangular panorama onto `size` x `size` `RGBA16F` faces
+    /// on the GPU. The top of the image is +Y and its center faces +X.
+    pub fn from_equirect(
+        gl: &'a gl::Gl,
+        image: &DynamicImage,
+        size: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, CubemapError> {
+        let equirect = Texture::from_image(gl, image, ColorSpace::Linear, None)?;
+        // Wraps around horizontally, but not over the poles
+        equirect.set_sampler(&SamplerDesc {
+            wrap: [Wrap::Repeat, Wrap::ClampToEdge, Wrap::ClampToEdge],
+            mip_filter: None,
+            ..SamplerDesc::default()
+        });
+        let program = ComputeProgram::from_source(
+            gl,
+            EQUIRECT_TO_CUBE,
+            &Defines::new(),
+            Some("Equirect to cubemap"),
+        )?;
+
+        let cubemap = Self::new(gl, size, gl::RGBA16F, label);
+        unsafe {
+            equirect.bind(0);
+            gl.BindImageTexture(0, cubemap.id, 0, gl::TRUE, 0, gl::WRITE_ONLY, gl::RGBA16F);
+            program.dispatch(program.groups_for([size, size, 6]));
+            gl.BindImageTexture(0, 0, 0, gl::FALSE, 0, gl::WRITE_ONLY, gl::RGBA16F);
+            equirect.unbind(0);
+
+            gl.MemoryBarrier(gl::TEXTURE_FETCH_BARRIER_BIT | gl::TEXTURE_UPDATE_BARRIER_BIT);
+            gl.GenerateTextureMipmap(cubemap.id);
+        }
+        tracing::trace!(
+            "Cubemap ({}): Converted a {} x {} equirect",
+            cubemap.id,
+            image.width(),
+            image.height()
+        );
+
+        Ok(cubemap)
+    }
+
+    /// Loads an equirectangular image, e.g. an `.hdr`, see [`Cubemap::from_equirect`]
+    #[instrument(skip(gl))]
+    pub fn from_equirect_file(
+        gl: &'a gl::Gl,
+        path: impl AsRef<Path> + fmt::Debug,
+        size: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, CubemapError> {
+        let image = open_image(path.as_ref())?;
+
+        Self::from_equirect(gl, &image, size, label)
+    }
+
+    pub fn set_sampler(&self, desc: &SamplerDesc) {
+        unsafe { desc.apply(self.gl, Target::Texture(self.id)) };
+    }
+
+    pub unsafe fn bind(&self, slot: u32) {
+        self.gl.BindTextureUnit(slot, self.id);
+    }
+
+    pub unsafe fn unbind(&self, slot: u32) {
+        self.gl.BindTextureUnit(slot, 0);
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    /// The width and height of every face
+    pub fn size(&self) -> u32 {
+        self.size
+    }
+    pub fn internal_format(&self) -> u32 {
+        self.internal_format
+    }
+    pub fn levels(&self) -> u32 {
+        self.levels
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
+
+impl Drop for Cubemap<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum CubemapError {
+    #[error("Texture error: {0}")]
+    TextureError(#[from] TextureError),
+    #[error("Program error: {0}")]
+    ProgramError(#[from] ProgramError),
+    #[error("The {face} face is {}x{}, expected {expected}x{expected}", size.0, size.1)]
+    FaceSize {
+        face: CubeFace,
+        size: (u32, u32),
+        expected: u32,
+    },
+    #[error("The {0} face has a different pixel format than the +X face")]
+    FaceFormat(CubeFace),
+}
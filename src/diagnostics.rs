@@ -0,0 +1,321 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{collections::HashMap, fmt, path::PathBuf};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    Error,
+    Warning,
+    Note,
+}
+
+impl Severity {
+    fn parse(word: &str) -> Option<Self> {
+        match word.trim().to_ascii_lowercase().as_str() {
+            // Mesa's preprocessor has its own prefix
+            "error" | "fatal error" | "preprocessor error" => Some(Self::Error),
+            "warning" | "preprocessor warning" => Some(Self::Warning),
+            "note" | "info" => Some(Self::Note),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for Severity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Error => write!(f, "error"),
+            Self::Warning => write!(f, "warning"),
+            Self::Note => write!(f, "note"),
+        }
+    }
+}
+
+/// A parsed shader info log, displayed rendered like rustc
+#[derive(Clone, Debug)]
+pub struct CompileLog {
+    pub diagnostics: Vec<Diagnostic>,
+    /// The info log as the driver wrote it
+    pub raw: String,
+    rendered: String,
+}
+
+impl CompileLog {
+    /// `source` is what was handed to the driver and `files` are the files behind its
+    /// source string numbers, `name` is used for diagnostics without a file
+    pub fn new(raw: String, source: &str, files: &[PathBuf], name: &str) -> Self {
+        let mut diagnostics = parse_log(&raw);
+        for diagnostic in &mut diagnostics {
+            diagnostic.file = files.get(diagnostic.source_string as usize).cloned();
+        }
+        let rendered = render(&diagnostics, &SourceLines::new(source), name);
+
+        Self {
+            diagnostics,
+            raw,
+            rendered,
+        }
+    }
+
+    pub fn errors(&self) -> impl Iterator<Item = &Diagnostic> {
+        self.diagnostics
+            .iter()
+            .filter(|diagnostic| diagnostic.severity == Severity::Error)
+    }
+
+    pub fn rendered(&self) -> &str {
+        &self.rendered
+    }
+}
+
+impl fmt::Display for CompileLog {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.diagnostics.is_empty() {
+            true => write!(f, "{}", self.raw.trim_end()),
+            false => write!(f, "{}", self.rendered.trim_end()),
+        }
+    }
+}
+
+/// One message from a shader info log
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// The file behind `source_string`, if the shader was loaded from files
+    pub file: Option<PathBuf>,
+    pub source_string: u32,
+    /// 1-based, `None` when the driver didn't give a position
+    pub line: Option<u32>,
+    /// 1-based, only Mesa reports columns
+    pub column: Option<u32>,
+    pub severity: Severity,
+    pub message: String,
+}
+
+/// Parses a shader info log in the Mesa, NVIDIA or AMD format:
+///
+/// ```text
+/// 0:12(5): error: `foo' undeclared
+/// 0:12(1): preprocessor error: #endif without #if
+/// 0(12) : error C1008: undefined variable "foo"
+/// ERROR: 0:12: 'foo' : undeclared identifier
+/// ```
+///
+/// Lines that don't match any of them are added to the previous message.
+pub fn parse_log(log: &str) -> Vec<Diagnostic> {
+    let mut diagnostics: Vec<Diagnostic> = Vec::new();
+
+    for line in log.lines().map(str::trim_end) {
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        match parse_mesa(line)
+            .or_else(|| parse_nvidia(line))
+            .or_else(|| parse_amd(line))
+        {
+            Some(diagnostic) => diagnostics.push(diagnostic),
+            None => match diagnostics.last_mut() {
+                Some(last) => {
+                    last.message.push('\n');
+                    last.message.push_str(line.trim());
+                }
+                None => diagnostics.push(Diagnostic {
+                    file: None,
+                    source_string: 0,
+                    line: None,
+                    column: None,
+                    severity: Severity::Error,
+                    message: line.trim().to_owned(),
+                }),
+            },
+        }
+    }
+
+    diagnostics
+}
+
+/// `0:12(5): error: message`, or `preprocessor error` from glcpp
+fn parse_mesa(line: &str) -> Option<Diagnostic> {
+    let (source_string, rest) = line.split_once(':')?;
+    let (line_number, rest) = rest.split_once('(')?;
+    let (column, rest) = rest.split_once("):")?;
+    let (severity, message) = rest.split_once(':')?;
+
+    Some(Diagnostic {
+        file: None,
+        source_string: source_string.parse().ok()?,
+        line: Some(line_number.parse().ok()?),
+        column: Some(column.parse().ok()?),
+        severity: Severity::parse(severity)?,
+        message: message.trim().to_owned(),
+    })
+}
+
+/// `0(12) : error C1008: message`
+fn parse_nvidia(line: &str) -> Option<Diagnostic> {
+    let (source_string, rest) = line.split_once('(')?;
+    let (line_number, rest) = rest.split_once(')')?;
+    let (severity, message) = rest.trim_start().strip_prefix(':')?.split_once(':')?;
+    // Drop the error code
+    let severity = severity.split_whitespace().next()?;
+
+    Some(Diagnostic {
+        file: None,
+        source_string: source_string.trim().parse().ok()?,
+        line: Some(line_number.parse().ok()?),
+        column: None,
+        severity: Severity::parse(severity)?,
+        message: message.trim().to_owned(),
+    })
+}
+
+/// `ERROR: 0:12: message`, or `error: message` without a position
+fn parse_amd(line: &str) -> Option<Diagnostic> {
+    let (severity, rest) = line.split_once(':')?;
+    let severity = Severity::parse(severity)?;
+
+    let position = rest
+        .trim_start()
+        .split_once(':')
+        .and_then(|(source_string, rest)| {
+            let (line_number, message) = rest.split_once(':')?;
+            Some((
+                source_string.parse().ok()?,
+                line_number.parse().ok()?,
+                message,
+            ))
+        });
+
+    Some(match position {
+        Some((source_string, line_number, message)) => Diagnostic {
+            file: None,
+            source_string,
+            line: Some(line_number),
+            column: None,
+            severity,
+            message: message.trim().to_owned(),
+        },
+        None => Diagnostic {
+            file: None,
+            source_string: 0,
+            line: None,
+            column: None,
+            severity,
+            message: rest.trim().to_owned(),
+        },
+    })
+}
+
+/// The lines of the original files, recovered from a source with `#line` directives
+pub struct SourceLines<'s> {
+    lines: HashMap<(u32, u32), &'s str>,
+}
+
+impl<'s> SourceLines<'s> {
+    /// `source` is what was handed to the driver
+    pub fn new(source: &'s str) -> Self {
+        let mut lines = HashMap::new();
+        let (mut source_string, mut line_number) = (0, 1);
+
+        for line in source.lines() {
+            let directive = line
+                .trim_start()
+                .strip_prefix('#')
+                .and_then(|directive| directive.trim_start().strip_prefix("line"));
+            if let Some(directive) = directive {
+                let mut numbers = directive.split_whitespace().map(str::parse::<u32>);
+                if let Some(Ok(line)) = numbers.next() {
+                    line_number = line;
+                    if let Some(Ok(string)) = numbers.next() {
+                        source_string = string;
+                    }
+                    continue;
+                }
+            }
+
+            lines.insert((source_string, line_number), line);
+            line_number += 1;
+        }
+
+        Self { lines }
+    }
+
+    pub fn get(&self, source_string: u32, line: u32) -> Option<&'s str> {
+        self.lines.get(&(source_string, line)).copied()
+    }
+}
+
+/// Renders `diagnostics` like rustc, with the offending line and a caret under the column.
+///
+/// `name` stands in for the file of diagnostics that don't have one.
+pub fn render(diagnostics: &[Diagnostic], lines: &SourceLines, name: &str) -> String {
+    let mut output = String::new();
+
+    for diagnostic in diagnostics {
+        output.push_str(&format!(
+            "{}: {}\n",
+            diagnostic.severity, diagnostic.message
+        ));
+
+        let Some(line_number) = diagnostic.line else {
+            continue;
+        };
+        let file = diagnostic
+            .file
+            .as_ref()
+            .map(|file| file.display().to_string())
+            .unwrap_or_else(|| name.to_owned());
+        let gutter = " ".repeat(line_number.to_string().len());
+
+        match diagnostic.column {
+            Some(column) => output.push_str(&format!(
+                "{}--> {}:{}:{}\n",
+                gutter, file, line_number, column
+            )),
+            None => output.push_str(&format!("{}--> {}:{}\n", gutter, file, line_number)),
+        }
+
+        let Some(source_line) = lines.get(diagnostic.source_string, line_number) else {
+            output.push('\n');
+            continue;
+        };
+        // Tabs would throw the caret off
+        let source_line = source_line.replace('\t', "    ");
+        let tabs_before = |column: usize| {
+            lines
+                .get(diagnostic.source_string, line_number)
+                .unwrap_or_default()
+                .chars()
+                .take(column)
+                .filter(|&c| c == '\t')
+                .count()
+        };
+
+        let (offset, width) = match diagnostic.column {
+            Some(column) => {
+                let column = column.saturating_sub(1) as usize;
+                (column + tabs_before(column) * 3, 1)
+            }
+            // Underline the whole line
+            None => {
+                let trimmed = source_line.trim_start();
+                (
+                    source_line.len() - trimmed.len(),
+                    trimmed.trim_end().len().max(1),
+                )
+            }
+        };
+
+        output.push_str(&format!("{} |\n", gutter));
+        output.push_str(&format!("{} | {}\n", line_number, source_line.trim_end()));
+        output.push_str(&format!(
+            "{} | {}{}\n\n",
+            gutter,
+            " ".repeat(offset),
+            "^".repeat(width)
+        ));
+    }
+
+    output
+}
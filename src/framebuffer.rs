@@ -0,0 +1,421 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use crate::texture::{Texture, TextureError};
+
+/// What kind of object backs a framebuffer attachment
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AttachmentKind {
+    /// Can be sampled later (render-to-texture)
+    Texture,
+    /// Can only be rendered to, read back or blitted
+    Renderbuffer,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AttachmentDesc {
+    pub internal_format: u32,
+    pub kind: AttachmentKind,
+}
+
+impl AttachmentDesc {
+    pub const fn texture(internal_format: u32) -> Self {
+        Self {
+            internal_format,
+            kind: AttachmentKind::Texture,
+        }
+    }
+
+    pub const fn renderbuffer(internal_format: u32) -> Self {
+        Self {
+            internal_format,
+            kind: AttachmentKind::Renderbuffer,
+        }
+    }
+}
+
+pub enum Attachment<'a> {
+    Texture(Texture<'a>),
+    Renderbuffer(Renderbuffer<'a>),
+}
+
+impl<'a> Attachment<'a> {
+    fn new(
+        gl: &'a gl::Gl,
+        size: (u32, u32),
+        desc: &AttachmentDesc,
+        label: Option<String>,
+    ) -> Result<Self, FramebufferError> {
+        let attachment = match desc.kind {
+            AttachmentKind::Texture => {
+                Self::Texture(Texture::with_storage(gl, size, desc.internal_format, None)?)
+            }
+            AttachmentKind::Renderbuffer => {
+                Self::Renderbuffer(Renderbuffer::new(gl, size, desc.internal_format, None))
+            }
+        };
+
+        if let Some(label) = label {
+            let identifier = match attachment {
+                Self::Texture(..) => gl::TEXTURE,
+                Self::Renderbuffer(..) => gl::RENDERBUFFER,
+            };
+            unsafe {
+                gl.ObjectLabel(
+                    identifier,
+                    attachment.id(),
+                    label.len() as i32,
+                    label.as_ptr().cast(),
+                );
+            }
+        }
+
+        Ok(attachment)
+    }
+
+    pub fn id(&self) -> u32 {
+        match self {
+            Self::Texture(texture) => texture.id(),
+            Self::Renderbuffer(renderbuffer) => renderbuffer.id(),
+        }
+    }
+
+    pub fn texture(&self) -> Option<&Texture<'a>> {
+        match self {
+            Self::Texture(texture) => Some(texture),
+            Self::Renderbuffer(..) => None,
+        }
+    }
+
+    fn attach(&self, gl: &gl::Gl, framebuffer: u32, attachment_point: u32) {
+        unsafe {
+            match self {
+                Self::Texture(texture) => {
+                    gl.NamedFramebufferTexture(framebuffer, attachment_point, texture.id(), 0)
+                }
+                Self::Renderbuffer(renderbuffer) => gl.NamedFramebufferRenderbuffer(
+                    framebuffer,
+                    attachment_point,
+                    gl::RENDERBUFFER,
+                    renderbuffer.id(),
+                ),
+            }
+        }
+    }
+}
+
+pub struct Renderbuffer<'a> {
+    gl: &'a gl::Gl,
+    id: u32,
+    size: (u32, u32),
+    internal_format: u32,
+    label: Option<&'a str>,
+}
+
+impl<'a> Renderbuffer<'a> {
+    pub fn new(
+        gl: &'a gl::Gl,
+        size: (u32, u32),
+        internal_format: u32,
+        label: Option<&'a str>,
+    ) -> Self {
+        let (width, height) = size;
+        let mut id = 0;
+
+        unsafe {
+            gl.CreateRenderbuffers(1, &mut id);
+            tracing::trace!(
+                "Created Renderbuffer ({}) ({} x {}) with internal format {:#x}",
+                id,
+                width,
+                height,
+                internal_format
+            );
+
+            if let Some(label) = label {
+                gl.ObjectLabel(
+                    gl::RENDERBUFFER,
+                    id,
+                    label.len() as i32,
+                    label.as_ptr().cast(),
+                );
+                tracing::trace!("Adding label to Renderbuffer ({}): {}", id, label);
+            }
+
+            gl.NamedRenderbufferStorage(id, internal_format, width as i32, height as i32);
+        }
+
+        Self {
+            gl,
+            id,
+            size,
+            internal_format,
+            label,
+        }
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+    pub fn internal_format(&self) -> u32 {
+        self.internal_format
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
+
+impl Drop for Renderbuffer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteRenderbuffers(1, &self.id);
+        }
+    }
+}
+
+pub struct Framebuffer<'a> {
+    gl: &'a gl::Gl,
+    id: u32,
+    size: (u32, u32),
+    color_descs: Vec<AttachmentDesc>,
+    depth_stencil_desc: Option<AttachmentDesc>,
+    color_attachments: Vec<Attachment<'a>>,
+    depth_stencil_attachment: Option<Attachment<'a>>,
+    label: Option<&'a str>,
+}
+
+impl<'a> Framebuffer<'a> {
+    /// Creates a framebuffer with a color attachment for each of `color`
+    /// (bound to `COLOR_ATTACHMENT0..n`) and an optional depth or depth/stencil attachment
+    pub fn new(
+        gl: &'a gl::Gl,
+        size: (u32, u32),
+        color: &[AttachmentDesc],
+        depth_stencil: Option<AttachmentDesc>,
+        label: Option<&'a str>,
+    ) -> Result<Self, FramebufferError> {
+        let mut id = 0;
+        unsafe {
+            gl.CreateFramebuffers(1, &mut id);
+            tracing::trace!("Created Framebuffer ({})", id);
+
+            if let Some(label) = label {
+                gl.ObjectLabel(
+                    gl::FRAMEBUFFER,
+                    id,
+                    label.len() as i32,
+                    label.as_ptr().cast(),
+                );
+                tracing::trace!("Adding label to Framebuffer ({}): {}", id, label);
+            }
+        }
+
+        let mut framebuffer = Self {
+            gl,
+            id,
+            size,
+            color_descs: color.to_vec(),
+            depth_stencil_desc: depth_stencil,
+            color_attachments: Vec::new(),
+            depth_stencil_attachment: None,
+            label,
+        };
+        framebuffer.create_attachments()?;
+
+        Ok(framebuffer)
+    }
+
+    /// Recreates every attachment with the new size, their contents are lost
+    pub fn resize(&mut self, size: (u32, u32)) -> Result<(), FramebufferError> {
+        if size == self.size {
+            return Ok(());
+        }
+
+        self.size = size;
+        self.create_attachments()
+    }
+
+    /// Copies the whole framebuffer into the whole of `target`, scaling if needed.
+    ///
+    /// `mask` is a combination of `gl::COLOR_BUFFER_BIT`, `gl::DEPTH_BUFFER_BIT` and
+    /// `gl::STENCIL_BUFFER_BIT`, `filter` is `gl::NEAREST` or `gl::LINEAR`.
+    pub fn blit(&self, target: &Framebuffer, mask: u32, filter: u32) {
+        let (width, height) = target.size;
+        self.blit_to(target.id, (0, 0, width, height), mask, filter);
+    }
+
+    /// Like [`Framebuffer::blit`], but into a region of any framebuffer (0 for the default one)
+    pub fn blit_to(&self, target: u32, region: (i32, i32, u32, u32), mask: u32, filter: u32) {
+        let (width, height) = self.size;
+        let (x, y, target_width, target_height) = region;
+
+        unsafe {
+            self.gl.BlitNamedFramebuffer(
+                self.id,
+                target,
+                0,
+                0,
+                width as i32,
+                height as i32,
+                x,
+                y,
+                x + target_width as i32,
+                y + target_height as i32,
+                mask,
+                filter,
+            );
+        }
+    }
+
+    /// Binds the framebuffer for drawing and reading and sets the viewport to cover it
+    pub unsafe fn bind(&self) {
+        self.gl.BindFramebuffer(gl::FRAMEBUFFER, self.id);
+        self.gl
+            .Viewport(0, 0, self.size.0 as i32, self.size.1 as i32);
+    }
+
+    pub unsafe fn unbind(&self) {
+        self.gl.BindFramebuffer(gl::FRAMEBUFFER, 0);
+    }
+
+    pub fn color_attachment(&self, index: usize) -> Option<&Attachment<'a>> {
+        self.color_attachments.get(index)
+    }
+
+    /// The texture backing color attachment `index`, if it's a texture attachment
+    pub fn color_texture(&self, index: usize) -> Option<&Texture<'a>> {
+        self.color_attachment(index).and_then(Attachment::texture)
+    }
+
+    pub fn depth_stencil_attachment(&self) -> Option<&Attachment<'a>> {
+        self.depth_stencil_attachment.as_ref()
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
+
+impl<'a> Framebuffer<'a> {
+    fn create_attachments(&mut self) -> Result<(), FramebufferError> {
+        let gl = self.gl;
+
+        // Old attachments are dropped (deleted) once they are replaced
+        self.color_attachments.clear();
+        for (i, desc) in self.color_descs.iter().enumerate() {
+            let label = self
+                .label
+                .map(|label| format!("{} - color attachment {}", label, i));
+            let attachment = Attachment::new(gl, self.size, desc, label)?;
+            attachment.attach(gl, self.id, gl::COLOR_ATTACHMENT0 + i as u32);
+            self.color_attachments.push(attachment);
+        }
+
+        self.depth_stencil_attachment = None;
+        if let Some(desc) = &self.depth_stencil_desc {
+            let attachment_point = match desc.internal_format {
+                gl::DEPTH_COMPONENT16
+                | gl::DEPTH_COMPONENT24
+                | gl::DEPTH_COMPONENT32
+                | gl::DEPTH_COMPONENT32F => gl::DEPTH_ATTACHMENT,
+                gl::STENCIL_INDEX8 => gl::STENCIL_ATTACHMENT,
+                _ => gl::DEPTH_STENCIL_ATTACHMENT,
+            };
+            let label = self
+                .label
+                .map(|label| format!("{} - depth/stencil attachment", label));
+            let attachment = Attachment::new(gl, self.size, desc, label)?;
+            attachment.attach(gl, self.id, attachment_point);
+            self.depth_stencil_attachment = Some(attachment);
+        }
+
+        unsafe {
+            if self.color_descs.is_empty() {
+                gl.NamedFramebufferDrawBuffer(self.id, gl::NONE);
+                gl.NamedFramebufferReadBuffer(self.id, gl::NONE);
+            } else {
+                let draw_buffers = (0..self.color_descs.len())
+                    .map(|i| gl::COLOR_ATTACHMENT0 + i as u32)
+                    .collect::<Vec<_>>();
+                gl.NamedFramebufferDrawBuffers(
+                    self.id,
+                    draw_buffers.len() as i32,
+                    draw_buffers.as_ptr(),
+                );
+                gl.NamedFramebufferReadBuffer(self.id, gl::COLOR_ATTACHMENT0);
+            }
+
+            let status = gl.CheckNamedFramebufferStatus(self.id, gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                let status = FramebufferStatus::from(status);
+                tracing::trace!("Framebuffer ({}) is incomplete: {:?}", self.id, status);
+                return Err(FramebufferError::Incomplete(status));
+            }
+        }
+
+        tracing::trace!(
+            "Framebuffer ({}) complete ({} x {}) with {} color attachment(s)",
+            self.id,
+            self.size.0,
+            self.size.1,
+            self.color_attachments.len()
+        );
+
+        Ok(())
+    }
+}
+
+impl Drop for Framebuffer<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteFramebuffers(1, &self.id);
+        }
+    }
+}
+
+/// Why `glCheckNamedFramebufferStatus` didn't return `FRAMEBUFFER_COMPLETE`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FramebufferStatus {
+    Undefined,
+    IncompleteAttachment,
+    MissingAttachment,
+    IncompleteDrawBuffer,
+    IncompleteReadBuffer,
+    Unsupported,
+    IncompleteMultisample,
+    IncompleteLayerTargets,
+    Unknown(u32),
+}
+
+impl From<u32> for FramebufferStatus {
+    fn from(status: u32) -> Self {
+        match status {
+            gl::FRAMEBUFFER_UNDEFINED => Self::Undefined,
+            gl::FRAMEBUFFER_INCOMPLETE_ATTACHMENT => Self::IncompleteAttachment,
+            gl::FRAMEBUFFER_INCOMPLETE_MISSING_ATTACHMENT => Self::MissingAttachment,
+            gl::FRAMEBUFFER_INCOMPLETE_DRAW_BUFFER => Self::IncompleteDrawBuffer,
+            gl::FRAMEBUFFER_INCOMPLETE_READ_BUFFER => Self::IncompleteReadBuffer,
+            gl::FRAMEBUFFER_UNSUPPORTED => Self::Unsupported,
+            gl::FRAMEBUFFER_INCOMPLETE_MULTISAMPLE => Self::IncompleteMultisample,
+            gl::FRAMEBUFFER_INCOMPLETE_LAYER_TARGETS => Self::IncompleteLayerTargets,
+            _ => Self::Unknown(status),
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum FramebufferError {
+    #[error("Framebuffer incomplete: {0:?}")]
+    Incomplete(FramebufferStatus),
+    #[error("Texture error: {0}")]
+    TextureError(#[from] TextureError),
+}
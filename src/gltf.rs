@@ -0,0 +1,434 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    borrow::Cow,
+    collections::HashSet,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use ::gltf::{camera::Projection, mesh::Mode};
+use base64::Engine;
+use glam::{Mat4, Vec3};
+use tracing::instrument;
+
+use crate::{
+    buffer::BufferError,
+    camera::Camera,
+    mesh::{Indices, Mesh, MeshData},
+    texture::{ColorSpace, Texture, TextureError},
+    vertex::ModelVertex,
+};
+
+/// Everything imported from a glTF file, with meshes and textures already uploaded
+pub struct GltfModel<'a> {
+    pub meshes: Vec<GltfMesh<'a>>,
+    pub materials: Vec<GltfMaterial>,
+    /// One per glTF image, in the same order
+    pub textures: Vec<Texture<'a>>,
+    pub nodes: Vec<GltfNode>,
+    /// Root nodes of the default scene (or the first one)
+    pub roots: Vec<usize>,
+    /// One per node with a camera, placed with the node's world transform
+    pub cameras: Vec<Camera>,
+}
+
+impl<'a> GltfModel<'a> {
+    pub fn material(&self, primitive: &GltfPrimitive) -> Option<&GltfMaterial> {
+        primitive.material.map(|i| &self.materials[i])
+    }
+
+    pub fn texture(&self, index: Option<usize>) -> Option<&Texture<'a>> {
+        index.map(|i| &self.textures[i])
+    }
+}
+
+pub struct GltfMesh<'a> {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive<'a>>,
+}
+
+pub struct GltfPrimitive<'a> {
+    pub mesh: Mesh<'a, ModelVertex>,
+    /// Index into [`GltfModel::materials`], `None` for the default material
+    pub material: Option<usize>,
+}
+
+/// A metallic-roughness material, textures are indices into [`GltfModel::textures`]
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_color_factor: [f32; 4],
+    pub base_color_texture: Option<usize>,
+    pub metallic_factor: f32,
+    pub roughness_factor: f32,
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub normal_scale: f32,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    /// Index into [`GltfModel::meshes`]
+    pub mesh: Option<usize>,
+    /// Index into [`GltfModel::cameras`]
+    pub camera: Option<usize>,
+    pub children: Vec<usize>,
+    /// Relative to the parent node
+    pub transform: Mat4,
+    pub world_transform: Mat4,
+}
+
+/// Imports a `.gltf` (with external or base64 embedded buffers and images) or `.glb` file.
+///
+/// Only triangle primitives are supported, primitives without normals get flat normals.
+/// Every mesh, texture and buffer gets `label`.
+#[instrument(skip(gl))]
+pub fn load_gltf<'a>(
+    gl: &'a gl::Gl,
+    path: impl AsRef<Path> + fmt::Debug,
+    label: Option<&'a str>,
+) -> Result<GltfModel<'a>, GltfError> {
+    let path = path.as_ref();
+    let data = read_file(path)?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    load_gltf_from_memory(gl, &data, base_dir, label)
+}
+
+/// Imports glTF or GLB data, external files are resolved relative to `base_dir`
+pub fn load_gltf_from_memory<'a>(
+    gl: &'a gl::Gl,
+    data: &[u8],
+    base_dir: &Path,
+    label: Option<&'a str>,
+) -> Result<GltfModel<'a>, GltfError> {
+    let ::gltf::Gltf { document, blob } = ::gltf::Gltf::from_slice(data)?;
+
+    let buffers = document
+        .buffers()
+        .map(|buffer| {
+            let data = match buffer.source() {
+                ::gltf::buffer::Source::Bin => blob.as_deref().map(Cow::Borrowed),
+                ::gltf::buffer::Source::Uri(uri) => Some(Cow::Owned(read_uri(uri, base_dir)?)),
+            };
+            match data {
+                Some(data) if data.len() >= buffer.length() => Ok(data),
+                _ => Err(GltfError::MissingBuffer(buffer.index())),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    // Base and emissive colors are sRGB, every other texture holds data
+    let srgb: HashSet<usize> = document
+        .materials()
+        .flat_map(|material| {
+            [
+                material.pbr_metallic_roughness().base_color_texture(),
+                material.emissive_texture(),
+            ]
+        })
+        .flatten()
+        .map(|info| info.texture().source().index())
+        .collect();
+    let textures = document
+        .images()
+        .map(|image| {
+            let data = match image.source() {
+                ::gltf::image::Source::View { view, .. } => {
+                    let buffer = &buffers[view.buffer().index()];
+                    let data = view
+                        .offset()
+                        .checked_add(view.length())
+                        .and_then(|end| buffer.get(view.offset()..end))
+                        .ok_or(GltfError::ViewOutOfRange(view.index()))?;
+                    Cow::Borrowed(data)
+                }
+                ::gltf::image::Source::Uri { uri, .. } => Cow::Owned(read_uri(uri, base_dir)?),
+            };
+            let color_space = if srgb.contains(&image.index()) {
+                ColorSpace::Srgb
+            } else {
+                ColorSpace::Linear
+            };
+            Texture::from_memory(gl, &data, color_space, label).map_err(|source| {
+                GltfError::Texture {
+                    image: image.index(),
+                    source,
+                }
+            })
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let materials: Vec<_> = document
+        .materials()
+        .map(|material| {
+            let pbr = material.pbr_metallic_roughness();
+            let normal = material.normal_texture();
+            GltfMaterial {
+                name: material.name().map(str::to_owned),
+                base_color_factor: pbr.base_color_factor(),
+                base_color_texture: pbr
+                    .base_color_texture()
+                    .map(|info| info.texture().source().index()),
+                metallic_factor: pbr.metallic_factor(),
+                roughness_factor: pbr.roughness_factor(),
+                metallic_roughness_texture: pbr
+                    .metallic_roughness_texture()
+                    .map(|info| info.texture().source().index()),
+                normal_texture: normal
+                    .as_ref()
+                    .map(|normal| normal.texture().source().index()),
+                normal_scale: normal.map_or(1.0, |normal| normal.scale()),
+            }
+        })
+        .collect();
+
+    let meshes = document
+        .meshes()
+        .map(|mesh| {
+            let primitives = mesh
+                .primitives()
+                .map(|primitive| {
+                    let data = read_primitive(&primitive, &buffers, mesh.index())?;
+                    Ok(GltfPrimitive {
+                        mesh: Mesh::new(gl, &data, label)?,
+                        material: primitive.material().index(),
+                    })
+                })
+                .collect::<Result<Vec<_>, GltfError>>()?;
+
+            Ok(GltfMesh {
+                name: mesh.name().map(str::to_owned),
+                primitives,
+            })
+        })
+        .collect::<Result<Vec<_>, GltfError>>()?;
+
+    let mut nodes: Vec<GltfNode> = document
+        .nodes()
+        .map(|node| {
+            let transform = Mat4::from_cols_array_2d(&node.transform().matrix());
+            GltfNode {
+                name: node.name().map(str::to_owned),
+                mesh: node.mesh().map(|mesh| mesh.index()),
+                camera: None,
+                children: node.children().map(|child| child.index()).collect(),
+                transform,
+                world_transform: transform,
+            }
+        })
+        .collect();
+
+    // Every node that isn't someone's child starts a hierarchy
+    let mut is_child = vec![false; nodes.len()];
+    for child in nodes.iter().flat_map(|node| &node.children) {
+        is_child[*child] = true;
+    }
+    let mut stack: Vec<(usize, Mat4)> = (0..nodes.len())
+        .filter(|&i| !is_child[i])
+        .map(|i| (i, Mat4::IDENTITY))
+        .collect();
+    // A node reached twice has two parents or is its own ancestor, one never
+    // reached is in a cycle without a root
+    let mut visited = vec![false; nodes.len()];
+    while let Some((i, parent_transform)) = stack.pop() {
+        if std::mem::replace(&mut visited[i], true) {
+            return Err(GltfError::NodeCycle(i));
+        }
+        let node = &mut nodes[i];
+        node.world_transform = parent_transform * node.transform;
+        stack.extend(
+            node.children
+                .iter()
+                .map(|&child| (child, node.world_transform)),
+        );
+    }
+    if let Some(i) = visited.iter().position(|&visited| !visited) {
+        return Err(GltfError::NodeCycle(i));
+    }
+
+    let mut cameras = Vec::new();
+    for node in document.nodes() {
+        let Some(camera) = node.camera() else {
+            continue;
+        };
+        let Projection::Perspective(perspective) = camera.projection() else {
+            tracing::warn!(
+                "glTF: Ignoring orthographic camera on node {}",
+                node.index()
+            );
+            continue;
+        };
+
+        let world_transform = nodes[node.index()].world_transform;
+        let mut camera = Camera::default();
+        camera.set_position(world_transform.transform_point3(Vec3::ZERO));
+        // glTF cameras look down their local -Z
+        camera.set_direction(world_transform.transform_vector3(Vec3::NEG_Z));
+        camera.set_fov(perspective.yfov().to_degrees());
+        if let Some(aspect_ratio) = perspective.aspect_ratio() {
+            camera.set_aspect_ratio(aspect_ratio);
+        }
+        let (_, far) = camera.clip_planes();
+        camera.set_clip_planes(perspective.znear(), perspective.zfar().unwrap_or(far));
+
+        nodes[node.index()].camera = Some(cameras.len());
+        cameras.push(camera);
+    }
+
+    let roots = document
+        .default_scene()
+        .or_else(|| document.scenes().next())
+        .map(|scene| scene.nodes().map(|node| node.index()).collect())
+        .unwrap_or_default();
+
+    tracing::debug!(
+        "glTF: Loaded {} meshes, {} materials, {} textures, {} nodes and {} cameras",
+        meshes.len(),
+        materials.len(),
+        textures.len(),
+        nodes.len(),
+        cameras.len()
+    );
+
+    Ok(GltfModel {
+        meshes,
+        materials,
+        textures,
+        nodes,
+        roots,
+        cameras,
+    })
+}
+
+fn read_primitive(
+    primitive: &::gltf::Primitive,
+    buffers: &[Cow<[u8]>],
+    mesh: usize,
+) -> Result<MeshData<ModelVertex>, GltfError> {
+    if primitive.mode() != Mode::Triangles {
+        return Err(GltfError::UnsupportedMode {
+            mesh,
+            mode: primitive.mode(),
+        });
+    }
+
+    let reader = primitive.reader(|buffer| Some(&buffers[buffer.index()]));
+    let positions: Vec<[f32; 3]> = reader
+        .read_positions()
+        .ok_or(GltfError::MissingPositions { mesh })?
+        .collect();
+    let uvs: Vec<[f32; 2]> = match reader.read_tex_coords(0) {
+        Some(uvs) => uvs.into_f32().collect(),
+        None => vec![[0.0; 2]; positions.len()],
+    };
+    let normals: Option<Vec<[f32; 3]>> = reader.read_normals().map(Iterator::collect);
+    for (attribute, count) in [("TEXCOORD_0", uvs.len())]
+        .into_iter()
+        .chain(normals.as_ref().map(|normals| ("NORMAL", normals.len())))
+    {
+        if count != positions.len() {
+            return Err(GltfError::AttributeCount {
+                mesh,
+                attribute,
+                count,
+                expected: positions.len(),
+            });
+        }
+    }
+    let indices: Vec<u32> = match reader.read_indices() {
+        Some(indices) => indices.into_u32().collect(),
+        None => (0..positions.len() as u32).collect(),
+    };
+
+    if let Some(&index) = indices.iter().find(|&&i| i as usize >= positions.len()) {
+        return Err(GltfError::IndexOutOfRange { mesh, index });
+    }
+
+    match normals {
+        Some(normals) => {
+            let vertices = positions
+                .iter()
+                .zip(&uvs)
+                .zip(normals)
+                .map(|((&position, &uv), normal)| ModelVertex::new(position, uv, normal))
+                .collect();
+            Ok(MeshData::new(vertices, Indices::from_u32(indices)))
+        }
+        None => {
+            // Flat normals need a vertex per triangle corner
+            let vertices: Vec<ModelVertex> = indices
+                .chunks_exact(3)
+                .flat_map(|triangle| {
+                    let [a, b, c] = [0, 1, 2].map(|i| Vec3::from(positions[triangle[i] as usize]));
+                    let normal = (b - a).cross(c - a).normalize_or_zero().to_array();
+                    triangle.iter().map(move |&i| (i as usize, normal))
+                })
+                .map(|(i, normal)| ModelVertex::new(positions[i], uvs[i], normal))
+                .collect();
+            Ok(MeshData::from_vertices(&vertices))
+        }
+    }
+}
+
+/// Decodes a base64 `data:` URI or reads the file it points to
+fn read_uri(uri: &str, base_dir: &Path) -> Result<Vec<u8>, GltfError> {
+    let Some(data_uri) = uri.strip_prefix("data:") else {
+        return read_file(&base_dir.join(uri));
+    };
+    let Some((_, data)) = data_uri.split_once(";base64,") else {
+        return Err(GltfError::UnsupportedUri(uri.chars().take(32).collect()));
+    };
+
+    Ok(base64::engine::general_purpose::STANDARD.decode(data)?)
+}
+
+fn read_file(path: &Path) -> Result<Vec<u8>, GltfError> {
+    std::fs::read(path).map_err(|source| GltfError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum GltfError {
+    #[error("glTF error: {0}")]
+    GltfError(#[from] ::gltf::Error),
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Only base64 data URIs are supported: {0}...")]
+    UnsupportedUri(String),
+    #[error("Base64 error: {0}")]
+    Base64Error(#[from] base64::DecodeError),
+    #[error("Buffer {0} is missing or shorter than its byteLength")]
+    MissingBuffer(usize),
+    #[error("Buffer view {0} runs past the end of its buffer")]
+    ViewOutOfRange(usize),
+    #[error(
+        "Node {0} is reached twice in the hierarchy, nodes can't have cycles or several parents"
+    )]
+    NodeCycle(usize),
+    #[error("Mesh {mesh}: {mode:?} primitives aren't supported, only triangles")]
+    UnsupportedMode { mesh: usize, mode: Mode },
+    #[error("Mesh {mesh}: primitive has no POSITION attribute")]
+    MissingPositions { mesh: usize },
+    #[error("Mesh {mesh}: {attribute} has {count} elements, POSITION has {expected}")]
+    AttributeCount {
+        mesh: usize,
+        attribute: &'static str,
+        count: usize,
+        expected: usize,
+    },
+    #[error("Mesh {mesh}: index {index} is out of range")]
+    IndexOutOfRange { mesh: usize, index: u32 },
+    #[error("Image {image}: {source}")]
+    Texture { image: usize, source: TextureError },
+    #[error("Buffer error: {0}")]
+    BufferError(#[from] BufferError),
+}
@@ -0,0 +1,230 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use khronos_egl as egl;
+
+// EGL_MESA_platform_surfaceless
+const PLATFORM_SURFACELESS_MESA: egl::Enum = 0x31DD;
+
+/// An OpenGL 4.5 core context without a window, backed by EGL.
+///
+/// Rendering goes to an offscreen framebuffer (RGBA8 color + depth/stencil)
+/// which is bound as the draw and read framebuffer on creation.
+/// Works with Mesa's llvmpipe on machines without a GPU or display server.
+pub struct HeadlessContext {
+    egl: egl::DynamicInstance<egl::EGL1_5>,
+    display: egl::Display,
+    context: egl::Context,
+    gl: gl::Gl,
+
+    framebuffer: u32,
+    color_renderbuffer: u32,
+    depth_stencil_renderbuffer: u32,
+    size: (u32, u32),
+}
+
+impl HeadlessContext {
+    pub fn new(size: (u32, u32)) -> Result<Self, HeadlessError> {
+        let egl = unsafe { egl::DynamicInstance::<egl::EGL1_5>::load_required() }
+            .map_err(|e| HeadlessError::LoadError(e.to_string()))?;
+        tracing::debug!("EGL: Loaded library");
+
+        let display = Self::get_display(&egl)?;
+        let (major, minor) = egl.initialize(display)?;
+        tracing::debug!("EGL: Initialized display (EGL {}.{})", major, minor);
+
+        let extensions = egl.query_string(Some(display), egl::EXTENSIONS)?;
+        if !extensions
+            .to_string_lossy()
+            .split(' ')
+            .any(|e| e == "EGL_KHR_surfaceless_context")
+        {
+            return Err(HeadlessError::MissingExtension(
+                "EGL_KHR_surfaceless_context",
+            ));
+        }
+
+        egl.bind_api(egl::OPENGL_API)?;
+
+        // No surface is ever created, so any surface type will do
+        let config_attribs = [
+            egl::SURFACE_TYPE,
+            0,
+            egl::RENDERABLE_TYPE,
+            egl::OPENGL_BIT,
+            egl::NONE,
+        ];
+        let config = egl
+            .choose_first_config(display, &config_attribs)?
+            .ok_or(HeadlessError::NoConfig)?;
+
+        let mut context_attribs = vec![
+            egl::CONTEXT_MAJOR_VERSION,
+            4,
+            egl::CONTEXT_MINOR_VERSION,
+            5,
+            egl::CONTEXT_OPENGL_PROFILE_MASK,
+            egl::CONTEXT_OPENGL_CORE_PROFILE_BIT,
+        ];
+        #[cfg(debug_assertions)]
+        context_attribs.extend_from_slice(&[egl::CONTEXT_OPENGL_DEBUG, egl::TRUE as i32]);
+        context_attribs.push(egl::NONE);
+
+        let context = egl.create_context(display, config, None, &context_attribs)?;
+        egl.make_current(display, None, None, Some(context))?;
+        tracing::debug!("EGL: Created surfaceless OpenGL 4.5 core context");
+
+        let gl = gl::Gl::load_with(|s| match egl.get_proc_address(s) {
+            Some(f) => f as *const _,
+            None => std::ptr::null(),
+        });
+        tracing::debug!("GL: Loaded functions");
+
+        let mut s = Self {
+            egl,
+            display,
+            context,
+            gl,
+            framebuffer: 0,
+            color_renderbuffer: 0,
+            depth_stencil_renderbuffer: 0,
+            size,
+        };
+        s.create_framebuffer()?;
+
+        Ok(s)
+    }
+
+    pub fn gl(&self) -> &gl::Gl {
+        &self.gl
+    }
+
+    pub fn size(&self) -> (u32, u32) {
+        self.size
+    }
+
+    /// The id of the offscreen framebuffer everything is rendered to
+    pub fn framebuffer(&self) -> u32 {
+        self.framebuffer
+    }
+}
+
+impl HeadlessContext {
+    fn get_display(egl: &egl::DynamicInstance<egl::EGL1_5>) -> Result<egl::Display, HeadlessError> {
+        // Prefer the surfaceless platform, it doesn't need a GPU or a display server
+        let client_extensions = egl
+            .query_string(None, egl::EXTENSIONS)
+            .map(|e| e.to_string_lossy().into_owned())
+            .unwrap_or_default();
+
+        if client_extensions
+            .split(' ')
+            .any(|e| e == "EGL_MESA_platform_surfaceless")
+        {
+            let display = unsafe {
+                egl.get_platform_display(
+                    PLATFORM_SURFACELESS_MESA,
+                    egl::DEFAULT_DISPLAY,
+                    &[egl::ATTRIB_NONE],
+                )
+            }?;
+            tracing::debug!("EGL: Using surfaceless platform");
+            return Ok(display);
+        }
+
+        unsafe { egl.get_display(egl::DEFAULT_DISPLAY) }.ok_or(HeadlessError::NoDisplay)
+    }
+
+    fn create_framebuffer(&mut self) -> Result<(), HeadlessError> {
+        let gl = &self.gl;
+        let (width, height) = self.size;
+
+        unsafe {
+            gl.CreateRenderbuffers(1, &mut self.color_renderbuffer);
+            gl.NamedRenderbufferStorage(
+                self.color_renderbuffer,
+                gl::RGBA8,
+                width as i32,
+                height as i32,
+            );
+
+            gl.CreateRenderbuffers(1, &mut self.depth_stencil_renderbuffer);
+            gl.NamedRenderbufferStorage(
+                self.depth_stencil_renderbuffer,
+                gl::DEPTH24_STENCIL8,
+                width as i32,
+                height as i32,
+            );
+
+            gl.CreateFramebuffers(1, &mut self.framebuffer);
+            gl.NamedFramebufferRenderbuffer(
+                self.framebuffer,
+                gl::COLOR_ATTACHMENT0,
+                gl::RENDERBUFFER,
+                self.color_renderbuffer,
+            );
+            gl.NamedFramebufferRenderbuffer(
+                self.framebuffer,
+                gl::DEPTH_STENCIL_ATTACHMENT,
+                gl::RENDERBUFFER,
+                self.depth_stencil_renderbuffer,
+            );
+
+            let label = "Headless framebuffer";
+            gl.ObjectLabel(
+                gl::FRAMEBUFFER,
+                self.framebuffer,
+                label.len() as i32,
+                label.as_ptr().cast(),
+            );
+
+            let status = gl.CheckNamedFramebufferStatus(self.framebuffer, gl::FRAMEBUFFER);
+            if status != gl::FRAMEBUFFER_COMPLETE {
+                return Err(HeadlessError::IncompleteFramebuffer(status));
+            }
+
+            gl.BindFramebuffer(gl::FRAMEBUFFER, self.framebuffer);
+            gl.Viewport(0, 0, width as i32, height as i32);
+        }
+        tracing::trace!(
+            "Created headless Framebuffer ({}) ({} x {})",
+            self.framebuffer,
+            width,
+            height
+        );
+
+        Ok(())
+    }
+}
+
+impl Drop for HeadlessContext {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteFramebuffers(1, &self.framebuffer);
+            self.gl.DeleteRenderbuffers(1, &self.color_renderbuffer);
+            self.gl
+                .DeleteRenderbuffers(1, &self.depth_stencil_renderbuffer);
+        }
+
+        let _ = self.egl.make_current(self.display, None, None, None);
+        let _ = self.egl.destroy_context(self.display, self.context);
+        // The display isn't terminated, it's shared with every other
+        // HeadlessContext in the process (e.g. tests running in parallel)
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum HeadlessError {
+    #[error("Failed to load EGL: {0}")]
+    LoadError(String),
+    #[error("No EGL display available")]
+    NoDisplay,
+    #[error("No EGL config supports OpenGL")]
+    NoConfig,
+    #[error("Missing EGL extension: {0}")]
+    MissingExtension(&'static str),
+    #[error("Framebuffer incomplete: {0:#x}")]
+    IncompleteFramebuffer(u32),
+    #[error("EGL error: {0}")]
+    EglError(#[from] egl::Error),
+}
@@ -0,0 +1,314 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt, fs,
+    path::{Path, PathBuf},
+};
+
+use image::DynamicImage;
+use tracing::instrument;
+
+use crate::{
+    sampler::{SamplerDesc, Target},
+    texture::{
+        mip_levels, open_image, with_tight_unpacking, ColorSpace, PixelFormat, TextureError,
+    },
+};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LayeredTarget {
+    /// `GL_TEXTURE_2D_ARRAY`, layers are filtered separately and only mipmapped in 2D
+    Array,
+    /// `GL_TEXTURE_3D`, filtered and mipmapped across slices too
+    Volume,
+}
+
+impl LayeredTarget {
+    pub fn gl_enum(self) -> u32 {
+        match self {
+            Self::Array => gl::TEXTURE_2D_ARRAY,
+            Self::Volume => gl::TEXTURE_3D,
+        }
+    }
+}
+
+/// A 2D array or 3D texture, made of `depth` layers (or slices) of `width` x `height`
+pub struct LayeredTexture<'a> {
+    gl: &'a gl::Gl,
+    id: u32,
+    target: LayeredTarget,
+    size: (u32, u32, u32),
+    format: PixelFormat,
+    levels: u32,
+    label: Option<&'a str>,
+}
+
+impl<'a> LayeredTexture<'a> {
+    /// Creates a texture with uninitialized layers, filled in with [`LayeredTexture::set_layer`]
+    pub fn new(
+        gl: &'a gl::Gl,
+        target: LayeredTarget,
+        size: (u32, u32, u32),
+        format: PixelFormat,
+        label: Option<&'a str>,
+    ) -> Self {
+        let (width, height, depth) = size;
+        let levels = match target {
+            LayeredTarget::Array => mip_levels((width, height)),
+            LayeredTarget::Volume => mip_levels((width.max(depth), height)),
+        };
+        let mut id = 0;
+
+        unsafe {
+            gl.CreateTextures(target.gl_enum(), 1, &mut id);
+            tracing::trace!(
+                "Created {:?} Texture ({}) ({} x {} x {}) with internal format {:#x}",
+                target,
+                id,
+                width,
+                height,
+                depth,
+                format.internal_format
+            );
+
+            if let Some(label) = label {
+                gl.ObjectLabel(gl::TEXTURE, id, label.len() as i32, label.as_ptr().cast());
+                tracing::trace!("Adding label to Texture ({}): {}", id, label);
+            }
+
+            SamplerDesc::default().apply(gl, Target::Texture(id));
+            gl.TextureStorage3D(
+                id,
+                levels as i32,
+                format.internal_format,
+                width as i32,
+                height as i32,
+                depth as i32,
+            );
+        }
+
+        Self {
+            gl,
+            id,
+            target,
+            size,
+            format,
+            levels,
+            label,
+        }
+    }
+
+    /// Uploads tightly packed `data` (x, then y, then layer) and generates its mipmaps
+    pub fn from_raw(
+        gl: &'a gl::Gl,
+        target: LayeredTarget,
+        size: (u32, u32, u32),
+        format: PixelFormat,
+        data: &[u8],
+        label: Option<&'a str>,
+    ) -> Result<Self, TextureError> {
+        let (width, height, depth) = size;
+        if format.texel_size() * width as usize * height as usize * depth as usize != data.len() {
+            return Err(TextureError::WrongSizedData);
+        }
+
+        let texture = Self::new(gl, target, size, format, label);
+        unsafe {
+            texture.upload(0, depth, data);
+            texture.generate_mipmaps();
+        }
+
+        Ok(texture)
+    }
+
+    /// Uses every image as a layer, they need the same size and type
+    pub fn from_images(
+        gl: &'a gl::Gl,
+        target: LayeredTarget,
+        images: &[DynamicImage],
+        color_space: ColorSpace,
+        label: Option<&'a str>,
+    ) -> Result<Self, LayeredTextureError> {
+        let first = images.first().ok_or(LayeredTextureError::NoLayers)?;
+        let format = PixelFormat::of(first, color_space)?;
+        let size = (first.width(), first.height(), images.len() as u32);
+
+        let texture = Self::new(gl, target, size, format, label);
+        for (layer, image) in images.iter().enumerate() {
+            texture.set_layer_image(layer as u32, image, color_space)?;
+        }
+        unsafe { texture.generate_mipmaps() };
+
+        Ok(texture)
+    }
+
+    #[instrument(skip(gl))]
+    pub fn from_files<P: AsRef<Path> + fmt::Debug>(
+        gl: &'a gl::Gl,
+        target: LayeredTarget,
+        paths: &[P],
+        color_space: ColorSpace,
+        label: Option<&'a str>,
+    ) -> Result<Self, LayeredTextureError> {
+        let images = paths
+            .iter()
+            .map(|path| open_image(path.as_ref()))
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Self::from_images(gl, target, &images, color_space, label)
+    }
+
+    /// Loads a headerless voxel file, laid out like the data of [`LayeredTexture::from_raw`]
+    #[instrument(skip(gl))]
+    pub fn from_raw_file(
+        gl: &'a gl::Gl,
+        path: impl AsRef<Path> + fmt::Debug,
+        size: (u32, u32, u32),
+        format: PixelFormat,
+        label: Option<&'a str>,
+    ) -> Result<Self, LayeredTextureError> {
+        let path = path.as_ref();
+        let data = fs::read(path).map_err(|source| LayeredTextureError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        Ok(Self::from_raw(
+            gl,
+            LayeredTarget::Volume,
+            size,
+            format,
+            &data,
+            label,
+        )?)
+    }
+
+    /// Replaces one layer with tightly packed `data` in the texture's format.
+    /// Mipmaps aren't updated, see [`LayeredTexture::generate_mipmaps`].
+    pub fn set_layer(&self, layer: u32, data: &[u8]) -> Result<(), LayeredTextureError> {
+        let (width, height, depth) = self.size;
+        if layer >= depth {
+            return Err(LayeredTextureError::LayerOutOfRange {
+                layer,
+                layers: depth,
+            });
+        }
+        if self.format.texel_size() * width as usize * height as usize != data.len() {
+            return Err(TextureError::WrongSizedData.into());
+        }
+
+        unsafe { self.upload(layer, 1, data) };
+
+        Ok(())
+    }
+
+    /// Replaces one layer with `image`, which needs the texture's size and type
+    pub fn set_layer_image(
+        &self,
+        layer: u32,
+        image: &DynamicImage,
+        color_space: ColorSpace,
+    ) -> Result<(), LayeredTextureError> {
+        let (width, height, _) = self.size;
+        if (image.width(), image.height()) != (width, height) {
+            return Err(LayeredTextureError::LayerSize {
+                layer,
+                size: (image.width(), image.height()),
+                expected: (width, height),
+            });
+        }
+        if PixelFormat::of(image, color_space)? != self.format {
+            return Err(LayeredTextureError::LayerFormat(layer));
+        }
+
+        self.set_layer(layer, image.as_bytes())
+    }
+
+    pub unsafe fn generate_mipmaps(&self) {
+        self.gl.GenerateTextureMipmap(self.id);
+    }
+
+    pub fn set_sampler(&self, desc: &SamplerDesc) {
+        unsafe { desc.apply(self.gl, Target::Texture(self.id)) };
+    }
+
+    pub unsafe fn bind(&self, slot: u32) {
+        self.gl.BindTextureUnit(slot, self.id);
+    }
+
+    pub unsafe fn unbind(&self, slot: u32) {
+        self.gl.BindTextureUnit(slot, 0);
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    pub fn target(&self) -> LayeredTarget {
+        self.target
+    }
+    /// Width, height and the number of layers
+    pub fn size(&self) -> (u32, u32, u32) {
+        self.size
+    }
+    pub fn format(&self) -> PixelFormat {
+        self.format
+    }
+    pub fn levels(&self) -> u32 {
+        self.levels
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+
+    /// Uploads `count` layers from `first` on to level 0
+    unsafe fn upload(&self, first: u32, count: u32, data: &[u8]) {
+        let (width, height, _) = self.size;
+        with_tight_unpacking(self.gl, || {
+            self.gl.TextureSubImage3D(
+                self.id,
+                0,
+                0,
+                0,
+                first as i32,
+                width as i32,
+                height as i32,
+                count as i32,
+                self.format.format,
+                self.format.data_type,
+                data.as_ptr().cast(),
+            )
+        });
+    }
+}
+
+impl Drop for LayeredTexture<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteTextures(1, &self.id);
+        }
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum LayeredTextureError {
+    #[error("Texture error: {0}")]
+    TextureError(#[from] TextureError),
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("A layered texture needs at least one layer")]
+    NoLayers,
+    #[error("Layer {layer} is out of range, there are {layers}")]
+    LayerOutOfRange { layer: u32, layers: u32 },
+    #[error("Layer {layer} is {}x{}, expected {}x{}", size.0, size.1, expected.0, expected.1)]
+    LayerSize {
+        layer: u32,
+        size: (u32, u32),
+        expected: (u32, u32),
+    },
+    #[error("Layer {0} has a different pixel format than the first layer")]
+    LayerFormat(u32),
+}
@@ -1,7 +1,37 @@
 // Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
 // SPDX-License-Identifier: MIT
 
+// Every GL call is unsafe, documenting it on each wrapper doesn't add anything
+#![allow(clippy::missing_safety_doc)]
+
+// Lets the derive macros refer to `::gl_playground` from inside this crate too
+extern crate self as gl_playground;
+
+pub mod block_layout;
+pub mod buffer;
 pub mod camera;
+pub mod compressed;
+pub mod compute;
+pub mod cubemap;
+pub mod diagnostics;
+pub mod framebuffer;
+pub mod gltf;
+pub mod headless;
+pub mod layered_texture;
+pub mod mesh;
+pub mod obj;
+pub mod preprocessor;
 pub mod program;
+pub mod program_cache;
+pub mod readback;
+pub mod reflection;
+pub mod sampler;
+pub mod skybox;
+pub mod spirv;
+pub mod storage_buffer;
 pub mod texture;
+pub mod uniform;
+pub mod uniform_buffer;
 pub mod vertex;
+pub mod vertex_array;
+pub mod watched_program;
@@ -1,22 +1,33 @@
 // Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
 // SPDX-License-Identifier: MIT
 
+use std::{env, ffi::CStr, path::PathBuf, sync::mpsc::Receiver};
 #[cfg(debug_assertions)]
-use std::ptr::null;
-use std::{
-    ffi::CStr,
-    mem::{size_of, size_of_val},
-    sync::mpsc::Receiver,
-};
+use std::{path::Path, ptr::null};
 
+#[cfg(not(debug_assertions))]
+use gl_playground::program::Program;
+#[cfg(debug_assertions)]
+use gl_playground::watched_program::WatchedProgram;
 use gl_playground::{
-    camera::Camera, program::Program, texture::Texture, vertex::Vertex, vertex::CUBE,
+    camera::Camera,
+    mesh::{Mesh, MeshData},
+    program_cache::ProgramCache,
+    readback,
+    texture::{ColorSpace, Texture},
+    uniform_buffer::{CameraBlock, UniformBuffer},
+    vertex::CUBE,
 };
 use glfw::Context;
 
 const SCR_WIDTH: u32 = 1280;
 const SCR_HEIGHT: u32 = 720;
 
+/// Debug builds watch the shaders where they're edited, found from the crate root
+/// wherever the binary is run from. Release builds embed them instead.
+#[cfg(debug_assertions)]
+const ROOT: &str = env!("CARGO_MANIFEST_DIR");
+
 fn main() {
     let mut collector = tracing_subscriber::fmt();
 
@@ -38,6 +49,7 @@ fn main() {
         glfw::OpenGlProfileHint::Core,
     ));
     glfw_context.window_hint(glfw::WindowHint::Resizable(false));
+    glfw_context.window_hint(glfw::WindowHint::SRgbCapable(true));
     glfw_context.window_hint(glfw::WindowHint::ContextVersion(4, 5));
     #[cfg(debug_assertions)]
     glfw_context.window_hint(glfw::WindowHint::OpenGlDebugContext(true));
@@ -64,6 +76,9 @@ fn main() {
         gl.DebugMessageCallback(Some(gl_debug_callback), null());
 
         gl.Viewport(0, 0, SCR_WIDTH as i32, SCR_HEIGHT as i32);
+        // Shaders work in linear, sRGB textures are decoded when sampled and the
+        // output is encoded back when written
+        gl.Enable(gl::FRAMEBUFFER_SRGB);
     }
 
     tracing::debug!("GL: Vendor: {}", unsafe {
@@ -78,59 +93,47 @@ fn main() {
             .unwrap()
     });
 
-    let program = Program::from_source(
+    // Linked programs are reused across runs until the sources or the driver change
+    let program_cache = ProgramCache::new(&gl, cache_dir().join("shader_cache"));
+
+    // Recompiled whenever the files are saved
+    #[cfg(debug_assertions)]
+    let mut program = WatchedProgram::from_files_cached(
         &gl,
-        include_str!("shaders/basic.vert"),
-        include_str!("shaders/basic.frag"),
+        Path::new(ROOT).join("src/shaders/basic.vert"),
+        Path::new(ROOT).join("src/shaders/basic.frag"),
+        &program_cache,
         Some("Basic Shader"),
     )
     .expect("Failed to create shader program");
+    // Embedded sources have no directory to resolve includes in
+    #[cfg(not(debug_assertions))]
+    let program = Program::builder(&gl)
+        .vertex(
+            include_str!("shaders/basic.vert")
+                .replace("#include \"camera.glsl\"", CameraBlock::GLSL),
+        )
+        .fragment(include_str!("shaders/basic.frag"))
+        .cache(&program_cache)
+        .label("Basic Shader")
+        .build()
+        .expect("Failed to create shader program");
     tracing::debug!("GL: Built program successfully");
 
-    let texture = Texture::from_file(&gl, "assets/brick.webp", Some("Brick wall"))
-        .expect("Failed to load texture");
+    let texture = Texture::from_memory(
+        &gl,
+        include_bytes!("../assets/brick.webp"),
+        ColorSpace::Srgb,
+        Some("Brick wall"),
+    )
+    .expect("Failed to load texture");
 
     unsafe {
         gl.ClearColor(0.2, 0.2, 0.2, 1.0);
         gl.Enable(gl::DEPTH_TEST);
 
-        let mut vao = 0;
-        gl.CreateVertexArrays(1, &mut vao);
-
-        let mut buffer = 0;
-        gl.CreateBuffers(1, &mut buffer);
-        gl.NamedBufferStorage(
-            buffer,
-            size_of_val(&CUBE) as isize,
-            CUBE.as_ptr().cast(),
-            gl::DYNAMIC_STORAGE_BIT,
-        );
-        gl.ObjectLabel(gl::BUFFER, buffer, -1, ("Cube\0").as_ptr().cast());
-
-        gl.VertexArrayVertexBuffer(vao, 0, buffer, 0, size_of::<Vertex>() as i32);
-
-        gl.EnableVertexArrayAttrib(vao, 0);
-        gl.EnableVertexArrayAttrib(vao, 1);
-
-        gl.VertexArrayAttribFormat(
-            vao,
-            0,
-            3,
-            gl::FLOAT,
-            gl::FALSE,
-            bytemuck::offset_of!(Vertex, position) as u32,
-        );
-        gl.VertexArrayAttribFormat(
-            vao,
-            1,
-            2,
-            gl::FLOAT,
-            gl::FALSE,
-            bytemuck::offset_of!(Vertex, uv) as u32,
-        );
-
-        gl.VertexArrayAttribBinding(vao, 0, 0);
-        gl.VertexArrayAttribBinding(vao, 1, 0);
+        let cube = Mesh::new(&gl, &MeshData::from_vertices(&CUBE), Some("Cube"))
+            .expect("Failed to create cube mesh");
 
         // tell GLFW to capture our mouse
         window.set_cursor_mode(glfw::CursorMode::Disabled);
@@ -141,6 +144,15 @@ fn main() {
         let mut camera = Camera::default();
         camera.set_position(glam::vec3(0.0, 0.0, 1.0));
 
+        let camera_block = UniformBuffer::new(
+            &gl,
+            &CameraBlock::from(&camera),
+            CameraBlock::BINDING,
+            Some("Camera block"),
+        )
+        .expect("Failed to create camera block");
+        camera_block.bind();
+
         // let proj = glam::Mat4::perspective_rh_gl(
         //     100.0f32.to_radians(),
         //     SCR_WIDTH as f32 / SCR_HEIGHT as f32,
@@ -148,8 +160,6 @@ fn main() {
         //     100.0,
         // );
 
-        let proj_view_loc = program.get_unifrom("uProjView").unwrap();
-
         let mut current_time = glfw_context.get_time();
         let mut last_time = 0.0f64;
         let mut delta_time;
@@ -157,56 +167,83 @@ fn main() {
         // Camera Fix
         // window.set_cursor_pos_polling(false);
 
+        let mut screenshot = false;
+
         tracing::debug!("GLFW Window: Starting game loop");
         while !window.should_close() {
             delta_time = current_time - last_time;
             last_time = current_time;
             current_time = glfw_context.get_time();
 
+            #[cfg(debug_assertions)]
+            program.reload_if_changed();
+
             gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
-            gl.BindVertexArray(vao);
             program.bind();
             texture.bind(0);
 
-            let proj_view = camera.proj_view_matrix();
-            gl.UniformMatrix4fv(proj_view_loc, 1, gl::FALSE, &proj_view.to_cols_array()[0]);
+            camera_block.set(&CameraBlock::from(&camera));
 
             glfw_context.poll_events();
-            handle_events(&gl, &mut window, &event_receiver, &mut camera);
+            handle_events(
+                &gl,
+                &mut window,
+                &event_receiver,
+                &mut camera,
+                &mut screenshot,
+            );
 
             camera.proccess_movement(delta_time as f32);
 
-            gl.DrawArrays(gl::TRIANGLES, 0, (CUBE.len() * 3) as i32);
-            // gl.DrawElements(
-            //     gl::TRIANGLES,
-            //     CUBE.len() as i32,
-            //     gl::UNSIGNED_INT,
-            //     std::ptr::null(),
-            // );
+            cube.draw();
+
+            // The back buffer is only defined until it's swapped
+            if std::mem::take(&mut screenshot) {
+                let (w, h) = window.get_framebuffer_size();
+                match readback::save_screenshot(&gl, (w as u32, h as u32), "screenshots") {
+                    Ok(path) => tracing::info!("Program: Saved screenshot to {}", path.display()),
+                    Err(e) => tracing::error!("Program: Failed to save screenshot: {}", e),
+                }
+            }
 
             window.swap_buffers();
         }
         tracing::debug!("GLFW Window: Ended game loop");
-
-        gl.DeleteBuffers(1, &buffer);
-        gl.DeleteVertexArrays(1, &vao);
     }
 
     tracing::info!("Program: End");
 }
 
+/// The per-user cache directory, so driver-specific binaries stay out of the checkout
+fn cache_dir() -> PathBuf {
+    let base = if cfg!(windows) {
+        env::var_os("LOCALAPPDATA").map(PathBuf::from)
+    } else if cfg!(target_os = "macos") {
+        env::var_os("HOME").map(|home| PathBuf::from(home).join("Library/Caches"))
+    } else {
+        env::var_os("XDG_CACHE_HOME")
+            .map(PathBuf::from)
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".cache")))
+    };
+
+    base.unwrap_or_else(env::temp_dir).join("gl_playground")
+}
+
 fn handle_events(
     gl: &gl::Gl,
     window: &mut glfw::Window,
     receiver: &Receiver<(f64, glfw::WindowEvent)>,
     camera: &mut Camera,
+    screenshot: &mut bool,
 ) {
-    for (_, event) in glfw::flush_messages(&receiver) {
+    for (_, event) in glfw::flush_messages(receiver) {
         camera.proccess_event(&event);
         match event {
             glfw::WindowEvent::Key(glfw::Key::Escape, _, glfw::Action::Press, _) => {
                 window.set_should_close(true)
             }
+            // Taken once the frame is drawn
+            glfw::WindowEvent::Key(glfw::Key::F12, _, glfw::Action::Press, _) => *screenshot = true,
             glfw::WindowEvent::FramebufferSize(w, h) => {
                 unsafe {
                     gl.Viewport(0, 0, w, h);
@@ -0,0 +1,191 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::collections::HashMap;
+
+use crate::{
+    buffer::{Buffer, BufferError},
+    vertex::VertexLayout,
+    vertex_array::VertexArray,
+};
+
+/// Triangle list indices, stored as `u16` when every index fits
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Indices {
+    U16(Vec<u16>),
+    U32(Vec<u32>),
+}
+
+impl Indices {
+    /// Picks the smallest index type that can hold every index
+    pub fn from_u32(indices: Vec<u32>) -> Self {
+        if indices.iter().all(|&i| i <= u16::MAX as u32) {
+            Self::U16(indices.into_iter().map(|i| i as u16).collect())
+        } else {
+            Self::U32(indices)
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        match self {
+            Self::U16(indices) => indices.len(),
+            Self::U32(indices) => indices.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn get(&self, i: usize) -> Option<u32> {
+        match self {
+            Self::U16(indices) => indices.get(i).map(|&i| i as u32),
+            Self::U32(indices) => indices.get(i).copied(),
+        }
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..self.len()).map(|i| self.get(i).unwrap())
+    }
+
+    /// `gl::UNSIGNED_SHORT` or `gl::UNSIGNED_INT`
+    pub fn index_type(&self) -> u32 {
+        match self {
+            Self::U16(..) => gl::UNSIGNED_SHORT,
+            Self::U32(..) => gl::UNSIGNED_INT,
+        }
+    }
+}
+
+/// Vertices and indices of an indexed triangle mesh, on the CPU side
+#[derive(Clone, Debug)]
+pub struct MeshData<V: VertexLayout> {
+    pub vertices: Vec<V>,
+    pub indices: Indices,
+}
+
+impl<V: VertexLayout> MeshData<V> {
+    pub fn new(vertices: Vec<V>, indices: Indices) -> Self {
+        Self { vertices, indices }
+    }
+
+    /// Turns a flat triangle list (like `CUBE`) into an indexed mesh,
+    /// merging vertices that are bitwise identical
+    pub fn from_vertices(vertices: &[V]) -> Self {
+        let mut unique = Vec::new();
+        let mut lookup: HashMap<&[u8], u32> = HashMap::new();
+
+        let indices = vertices
+            .iter()
+            .map(|vertex| {
+                *lookup.entry(bytemuck::bytes_of(vertex)).or_insert_with(|| {
+                    unique.push(*vertex);
+                    (unique.len() - 1) as u32
+                })
+            })
+            .collect();
+
+        Self {
+            vertices: unique,
+            indices: Indices::from_u32(indices),
+        }
+    }
+
+    /// The flat triangle list again, one vertex per index
+    pub fn expand(&self) -> Vec<V> {
+        self.indices
+            .iter()
+            .map(|i| self.vertices[i as usize])
+            .collect()
+    }
+}
+
+enum IndexBuffer<'a> {
+    U16(Buffer<'a, u16>),
+    U32(Buffer<'a, u32>),
+}
+
+impl IndexBuffer<'_> {
+    fn id(&self) -> u32 {
+        match self {
+            Self::U16(buffer) => buffer.id(),
+            Self::U32(buffer) => buffer.id(),
+        }
+    }
+}
+
+/// An indexed triangle mesh uploaded to the GPU, ready to draw
+pub struct Mesh<'a, V: VertexLayout> {
+    gl: &'a gl::Gl,
+    vertex_buffer: Buffer<'a, V>,
+    index_buffer: IndexBuffer<'a>,
+    vertex_array: VertexArray<'a>,
+    index_count: usize,
+    index_type: u32,
+    label: Option<&'a str>,
+}
+
+impl<'a, V: VertexLayout> Mesh<'a, V> {
+    pub fn new(
+        gl: &'a gl::Gl,
+        data: &MeshData<V>,
+        label: Option<&'a str>,
+    ) -> Result<Self, BufferError> {
+        let vertex_buffer = Buffer::from_data(gl, &data.vertices, 0, label)?;
+        let index_buffer = match &data.indices {
+            Indices::U16(indices) => IndexBuffer::U16(Buffer::from_data(gl, indices, 0, label)?),
+            Indices::U32(indices) => IndexBuffer::U32(Buffer::from_data(gl, indices, 0, label)?),
+        };
+
+        let vertex_array = VertexArray::from_layout::<V>(gl, label);
+        vertex_array.set_vertex_buffer(0, &vertex_buffer);
+        unsafe {
+            gl.VertexArrayElementBuffer(vertex_array.id(), index_buffer.id());
+        }
+        tracing::trace!(
+            "Created Mesh with {} vertices and {} indices",
+            data.vertices.len(),
+            data.indices.len()
+        );
+
+        Ok(Self {
+            gl,
+            vertex_buffer,
+            index_buffer,
+            vertex_array,
+            index_count: data.indices.len(),
+            index_type: data.indices.index_type(),
+            label,
+        })
+    }
+
+    /// Binds the vertex array and draws every triangle, with whatever program is bound
+    pub unsafe fn draw(&self) {
+        self.vertex_array.bind();
+        self.gl.DrawElements(
+            gl::TRIANGLES,
+            self.index_count as i32,
+            self.index_type,
+            std::ptr::null(),
+        );
+    }
+
+    pub fn vertex_buffer(&self) -> &Buffer<'a, V> {
+        &self.vertex_buffer
+    }
+    pub fn vertex_array(&self) -> &VertexArray<'a> {
+        &self.vertex_array
+    }
+    pub fn index_buffer_id(&self) -> u32 {
+        self.index_buffer.id()
+    }
+    pub fn index_count(&self) -> usize {
+        self.index_count
+    }
+    pub fn index_type(&self) -> u32 {
+        self.index_type
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
@@ -0,0 +1,405 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use tracing::instrument;
+
+use crate::{
+    mesh::MeshData,
+    texture::{ColorSpace, Texture, TextureError},
+    vertex::ModelVertex,
+};
+
+/// Everything loaded from an OBJ file and the MTL files it references
+#[derive(Clone, Debug, Default)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: Vec<ObjMaterial>,
+}
+
+impl ObjModel {
+    pub fn material(&self, mesh: &ObjMesh) -> Option<&ObjMaterial> {
+        mesh.material.map(|i| &self.materials[i])
+    }
+}
+
+/// The faces of one object/group that share a material.
+///
+/// A new mesh is started whenever `o`, `g` or `usemtl` changes.
+#[derive(Clone, Debug)]
+pub struct ObjMesh {
+    pub object: String,
+    pub group: String,
+    /// Index into [`ObjModel::materials`]
+    pub material: Option<usize>,
+    pub data: MeshData<ModelVertex>,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    pub shininess: f32,
+    pub dissolve: f32,
+    /// `map_Kd`, resolved relative to the MTL file
+    pub diffuse_texture: Option<PathBuf>,
+}
+
+impl ObjMaterial {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            ambient: [0.0; 3],
+            diffuse: [1.0; 3],
+            specular: [0.0; 3],
+            shininess: 0.0,
+            dissolve: 1.0,
+            diffuse_texture: None,
+        }
+    }
+
+    /// Loads `map_Kd` as sRGB through [`Texture::from_file`], `None` if the material has none
+    pub fn load_diffuse_texture<'a>(
+        &self,
+        gl: &'a gl::Gl,
+        label: Option<&'a str>,
+    ) -> Option<Result<Texture<'a>, TextureError>> {
+        self.diffuse_texture
+            .as_ref()
+            .map(|path| Texture::from_file(gl, path, ColorSpace::Srgb, label))
+    }
+}
+
+/// Loads an OBJ file, along with the MTL files named by `mtllib` (relative to it).
+///
+/// Polygons are triangulated as fans and texture coordinates are flipped
+/// vertically to match how [`Texture`] uploads images.
+/// Faces without normals get a flat normal.
+#[instrument]
+pub fn load_obj(path: impl AsRef<Path> + fmt::Debug) -> Result<ObjModel, ObjError> {
+    let path = path.as_ref();
+    let source = read_file(path)?;
+    let base_dir = path.parent().unwrap_or(Path::new(""));
+
+    parse_obj(&source, base_dir).map_err(|e| e.in_file(path))
+}
+
+/// Parses OBJ source, `mtllib` paths are resolved relative to `base_dir`
+pub fn parse_obj(source: &str, base_dir: &Path) -> Result<ObjModel, ObjError> {
+    let mut positions: Vec<[f32; 3]> = Vec::new();
+    let mut uvs: Vec<[f32; 2]> = Vec::new();
+    let mut normals: Vec<[f32; 3]> = Vec::new();
+
+    let mut model = ObjModel::default();
+    let mut builder = MeshBuilder::default();
+
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let error = |kind| ObjError::Parse {
+            file: None,
+            line: line_number,
+            kind,
+        };
+
+        let line = line.split('#').next().unwrap().trim();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let rest: Vec<&str> = tokens.collect();
+
+        match keyword {
+            "v" => positions.push(parse_floats::<3>(&rest, "v").map_err(error)?),
+            "vt" => {
+                let [u, v] = parse_floats::<2>(&rest, "vt").map_err(error)?;
+                uvs.push([u, 1.0 - v]);
+            }
+            "vn" => normals.push(parse_floats::<3>(&rest, "vn").map_err(error)?),
+            "f" => {
+                if rest.len() < 3 {
+                    return Err(error(ParseErrorKind::MissingValues("f")));
+                }
+
+                let corners = rest
+                    .iter()
+                    .map(|corner| parse_corner(corner, positions.len(), uvs.len(), normals.len()))
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(error)?;
+
+                builder.push_polygon(&corners, &positions, &uvs, &normals);
+            }
+            "o" | "g" => {
+                let name = rest.join(" ");
+                builder.finish(&mut model);
+                if keyword == "o" {
+                    builder.object = name;
+                    builder.group = String::new();
+                } else {
+                    builder.group = name;
+                }
+            }
+            "usemtl" => {
+                let name = rest.join(" ");
+                let material = model
+                    .materials
+                    .iter()
+                    .position(|m| m.name == name)
+                    .ok_or(error(ParseErrorKind::UnknownMaterial(name)))?;
+                builder.finish(&mut model);
+                builder.material = Some(material);
+            }
+            "mtllib" => {
+                for file in rest {
+                    let path = base_dir.join(file);
+                    let source = read_file(&path)?;
+                    let mtl_dir = path.parent().unwrap_or(Path::new(""));
+                    let materials = parse_mtl(&source, mtl_dir).map_err(|e| e.in_file(&path))?;
+                    model.materials.extend(materials);
+                }
+            }
+            // Smoothing groups, lines, points etc. don't affect triangle meshes
+            _ => tracing::trace!("OBJ: Ignoring line {}: {}", line_number, line),
+        }
+    }
+    builder.finish(&mut model);
+
+    tracing::debug!(
+        "OBJ: Loaded {} meshes and {} materials",
+        model.meshes.len(),
+        model.materials.len()
+    );
+
+    Ok(model)
+}
+
+/// Parses MTL source, texture paths are resolved relative to `base_dir`
+pub fn parse_mtl(source: &str, base_dir: &Path) -> Result<Vec<ObjMaterial>, ObjError> {
+    let mut materials: Vec<ObjMaterial> = Vec::new();
+
+    for (i, line) in source.lines().enumerate() {
+        let line_number = i + 1;
+        let error = |kind| ObjError::Parse {
+            file: None,
+            line: line_number,
+            kind,
+        };
+
+        let line = line.split('#').next().unwrap().trim();
+        let mut tokens = line.split_whitespace();
+        let Some(keyword) = tokens.next() else {
+            continue;
+        };
+        let rest: Vec<&str> = tokens.collect();
+
+        if keyword == "newmtl" {
+            materials.push(ObjMaterial::new(&rest.join(" ")));
+            continue;
+        }
+
+        let material = materials
+            .last_mut()
+            .ok_or(error(ParseErrorKind::NoMaterial))?;
+        match keyword {
+            "Ka" => material.ambient = parse_floats::<3>(&rest, "Ka").map_err(error)?,
+            "Kd" => material.diffuse = parse_floats::<3>(&rest, "Kd").map_err(error)?,
+            "Ks" => material.specular = parse_floats::<3>(&rest, "Ks").map_err(error)?,
+            "Ns" => material.shininess = parse_floats::<1>(&rest, "Ns").map_err(error)?[0],
+            "d" => material.dissolve = parse_floats::<1>(&rest, "d").map_err(error)?[0],
+            "Tr" => material.dissolve = 1.0 - parse_floats::<1>(&rest, "Tr").map_err(error)?[0],
+            "map_Kd" => {
+                // Options like `-s 1 1 1` come before the file name
+                let file = rest
+                    .last()
+                    .ok_or(error(ParseErrorKind::MissingValues("map_Kd")))?;
+                material.diffuse_texture = Some(base_dir.join(file));
+            }
+            _ => tracing::trace!("MTL: Ignoring line {}: {}", line_number, line),
+        }
+    }
+
+    Ok(materials)
+}
+
+#[derive(Default)]
+struct MeshBuilder {
+    object: String,
+    group: String,
+    material: Option<usize>,
+    vertices: Vec<ModelVertex>,
+}
+
+impl MeshBuilder {
+    fn push_polygon(
+        &mut self,
+        corners: &[Corner],
+        positions: &[[f32; 3]],
+        uvs: &[[f32; 2]],
+        normals: &[[f32; 3]],
+    ) {
+        let vertex = |corner: &Corner, flat_normal: [f32; 3]| ModelVertex {
+            position: positions[corner.position],
+            uv: corner.uv.map(|i| uvs[i]).unwrap_or_default(),
+            normal: corner.normal.map(|i| normals[i]).unwrap_or(flat_normal),
+        };
+
+        // Triangle fan around the first corner
+        for i in 1..corners.len() - 1 {
+            let triangle = [&corners[0], &corners[i], &corners[i + 1]];
+
+            let [a, b, c] = triangle.map(|c| glam::Vec3::from(positions[c.position]));
+            let flat_normal = (b - a).cross(c - a).normalize_or_zero().to_array();
+
+            self.vertices
+                .extend(triangle.iter().map(|c| vertex(c, flat_normal)));
+        }
+    }
+
+    fn finish(&mut self, model: &mut ObjModel) {
+        if self.vertices.is_empty() {
+            return;
+        }
+
+        model.meshes.push(ObjMesh {
+            object: self.object.clone(),
+            group: self.group.clone(),
+            material: self.material,
+            data: MeshData::from_vertices(&self.vertices),
+        });
+        self.vertices.clear();
+    }
+}
+
+/// Zero-based indices of one face corner
+struct Corner {
+    position: usize,
+    uv: Option<usize>,
+    normal: Option<usize>,
+}
+
+fn parse_corner(
+    corner: &str,
+    positions: usize,
+    uvs: usize,
+    normals: usize,
+) -> Result<Corner, ParseErrorKind> {
+    let mut parts = corner.split('/');
+
+    let position = resolve_index(parts.next(), positions)?
+        .ok_or_else(|| ParseErrorKind::InvalidFace(corner.to_owned()))?;
+    let uv = resolve_index(parts.next(), uvs)?;
+    let normal = resolve_index(parts.next(), normals)?;
+
+    if parts.next().is_some() {
+        return Err(ParseErrorKind::InvalidFace(corner.to_owned()));
+    }
+
+    Ok(Corner {
+        position,
+        uv,
+        normal,
+    })
+}
+
+/// Turns a 1-based (or negative, relative to the end) index into a 0-based one
+fn resolve_index(index: Option<&str>, count: usize) -> Result<Option<usize>, ParseErrorKind> {
+    let index = match index {
+        None | Some("") => return Ok(None),
+        Some(index) => index,
+    };
+
+    let value: i64 = index
+        .parse()
+        .map_err(|_| ParseErrorKind::InvalidNumber(index.to_owned()))?;
+    let resolved = match value {
+        1.. => value - 1,
+        ..=-1 => count as i64 + value,
+        0 => -1,
+    };
+
+    if resolved < 0 || resolved >= count as i64 {
+        return Err(ParseErrorKind::IndexOutOfRange(value));
+    }
+
+    Ok(Some(resolved as usize))
+}
+
+fn parse_floats<const N: usize>(
+    values: &[&str],
+    keyword: &'static str,
+) -> Result<[f32; N], ParseErrorKind> {
+    // Extra values (like the optional w of `v`) are ignored
+    if values.len() < N {
+        return Err(ParseErrorKind::MissingValues(keyword));
+    }
+
+    let mut result = [0.0; N];
+    for (value, text) in result.iter_mut().zip(values) {
+        *value = text
+            .parse()
+            .map_err(|_| ParseErrorKind::InvalidNumber((*text).to_owned()))?;
+    }
+
+    Ok(result)
+}
+
+fn read_file(path: &Path) -> Result<String, ObjError> {
+    std::fs::read_to_string(path).map_err(|source| ObjError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+#[derive(Debug, PartialEq, Eq, thiserror::Error)]
+pub enum ParseErrorKind {
+    #[error("Not a number: {0}")]
+    InvalidNumber(String),
+    #[error("Not enough values for {0}")]
+    MissingValues(&'static str),
+    #[error("Invalid face corner: {0}")]
+    InvalidFace(String),
+    #[error("Index {0} is out of range")]
+    IndexOutOfRange(i64),
+    #[error("Unknown material: {0}")]
+    UnknownMaterial(String),
+    #[error("Material property before any newmtl")]
+    NoMaterial,
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ObjError {
+    #[error("{}line {line}: {kind}", file.as_ref().map(|f| format!("{}, ", f.display())).unwrap_or_default())]
+    Parse {
+        file: Option<PathBuf>,
+        line: usize,
+        kind: ParseErrorKind,
+    },
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+}
+
+impl ObjError {
+    /// Attaches the file a parse error happened in, unless it already has one
+    fn in_file(self, path: &Path) -> Self {
+        match self {
+            Self::Parse {
+                file: None,
+                line,
+                kind,
+            } => Self::Parse {
+                file: Some(path.to_owned()),
+                line,
+                kind,
+            },
+            e => e,
+        }
+    }
+}
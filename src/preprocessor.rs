@@ -0,0 +1,329 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use tracing::instrument;
+
+/// Resolves `#include "file.glsl"` (or `<file.glsl>`) relative to a shader root.
+///
+/// Every included file is wrapped in `#line` directives with its own source string
+/// number, so driver logs can be mapped back through [`ShaderSource::file`].
+/// Files with `#pragma once` are only included the first time, classic
+/// `#ifndef` guards are left to the driver's preprocessor.
+#[derive(Clone, Debug)]
+pub struct Preprocessor {
+    root: PathBuf,
+}
+
+/// Preprocessed GLSL ready for `glShaderSource`, with its source map
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ShaderSource {
+    pub source: String,
+    /// Indexed by the source string number used in the `#line` directives,
+    /// 0 is the file that was loaded (empty for sources that weren't loaded from files)
+    pub files: Vec<PathBuf>,
+}
+
+impl From<&str> for ShaderSource {
+    /// A source that wasn't loaded from a file
+    fn from(source: &str) -> Self {
+        Self {
+            source: source.to_owned(),
+            files: Vec::new(),
+        }
+    }
+}
+
+impl From<String> for ShaderSource {
+    fn from(source: String) -> Self {
+        Self {
+            source,
+            files: Vec::new(),
+        }
+    }
+}
+
+impl ShaderSource {
+    /// The file behind a source string number from a driver log
+    pub fn file(&self, source_number: usize) -> Option<&Path> {
+        self.files.get(source_number).map(PathBuf::as_path)
+    }
+}
+
+/// `#define`s injected right after `#version`, e.g. feature toggles like `HAS_NORMAL_MAP`.
+///
+/// Kept sorted, so equal sets compare and hash equal whatever order they were added in.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Defines(BTreeMap<String, String>);
+
+impl Defines {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a define without a value, for `#ifdef`
+    pub fn with(mut self, name: impl Into<String>) -> Self {
+        self.insert(name, "");
+        self
+    }
+
+    pub fn with_value(mut self, name: impl Into<String>, value: impl ToString) -> Self {
+        self.insert(name, value);
+        self
+    }
+
+    pub fn insert(&mut self, name: impl Into<String>, value: impl ToString) {
+        self.0.insert(name.into(), value.to_string());
+    }
+
+    pub fn remove(&mut self, name: &str) {
+        self.0.remove(name);
+    }
+
+    pub fn contains(&self, name: &str) -> bool {
+        self.0.contains_key(name)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+    }
+
+    pub fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Inserts the defines after the `#version` line (or at the top without one),
+    /// followed by a `#line` so the following lines keep their numbers
+    pub fn apply(&self, source: &str) -> String {
+        if self.is_empty() {
+            return source.to_owned();
+        }
+
+        let mut lines = source.lines();
+        let version = source
+            .lines()
+            .position(|line| line.trim_start().starts_with("#version"));
+        let mut output = String::with_capacity(source.len());
+        for line in lines.by_ref().take(version.map_or(0, |i| i + 1)) {
+            output.push_str(line);
+            output.push('\n');
+        }
+
+        for (name, value) in self.iter() {
+            match value {
+                "" => output.push_str(&format!("#define {}\n", name)),
+                value => output.push_str(&format!("#define {} {}\n", name, value)),
+            }
+        }
+        output.push_str(&format!("#line {}\n", version.map_or(1, |i| i + 2)));
+
+        for line in lines {
+            output.push_str(line);
+            output.push('\n');
+        }
+
+        output
+    }
+}
+
+impl<S: Into<String>> FromIterator<S> for Defines {
+    /// Defines without values
+    fn from_iter<I: IntoIterator<Item = S>>(names: I) -> Self {
+        names.into_iter().fold(Self::new(), Self::with)
+    }
+}
+
+#[derive(Default)]
+struct State {
+    source: String,
+    files: Vec<PathBuf>,
+    canonical_files: Vec<PathBuf>,
+    once: HashSet<PathBuf>,
+    stack: Vec<PathBuf>,
+}
+
+impl Preprocessor {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self { root: root.into() }
+    }
+
+    /// Loads `path` (relative to the root) and resolves its includes
+    #[instrument(skip(self))]
+    pub fn load(
+        &self,
+        path: impl AsRef<Path> + fmt::Debug,
+    ) -> Result<ShaderSource, PreprocessError> {
+        let path = self.root.join(path);
+        let source = read_file(&path)?;
+
+        self.process(&source, path)
+    }
+
+    /// Resolves the includes of `source`, which is called `name` in the source map
+    pub fn process(
+        &self,
+        source: &str,
+        name: impl Into<PathBuf>,
+    ) -> Result<ShaderSource, PreprocessError> {
+        let name = name.into();
+        let canonical = name.canonicalize().unwrap_or_else(|_| name.clone());
+
+        let mut state = State::default();
+        state.files.push(name);
+        state.canonical_files.push(canonical.clone());
+        state.stack.push(canonical);
+        self.expand(&mut state, source, 0)?;
+
+        Ok(ShaderSource {
+            source: state.source,
+            files: state.files,
+        })
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+}
+
+impl Preprocessor {
+    fn expand(&self, state: &mut State, source: &str, file: usize) -> Result<(), PreprocessError> {
+        for (i, line) in source.lines().enumerate() {
+            let line_number = i + 1;
+            let directive = line
+                .trim_start()
+                .strip_prefix('#')
+                .map(|directive| directive.trim_start());
+
+            match directive {
+                Some(directive) if directive.starts_with("include") => {
+                    let include =
+                        parse_include(&directive["include".len()..]).ok_or_else(|| {
+                            PreprocessError::MalformedInclude {
+                                file: state.files[file].clone(),
+                                line: line_number,
+                            }
+                        })?;
+                    self.include(state, include, file, line_number)?;
+                    // Back to the including file, on the line after the #include
+                    state
+                        .source
+                        .push_str(&format!("#line {} {}\n", line_number + 1, file));
+                }
+                Some(directive) if directive.split_whitespace().eq(["pragma", "once"]) => {
+                    let canonical = state.canonical_files[file].clone();
+                    state.once.insert(canonical);
+                    // Keeps the line numbers intact
+                    state.source.push('\n');
+                }
+                _ => {
+                    state.source.push_str(line);
+                    state.source.push('\n');
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn include(
+        &self,
+        state: &mut State,
+        include: &str,
+        parent: usize,
+        line: usize,
+    ) -> Result<(), PreprocessError> {
+        let path = self.root.join(include);
+        let canonical = path
+            .canonicalize()
+            .map_err(|source| PreprocessError::IncludeNotFound {
+                file: state.files[parent].clone(),
+                line,
+                path: path.clone(),
+                source,
+            })?;
+
+        if state.stack.contains(&canonical) {
+            let mut cycle = state.stack.clone();
+            cycle.push(canonical);
+            return Err(PreprocessError::IncludeCycle(cycle));
+        }
+        if state.once.contains(&canonical) {
+            tracing::trace!("Shader: Skipping {} (#pragma once)", path.display());
+            return Ok(());
+        }
+
+        let source = read_file(&path)?;
+        let file = match state.canonical_files.iter().position(|f| *f == canonical) {
+            Some(file) => file,
+            None => {
+                state.files.push(path);
+                state.canonical_files.push(canonical.clone());
+                state.files.len() - 1
+            }
+        };
+        tracing::trace!(
+            "Shader: Including {} as source string {}",
+            state.files[file].display(),
+            file
+        );
+
+        state.source.push_str(&format!("#line 1 {}\n", file));
+        state.stack.push(canonical);
+        self.expand(state, &source, file)?;
+        state.stack.pop();
+
+        Ok(())
+    }
+}
+
+/// `"file.glsl"` or `<file.glsl>`, ignoring a trailing `//` comment
+fn parse_include(rest: &str) -> Option<&str> {
+    let rest = rest.split("//").next().unwrap().trim();
+    let path = rest
+        .strip_prefix('"')
+        .and_then(|rest| rest.strip_suffix('"'))
+        .or_else(|| {
+            rest.strip_prefix('<')
+                .and_then(|rest| rest.strip_suffix('>'))
+        })?;
+
+    (!path.is_empty()).then_some(path)
+}
+
+fn read_file(path: &Path) -> Result<String, PreprocessError> {
+    std::fs::read_to_string(path).map_err(|source| PreprocessError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum PreprocessError {
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("{}:{line}: Expected #include \"file\" or #include <file>", file.display())]
+    MalformedInclude { file: PathBuf, line: usize },
+    #[error("{}:{line}: Can't include {}: {source}", file.display(), path.display())]
+    IncludeNotFound {
+        file: PathBuf,
+        line: usize,
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Include cycle: {}", .0.iter().map(|path| path.display().to_string()).collect::<Vec<_>>().join(" -> "))]
+    IncludeCycle(Vec<PathBuf>),
+}
@@ -1,87 +1,208 @@
 // Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
 // SPDX-License-Identifier: MIT
 
-use std::ffi::CString;
+use std::{
+    cell::{OnceCell, RefCell},
+    collections::HashMap,
+    ffi::CString,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use tracing::instrument;
+
+use crate::{
+    diagnostics::CompileLog,
+    preprocessor::{Defines, PreprocessError, Preprocessor, ShaderSource},
+    program_cache::ProgramCache,
+    reflection::{self, ProgramInterface},
+    spirv::SpirvShader,
+    uniform::{Uniform, UniformError},
+};
 
 pub struct Program<'a> {
     gl: &'a gl::Gl,
     id: u32,
     label: Option<&'a str>,
+    /// Looked up on the first [`Program::set`] of each name, `None` for inactive uniforms
+    uniforms: RefCell<HashMap<String, Option<CachedUniform>>>,
+    /// Only queried in debug builds, to check the types passed to [`Program::set`]
+    interface: OnceCell<ProgramInterface>,
+}
+
+#[derive(Clone, Copy, Debug)]
+struct CachedUniform {
+    location: i32,
+    /// `None` in release builds
+    gl_type: Option<u32>,
 }
 
 impl<'a> Program<'a> {
+    /// Starts a program with any combination of stages
+    pub fn builder(gl: &'a gl::Gl) -> ProgramBuilder<'a> {
+        ProgramBuilder {
+            gl,
+            stages: Vec::new(),
+            defines: Defines::new(),
+            label: None,
+            cache: None,
+        }
+    }
+
+    /// `defines` are inserted after the `#version` line of both shaders
     pub fn from_source(
         gl: &'a gl::Gl,
         vertex: &str,
         fragment: &str,
+        defines: &Defines,
         label: Option<&'a str>,
     ) -> Result<Self, ProgramError> {
-        tracing::trace!("Shader: Compiling vertex shader: {}", vertex);
-        let vertex = create_shader(
+        Self::link(
             gl,
-            vertex,
-            gl::VERTEX_SHADER,
-            if let Some(label) = label {
-                Some(format!("{} - vertex shader", label))
-            } else {
-                None
-            },
-        )?;
-
-        tracing::trace!("Shader: Compiling fragment shader: {}", fragment);
-        let fragment = create_shader(
+            &[
+                (gl::VERTEX_SHADER, &vertex.into()),
+                (gl::FRAGMENT_SHADER, &fragment.into()),
+            ],
+            defines,
+            label,
+            None,
+        )
+    }
+
+    /// Loads both shaders through `preprocessor`, resolving their `#include`s
+    #[instrument(skip(gl, preprocessor))]
+    pub fn from_files(
+        gl: &'a gl::Gl,
+        preprocessor: &Preprocessor,
+        vertex: impl AsRef<Path> + fmt::Debug,
+        fragment: impl AsRef<Path> + fmt::Debug,
+        defines: &Defines,
+        label: Option<&'a str>,
+    ) -> Result<Self, ProgramError> {
+        let vertex = preprocessor.load(vertex)?;
+        let fragment = preprocessor.load(fragment)?;
+
+        Self::from_preprocessed(gl, &vertex, &fragment, defines, label)
+    }
+
+    pub fn from_preprocessed(
+        gl: &'a gl::Gl,
+        vertex: &ShaderSource,
+        fragment: &ShaderSource,
+        defines: &Defines,
+        label: Option<&'a str>,
+    ) -> Result<Self, ProgramError> {
+        Self::link(
             gl,
-            fragment,
-            gl::FRAGMENT_SHADER,
-            if let Some(label) = label {
-                Some(format!("{} - fragment shader", label))
-            } else {
-                None
-            },
-        )?;
+            &[(gl::VERTEX_SHADER, vertex), (gl::FRAGMENT_SHADER, fragment)],
+            defines,
+            label,
+            None,
+        )
+    }
+
+    /// Specializes every `(shader type, module)` stage and links them, needs
+    /// `GL_ARB_gl_spirv`. SPIR-V and GLSL stages can't be mixed in one program.
+    pub fn from_spirv(
+        gl: &'a gl::Gl,
+        stages: &[(u32, &SpirvShader)],
+        label: Option<&'a str>,
+    ) -> Result<Self, ProgramError> {
+        if stages.is_empty() {
+            return Err(ProgramError::NoStages);
+        }
+        if !gl.SpecializeShaderARB.is_loaded() {
+            return Err(ProgramError::SpirvUnsupported);
+        }
 
-        let id = unsafe { gl.CreateProgram() };
-        if id == 0 {
-            return Err(ProgramError::CreationError);
+        let mut shaders = Vec::with_capacity(stages.len());
+        for &(shader_type, shader) in stages {
+            match create_spirv_shader(
+                gl,
+                shader,
+                shader_type,
+                label.map(|label| format!("{} - {}", label, stage_name(shader_type))),
+            ) {
+                Ok(shader) => shaders.push(shader),
+                Err(e) => {
+                    for shader in shaders {
+                        unsafe { gl.DeleteShader(shader) };
+                    }
+                    return Err(e);
+                }
+            }
         }
 
-        unsafe {
-            gl.AttachShader(id, vertex);
-            gl.AttachShader(id, fragment);
-
-            gl.LinkProgram(id);
-
-            gl.DetachShader(id, vertex);
-            gl.DetachShader(id, fragment);
-
-            gl.DeleteShader(vertex);
-            gl.DeleteShader(fragment);
-
-            let mut status = 0;
-            gl.GetProgramiv(id, gl::LINK_STATUS, &mut status);
-            if status != (gl::TRUE as i32) {
-                let mut info_log_len = 0;
-                gl.GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut info_log_len);
-                let mut buffer: Vec<u8> = Vec::with_capacity(info_log_len as usize);
-                gl.GetProgramInfoLog(
-                    id,
-                    info_log_len,
-                    &mut info_log_len,
-                    buffer.as_mut_ptr().cast(),
-                );
-                buffer.set_len(info_log_len as usize);
-
-                let info_log = String::from_utf8(buffer).expect("Failed to read info_log");
-                // tracing::debug!("Failed to link shader program: info_log: {}", info_log);
-                return Err(ProgramError::ShaderCompilationError(info_log));
+        let id = link_shaders(gl, shaders, false)?;
+
+        Ok(Self::from_linked(gl, id, label))
+    }
+}
+
+impl<'a> Program<'a> {
+    /// Compiles every `(shader type, source)` stage and links them, going through
+    /// `cache` first if there is one
+    pub(crate) fn link(
+        gl: &'a gl::Gl,
+        stages: &[(u32, &ShaderSource)],
+        defines: &Defines,
+        label: Option<&'a str>,
+        cache: Option<&ProgramCache>,
+    ) -> Result<Self, ProgramError> {
+        let key = cache.map(|cache| cache.key(stages, defines));
+        if let (Some(cache), Some(key)) = (cache, key) {
+            if let Some(program) = cache.load(gl, key, label) {
+                return Ok(program);
+            }
+        }
+
+        let mut shaders = Vec::with_capacity(stages.len());
+        for &(shader_type, source) in stages {
+            let code = defines.apply(&source.source);
+            tracing::trace!("Shader: Compiling {}: {}", stage_name(shader_type), code);
+
+            match create_shader(
+                gl,
+                (&code, &source.files),
+                shader_type,
+                label.map(|label| format!("{} - {}", label, stage_name(shader_type))),
+            ) {
+                Ok(shader) => shaders.push(shader),
+                Err(e) => {
+                    for shader in shaders {
+                        unsafe { gl.DeleteShader(shader) };
+                    }
+                    return Err(e);
+                }
             }
+        }
+
+        let id = link_shaders(gl, shaders, cache.is_some())?;
+        let program = Self::from_linked(gl, id, label);
+        if let (Some(cache), Some(key)) = (cache, key) {
+            if let Err(e) = cache.store(gl, &program, key) {
+                tracing::warn!("Shader: Failed to cache program ({}): {}", id, e);
+            }
+        }
+
+        Ok(program)
+    }
 
-            if let Some(label) = label {
+    /// Wraps the successfully linked program `id`
+    pub(crate) fn from_linked(gl: &'a gl::Gl, id: u32, label: Option<&'a str>) -> Self {
+        if let Some(label) = label {
+            unsafe {
                 gl.ObjectLabel(gl::PROGRAM, id, label.len() as i32, label.as_ptr().cast());
             }
         }
 
-        Ok(Self { gl, id, label })
+        Self {
+            gl,
+            id,
+            label,
+            uniforms: RefCell::new(HashMap::new()),
+            interface: OnceCell::new(),
+        }
     }
 }
 
@@ -94,7 +215,72 @@ impl<'a> Program<'a> {
             return None;
         }
 
-        return Some(uniform);
+        Some(uniform)
+    }
+
+    /// Lists the active uniforms, inputs, outputs and blocks
+    pub fn reflect(&self) -> ProgramInterface {
+        ProgramInterface::query(self.gl, self.id)
+    }
+
+    /// Writes `value` to the uniform `name`, without binding the program.
+    ///
+    /// Errors are logged, an inactive uniform only on the first call.
+    pub fn set(&self, name: &str, value: impl Uniform) {
+        match self.try_set(name, value) {
+            Ok(()) | Err(UniformError::NotActive(_)) => {}
+            Err(e) => tracing::error!("Program ({}): {}", self.id, e),
+        }
+    }
+
+    /// Like [`Program::set`], but returns the error.
+    ///
+    /// In debug builds the type of `value` is checked against the reflected type first.
+    pub fn try_set<T: Uniform>(&self, name: &str, value: T) -> Result<(), UniformError> {
+        let uniform = self
+            .cached_uniform(name)
+            .ok_or_else(|| UniformError::NotActive(name.to_owned()))?;
+
+        if let Some(gl_type) = uniform.gl_type {
+            if !T::GL_TYPES.contains(&gl_type) {
+                return Err(UniformError::TypeMismatch {
+                    name: name.to_owned(),
+                    expected: reflection::type_name(gl_type),
+                    found: std::any::type_name::<T>(),
+                });
+            }
+        }
+
+        unsafe { value.set(self.gl, self.id, uniform.location) };
+        Ok(())
+    }
+
+    fn cached_uniform(&self, name: &str) -> Option<CachedUniform> {
+        if let Some(uniform) = self.uniforms.borrow().get(name) {
+            return *uniform;
+        }
+
+        let uniform = unsafe { self.get_unifrom(name) }.map(|location| CachedUniform {
+            location,
+            gl_type: cfg!(debug_assertions)
+                .then(|| {
+                    self.interface
+                        .get_or_init(|| self.reflect())
+                        .uniform(name)
+                        .map(|uniform| uniform.gl_type)
+                })
+                .flatten(),
+        });
+        if uniform.is_none() {
+            tracing::warn!(
+                "Program ({}): {}",
+                self.id,
+                UniformError::NotActive(name.to_owned())
+            );
+        }
+
+        self.uniforms.borrow_mut().insert(name.to_owned(), uniform);
+        uniform
     }
 
     pub unsafe fn bind(&self) {
@@ -122,19 +308,161 @@ impl Drop for Program<'_> {
     }
 }
 
+/// Collects the stages of a [`Program`], see [`Program::builder`].
+///
+/// Sources are `&str`s or preprocessed [`ShaderSource`]s.
+pub struct ProgramBuilder<'a> {
+    gl: &'a gl::Gl,
+    stages: Vec<(u32, ShaderSource)>,
+    defines: Defines,
+    label: Option<&'a str>,
+    cache: Option<&'a ProgramCache>,
+}
+
+impl<'a> ProgramBuilder<'a> {
+    pub fn vertex(self, source: impl Into<ShaderSource>) -> Self {
+        self.stage(gl::VERTEX_SHADER, source.into())
+    }
+
+    pub fn tess_control(self, source: impl Into<ShaderSource>) -> Self {
+        self.stage(gl::TESS_CONTROL_SHADER, source.into())
+    }
+
+    pub fn tess_evaluation(self, source: impl Into<ShaderSource>) -> Self {
+        self.stage(gl::TESS_EVALUATION_SHADER, source.into())
+    }
+
+    pub fn geometry(self, source: impl Into<ShaderSource>) -> Self {
+        self.stage(gl::GEOMETRY_SHADER, source.into())
+    }
+
+    pub fn fragment(self, source: impl Into<ShaderSource>) -> Self {
+        self.stage(gl::FRAGMENT_SHADER, source.into())
+    }
+
+    /// Inserted after the `#version` line of every stage
+    pub fn defines(mut self, defines: Defines) -> Self {
+        self.defines = defines;
+        self
+    }
+
+    pub fn label(mut self, label: &'a str) -> Self {
+        self.label = Some(label);
+        self
+    }
+
+    /// Loads the program from `cache` when possible, and stores it there after compiling
+    pub fn cache(mut self, cache: &'a ProgramCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    pub fn build(&self) -> Result<Program<'a>, ProgramError> {
+        if self.stages.is_empty() {
+            return Err(ProgramError::NoStages);
+        }
+
+        let stages: Vec<_> = self
+            .stages
+            .iter()
+            .map(|(shader_type, source)| (*shader_type, source))
+            .collect();
+        Program::link(self.gl, &stages, &self.defines, self.label, self.cache)
+    }
+
+    /// Replaces the stage if it was already set
+    fn stage(mut self, shader_type: u32, source: ShaderSource) -> Self {
+        self.stages.retain(|(stage, _)| *stage != shader_type);
+        self.stages.push((shader_type, source));
+        self
+    }
+}
+
+/// Builds permutations of one pair of shaders on demand, keeping every linked
+/// [`Program`] so a set of defines is only compiled once
+pub struct ProgramVariants<'a> {
+    gl: &'a gl::Gl,
+    vertex: ShaderSource,
+    fragment: ShaderSource,
+    variants: HashMap<Defines, Program<'a>>,
+    label: Option<&'a str>,
+}
+
+impl<'a> ProgramVariants<'a> {
+    pub fn from_source(
+        gl: &'a gl::Gl,
+        vertex: &str,
+        fragment: &str,
+        label: Option<&'a str>,
+    ) -> Self {
+        Self::from_preprocessed(gl, vertex.into(), fragment.into(), label)
+    }
+
+    pub fn from_preprocessed(
+        gl: &'a gl::Gl,
+        vertex: ShaderSource,
+        fragment: ShaderSource,
+        label: Option<&'a str>,
+    ) -> Self {
+        Self {
+            gl,
+            vertex,
+            fragment,
+            variants: HashMap::new(),
+            label,
+        }
+    }
+
+    /// The program for `defines`, built the first time it's asked for
+    pub fn get(&mut self, defines: &Defines) -> Result<&Program<'a>, ProgramError> {
+        if !self.variants.contains_key(defines) {
+            tracing::debug!("Shader: Building variant {:?}", defines);
+            let program = Program::from_preprocessed(
+                self.gl,
+                &self.vertex,
+                &self.fragment,
+                defines,
+                self.label,
+            )?;
+            self.variants.insert(defines.clone(), program);
+        }
+
+        Ok(&self.variants[defines])
+    }
+
+    /// Number of variants built so far
+    pub fn len(&self) -> usize {
+        self.variants.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.variants.is_empty()
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum ProgramError {
     #[error("Shader/Program creation failed")]
     CreationError,
-    #[error("Shader compilation: {0}")]
-    ShaderCompilationError(String),
+    #[error("A program needs at least one stage")]
+    NoStages,
+    #[error("Shader compilation failed:\n{0}")]
+    ShaderCompilationError(CompileLog),
     #[error("Program linkage: {0}")]
     ProgramLinkageError(String),
+    #[error("Preprocessing: {0}")]
+    PreprocessError(#[from] PreprocessError),
+    #[error("SPIR-V shaders need GL_ARB_gl_spirv, which the driver doesn't support")]
+    SpirvUnsupported,
+    #[error("Specializing {name} failed: {log}")]
+    SpecializationError { name: String, log: String },
 }
 
-fn create_shader<'a>(
-    gl: &'a gl::Gl,
-    source: &str,
+fn create_shader(
+    gl: &gl::Gl,
+    (source, files): (&str, &[PathBuf]),
     shader_type: u32,
     label: Option<String>,
 ) -> Result<u32, ProgramError> {
@@ -146,7 +474,7 @@ fn create_shader<'a>(
     }
 
     unsafe {
-        if let Some(label) = label {
+        if let Some(label) = &label {
             gl.ObjectLabel(gl::SHADER, id, label.len() as i32, label.as_ptr().cast());
             tracing::trace!("Adding label to Shader ({}): {}", id, label);
         }
@@ -180,10 +508,138 @@ fn create_shader<'a>(
 
             let info_log = String::from_utf8(buffer).expect("Failed to read info_log");
             // tracing::debug!("Failed to compile shader: info_log: {}", info_log);
+            gl.DeleteShader(id);
 
-            return Err(ProgramError::ShaderCompilationError(info_log));
+            let name = label.unwrap_or_else(|| format!("<{}>", stage_name(shader_type)));
+            let log = CompileLog::new(info_log, source, files, &name);
+            return Err(ProgramError::ShaderCompilationError(log));
         }
     }
 
     Ok(id)
 }
+
+fn create_spirv_shader(
+    gl: &gl::Gl,
+    shader: &SpirvShader,
+    shader_type: u32,
+    label: Option<String>,
+) -> Result<u32, ProgramError> {
+    let id = unsafe { gl.CreateShader(shader_type) };
+    if id == 0 {
+        return Err(ProgramError::CreationError);
+    }
+
+    let entry_point = CString::new(shader.entry_point_name()).expect("Failed to make CString");
+    let (indices, values): (Vec<u32>, Vec<u32>) = shader.constants().iter().copied().unzip();
+    unsafe {
+        if let Some(label) = &label {
+            gl.ObjectLabel(gl::SHADER, id, label.len() as i32, label.as_ptr().cast());
+        }
+
+        let bytes = shader.bytes();
+        gl.ShaderBinary(
+            1,
+            &id,
+            gl::SHADER_BINARY_FORMAT_SPIR_V_ARB,
+            bytes.as_ptr().cast(),
+            bytes.len() as i32,
+        );
+        gl.SpecializeShaderARB(
+            id,
+            entry_point.as_ptr(),
+            indices.len() as u32,
+            indices.as_ptr(),
+            values.as_ptr(),
+        );
+        tracing::trace!(
+            "Shader: Specialized SPIR-V Shader ({}) at {:?} with {:?}",
+            id,
+            shader.entry_point_name(),
+            shader.constants()
+        );
+
+        let mut status: i32 = 0;
+        gl.GetShaderiv(id, gl::COMPILE_STATUS, &mut status);
+        if status != (gl::TRUE as i32) {
+            let mut info_log_len = 0;
+            gl.GetShaderiv(id, gl::INFO_LOG_LENGTH, &mut info_log_len);
+            let mut buffer: Vec<u8> = Vec::with_capacity(info_log_len as usize);
+            gl.GetShaderInfoLog(
+                id,
+                info_log_len,
+                &mut info_log_len,
+                buffer.as_mut_ptr().cast(),
+            );
+            buffer.set_len(info_log_len as usize);
+            gl.DeleteShader(id);
+
+            let name = label.unwrap_or_else(|| format!("<{}>", stage_name(shader_type)));
+            let log = String::from_utf8_lossy(&buffer).into_owned();
+            return Err(ProgramError::SpecializationError { name, log });
+        }
+    }
+
+    Ok(id)
+}
+
+/// Links and then deletes `shaders`, `retrievable` asks for a binary that can be cached
+fn link_shaders(gl: &gl::Gl, shaders: Vec<u32>, retrievable: bool) -> Result<u32, ProgramError> {
+    let id = unsafe { gl.CreateProgram() };
+    if id == 0 {
+        for shader in shaders {
+            unsafe { gl.DeleteShader(shader) };
+        }
+        return Err(ProgramError::CreationError);
+    }
+
+    unsafe {
+        for &shader in &shaders {
+            gl.AttachShader(id, shader);
+        }
+
+        if retrievable {
+            gl.ProgramParameteri(id, gl::PROGRAM_BINARY_RETRIEVABLE_HINT, gl::TRUE as i32);
+        }
+        gl.LinkProgram(id);
+
+        for shader in shaders {
+            gl.DetachShader(id, shader);
+            gl.DeleteShader(shader);
+        }
+
+        let mut status = 0;
+        gl.GetProgramiv(id, gl::LINK_STATUS, &mut status);
+        if status != (gl::TRUE as i32) {
+            let mut info_log_len = 0;
+            gl.GetProgramiv(id, gl::INFO_LOG_LENGTH, &mut info_log_len);
+            let mut buffer: Vec<u8> = Vec::with_capacity(info_log_len as usize);
+            gl.GetProgramInfoLog(
+                id,
+                info_log_len,
+                &mut info_log_len,
+                buffer.as_mut_ptr().cast(),
+            );
+            buffer.set_len(info_log_len as usize);
+
+            let info_log = String::from_utf8(buffer).expect("Failed to read info_log");
+            // tracing::debug!("Failed to link shader program: info_log: {}", info_log);
+            gl.DeleteProgram(id);
+            return Err(ProgramError::ProgramLinkageError(info_log));
+        }
+    }
+
+    Ok(id)
+}
+
+fn stage_name(shader_type: u32) -> &'static str {
+    match shader_type {
+        gl::VERTEX_SHADER => "vertex shader",
+        gl::TESS_CONTROL_SHADER => "tessellation control shader",
+        gl::TESS_EVALUATION_SHADER => "tessellation evaluation shader",
+        gl::GEOMETRY_SHADER => "geometry shader",
+        gl::FRAGMENT_SHADER => "fragment shader",
+        gl::COMPUTE_SHADER => "compute shader",
+        _ => "shader",
+    }
+}
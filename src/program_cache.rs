@@ -0,0 +1,212 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    ffi::CStr,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    preprocessor::{Defines, ShaderSource},
+    program::Program,
+};
+
+const MAGIC: &[u8; 4] = b"GLPB";
+
+/// Linked program binaries stored on disk, from `glGetProgramBinary`.
+///
+/// Entries are keyed by the sources, the defines and the driver, so a driver update
+/// just means compiling again. Drivers may still reject a binary, which is then
+/// treated like a miss.
+#[derive(Clone, Debug)]
+pub struct ProgramCache {
+    dir: PathBuf,
+    /// Vendor, renderer and version
+    driver: String,
+    /// Whether the driver supports any binary formats
+    enabled: bool,
+}
+
+impl ProgramCache {
+    pub fn new(gl: &gl::Gl, dir: impl Into<PathBuf>) -> Self {
+        let string = |name| unsafe {
+            let string = gl.GetString(name);
+            if string.is_null() {
+                return String::new();
+            }
+            CStr::from_ptr(string.cast()).to_string_lossy().into_owned()
+        };
+        let driver = format!(
+            "{}\n{}\n{}",
+            string(gl::VENDOR),
+            string(gl::RENDERER),
+            string(gl::VERSION)
+        );
+
+        let mut formats = 0;
+        unsafe {
+            gl.GetIntegerv(gl::NUM_PROGRAM_BINARY_FORMATS, &mut formats);
+        }
+        if formats == 0 {
+            tracing::warn!("Shader: The driver has no program binary formats, not caching");
+        }
+
+        Self {
+            dir: dir.into(),
+            driver,
+            enabled: formats > 0,
+        }
+    }
+
+    /// Hashes the stages as they'd be compiled, with `defines` applied
+    pub fn key(&self, stages: &[(u32, &ShaderSource)], defines: &Defines) -> u64 {
+        let mut hash = Fnv1a::new();
+        hash.write(self.driver.as_bytes());
+        for &(shader_type, source) in stages {
+            hash.write(&shader_type.to_le_bytes());
+            hash.write(defines.apply(&source.source).as_bytes());
+        }
+
+        hash.finish()
+    }
+
+    /// The cached program for `key`, `None` if there is none or the driver rejected it
+    pub fn load<'a>(
+        &self,
+        gl: &'a gl::Gl,
+        key: u64,
+        label: Option<&'a str>,
+    ) -> Option<Program<'a>> {
+        if !self.enabled {
+            return None;
+        }
+
+        let path = self.path(key);
+        let data = fs::read(&path).ok()?;
+        let Some((format, binary)) = parse(&data) else {
+            tracing::warn!("Shader: Ignoring malformed cache entry {}", path.display());
+            return None;
+        };
+
+        let id = unsafe { gl.CreateProgram() };
+        // A miss, compiling reports the failure
+        if id == 0 {
+            tracing::warn!("Shader: Failed to create a program for {}", path.display());
+            return None;
+        }
+        let mut status = 0;
+        unsafe {
+            gl.ProgramBinary(id, format, binary.as_ptr().cast(), binary.len() as i32);
+            gl.GetProgramiv(id, gl::LINK_STATUS, &mut status);
+        }
+        if status != gl::TRUE as i32 {
+            tracing::debug!(
+                "Shader: The driver rejected cached program {}, recompiling",
+                path.display()
+            );
+            unsafe { gl.DeleteProgram(id) };
+            return None;
+        }
+
+        tracing::trace!("Shader: Loaded program ({}) from {}", id, path.display());
+        Some(Program::from_linked(gl, id, label))
+    }
+
+    /// Writes the binary of `program` under `key`
+    pub fn store(&self, gl: &gl::Gl, program: &Program, key: u64) -> io::Result<()> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let mut len = 0;
+        unsafe {
+            gl.GetProgramiv(program.id(), gl::PROGRAM_BINARY_LENGTH, &mut len);
+        }
+        let mut binary = vec![0u8; len as usize];
+        let mut format = 0;
+        unsafe {
+            gl.GetProgramBinary(
+                program.id(),
+                len,
+                &mut len,
+                &mut format,
+                binary.as_mut_ptr().cast(),
+            );
+        }
+        binary.truncate(len as usize);
+        if binary.is_empty() {
+            return Ok(());
+        }
+
+        let mut data = Vec::with_capacity(binary.len() + 8);
+        data.extend_from_slice(MAGIC);
+        data.extend_from_slice(&format.to_le_bytes());
+        data.extend_from_slice(&binary);
+
+        // Written next to the entry and renamed, so a crash never leaves half an entry
+        fs::create_dir_all(&self.dir)?;
+        let path = self.path(key);
+        let partial = path.with_extension("partial");
+        fs::write(&partial, data)?;
+        fs::rename(&partial, &path)?;
+        tracing::trace!(
+            "Shader: Cached program ({}) in {}",
+            program.id(),
+            path.display()
+        );
+
+        Ok(())
+    }
+
+    /// Removes every cached program
+    pub fn clear(&self) -> io::Result<()> {
+        match fs::remove_dir_all(&self.dir) {
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    }
+
+    /// The file the program for `key` is stored in
+    pub fn path(&self, key: u64) -> PathBuf {
+        self.dir.join(format!("{:016x}.bin", key))
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+    pub fn is_enabled(&self) -> bool {
+        self.enabled
+    }
+}
+
+/// `GLPB`, the binary format as a little endian `u32`, then the binary
+fn parse(data: &[u8]) -> Option<(u32, &[u8])> {
+    let data = data.strip_prefix(MAGIC)?;
+    let (format, binary) = data.split_first_chunk::<4>()?;
+
+    (!binary.is_empty()).then_some((u32::from_le_bytes(*format), binary))
+}
+
+/// 64-bit FNV-1a, unlike `DefaultHasher` it's the same across Rust versions
+struct Fnv1a(u64);
+
+impl Fnv1a {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+        // Keeps ("ab", "c") and ("a", "bc") apart
+        self.0 ^= bytes.len() as u64;
+        self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+    }
+
+    fn finish(&self) -> u64 {
+        self.0
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use image::RgbaImage;
+
+use crate::texture::Texture;
+
+/// Reads a region of `framebuffer` (0 for the default one) into an image.
+///
+/// `source` is the buffer to read from, e.g. `gl::FRONT`/`gl::BACK` for the default
+/// framebuffer or `gl::COLOR_ATTACHMENTi` for a framebuffer object.
+/// The image is flipped so the top row comes first.
+pub fn read_framebuffer(
+    gl: &gl::Gl,
+    framebuffer: u32,
+    source: u32,
+    region: (i32, i32, u32, u32),
+) -> RgbaImage {
+    let (x, y, width, height) = region;
+    let mut data = vec![0u8; width as usize * height as usize * 4];
+
+    unsafe {
+        let mut previous_framebuffer = 0;
+        gl.GetIntegerv(gl::READ_FRAMEBUFFER_BINDING, &mut previous_framebuffer);
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, framebuffer);
+
+        let mut previous_source = 0;
+        gl.GetIntegerv(gl::READ_BUFFER, &mut previous_source);
+        gl.ReadBuffer(source);
+
+        let mut previous_alignment = 0;
+        gl.GetIntegerv(gl::PACK_ALIGNMENT, &mut previous_alignment);
+        gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+
+        gl.ReadnPixels(
+            x,
+            y,
+            width as i32,
+            height as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data.len() as i32,
+            data.as_mut_ptr().cast(),
+        );
+        tracing::trace!(
+            "Read back Framebuffer ({}) ({} x {} at {}, {})",
+            framebuffer,
+            width,
+            height,
+            x,
+            y
+        );
+
+        gl.PixelStorei(gl::PACK_ALIGNMENT, previous_alignment);
+        gl.ReadBuffer(previous_source as u32);
+        gl.BindFramebuffer(gl::READ_FRAMEBUFFER, previous_framebuffer as u32);
+    }
+
+    let image =
+        RgbaImage::from_raw(width, height, data).expect("Readback buffer has the wrong size");
+    image::imageops::flip_vertical(&image)
+}
+
+/// Reads mip `level` of a texture into an image, e.g. a framebuffer color attachment.
+///
+/// Like [`read_framebuffer`] the image is flipped so the top row comes first.
+pub fn read_texture(gl: &gl::Gl, texture: &Texture, level: u32) -> RgbaImage {
+    let (width, height) = texture.image_size();
+    let width = (width >> level).max(1);
+    let height = (height >> level).max(1);
+    let mut data = vec![0u8; width as usize * height as usize * 4];
+
+    unsafe {
+        let mut previous_alignment = 0;
+        gl.GetIntegerv(gl::PACK_ALIGNMENT, &mut previous_alignment);
+        gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+
+        gl.GetTextureImage(
+            texture.id(),
+            level as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data.len() as i32,
+            data.as_mut_ptr().cast(),
+        );
+        tracing::trace!(
+            "Read back Texture ({}) level {} ({} x {})",
+            texture.id(),
+            level,
+            width,
+            height
+        );
+
+        gl.PixelStorei(gl::PACK_ALIGNMENT, previous_alignment);
+    }
+
+    let image =
+        RgbaImage::from_raw(width, height, data).expect("Readback buffer has the wrong size");
+    image::imageops::flip_vertical(&image)
+}
+
+/// Reads the back buffer of the default framebuffer and saves it as
+/// `screenshot-<unix time in ms>.png` inside `directory`.
+///
+/// Call it after drawing and before swapping, the back buffer is undefined after a swap.
+pub fn save_screenshot(
+    gl: &gl::Gl,
+    size: (u32, u32),
+    directory: impl AsRef<Path>,
+) -> Result<PathBuf, image::ImageError> {
+    let image = read_framebuffer(gl, 0, gl::BACK, (0, 0, size.0, size.1));
+
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis();
+
+    let directory = directory.as_ref();
+    std::fs::create_dir_all(directory)?;
+    let path = directory.join(format!("screenshot-{}.png", timestamp));
+    image.save(&path)?;
+
+    Ok(path)
+}
@@ -0,0 +1,364 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+/// Everything active in a linked program, from `glGetProgramResource*`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ProgramInterface {
+    /// Includes the members of uniform blocks
+    pub uniforms: Vec<Uniform>,
+    /// Vertex inputs (or compute/tessellation inputs of the first stage), built-ins have no location
+    pub inputs: Vec<Variable>,
+    /// Fragment outputs (or outputs of the last stage)
+    pub outputs: Vec<Variable>,
+    pub uniform_blocks: Vec<Block>,
+    pub storage_blocks: Vec<Block>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Uniform {
+    /// Arrays are reported as `name[0]`
+    pub name: String,
+    /// e.g. `gl::FLOAT_MAT4`, see [`type_name`]
+    pub gl_type: u32,
+    /// `None` for members of uniform blocks
+    pub location: Option<i32>,
+    pub array_size: u32,
+    /// Index into [`ProgramInterface::uniform_blocks`]
+    pub block_index: Option<usize>,
+    /// Byte offset in the block, for members of uniform blocks
+    pub offset: Option<u32>,
+    pub array_stride: u32,
+    pub matrix_stride: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Variable {
+    pub name: String,
+    pub gl_type: u32,
+    pub location: Option<i32>,
+    pub array_size: u32,
+}
+
+/// A uniform or shader storage block
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Block {
+    pub name: String,
+    pub binding: u32,
+    /// Minimum buffer size in bytes, not counting a runtime-sized array past its first element
+    pub data_size: u32,
+    pub members: Vec<BlockMember>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BlockMember {
+    pub name: String,
+    pub gl_type: u32,
+    pub offset: u32,
+    /// 0 for runtime-sized arrays
+    pub array_size: u32,
+    pub array_stride: u32,
+    pub matrix_stride: u32,
+    /// Only for storage blocks, the stride of the outermost array the member is in,
+    /// 0 outside arrays
+    pub top_level_array_stride: Option<u32>,
+    /// Only for storage blocks, the length of the outermost array the member is in,
+    /// 0 if it's runtime-sized and 1 outside arrays
+    pub top_level_array_size: Option<u32>,
+}
+
+impl ProgramInterface {
+    /// Queries every interface of the linked program `program`
+    pub fn query(gl: &gl::Gl, program: u32) -> Self {
+        let query = Query { gl, program };
+
+        let uniform_blocks = (0..query.count(gl::UNIFORM_BLOCK))
+            .map(|i| query.block(gl::UNIFORM_BLOCK, i, gl::UNIFORM))
+            .collect();
+        let storage_blocks = (0..query.count(gl::SHADER_STORAGE_BLOCK))
+            .map(|i| query.block(gl::SHADER_STORAGE_BLOCK, i, gl::BUFFER_VARIABLE))
+            .collect();
+
+        let uniforms = (0..query.count(gl::UNIFORM))
+            .map(|i| {
+                let [gl_type, location, array_size, block_index, offset, array_stride, matrix_stride] =
+                    query.properties(
+                        gl::UNIFORM,
+                        i,
+                        [
+                            gl::TYPE,
+                            gl::LOCATION,
+                            gl::ARRAY_SIZE,
+                            gl::BLOCK_INDEX,
+                            gl::OFFSET,
+                            gl::ARRAY_STRIDE,
+                            gl::MATRIX_STRIDE,
+                        ],
+                    );
+                let block_index = (block_index >= 0).then_some(block_index as usize);
+
+                Uniform {
+                    name: query.name(gl::UNIFORM, i),
+                    gl_type: gl_type as u32,
+                    location: (location >= 0).then_some(location),
+                    array_size: array_size as u32,
+                    block_index,
+                    offset: block_index.map(|_| offset as u32),
+                    array_stride: array_stride.max(0) as u32,
+                    matrix_stride: matrix_stride.max(0) as u32,
+                }
+            })
+            .collect();
+
+        let variables = |interface| {
+            (0..query.count(interface))
+                .map(|i| {
+                    let [gl_type, location, array_size] =
+                        query.properties(interface, i, [gl::TYPE, gl::LOCATION, gl::ARRAY_SIZE]);
+
+                    Variable {
+                        name: query.name(interface, i),
+                        gl_type: gl_type as u32,
+                        location: (location >= 0).then_some(location),
+                        array_size: array_size as u32,
+                    }
+                })
+                .collect()
+        };
+
+        Self {
+            uniforms,
+            inputs: variables(gl::PROGRAM_INPUT),
+            outputs: variables(gl::PROGRAM_OUTPUT),
+            uniform_blocks,
+            storage_blocks,
+        }
+    }
+
+    /// Arrays are found by `name`, `name[0]` or any other element like `name[2]`
+    pub fn uniform(&self, name: &str) -> Option<&Uniform> {
+        let array = match name
+            .strip_suffix(']')
+            .and_then(|name| name.rsplit_once('['))
+        {
+            Some((array, _)) => format!("{}[0]", array),
+            None => format!("{}[0]", name),
+        };
+
+        self.uniforms
+            .iter()
+            .find(|uniform| uniform.name == name || uniform.name == array)
+    }
+
+    pub fn input(&self, name: &str) -> Option<&Variable> {
+        self.inputs.iter().find(|input| input.name == name)
+    }
+
+    pub fn output(&self, name: &str) -> Option<&Variable> {
+        self.outputs.iter().find(|output| output.name == name)
+    }
+
+    pub fn uniform_block(&self, name: &str) -> Option<&Block> {
+        self.uniform_blocks.iter().find(|block| block.name == name)
+    }
+
+    pub fn storage_block(&self, name: &str) -> Option<&Block> {
+        self.storage_blocks.iter().find(|block| block.name == name)
+    }
+}
+
+impl Block {
+    pub fn member(&self, name: &str) -> Option<&BlockMember> {
+        self.members.iter().find(|member| member.name == name)
+    }
+}
+
+struct Query<'a> {
+    gl: &'a gl::Gl,
+    program: u32,
+}
+
+impl Query<'_> {
+    fn count(&self, interface: u32) -> u32 {
+        let mut count = 0;
+        unsafe {
+            self.gl.GetProgramInterfaceiv(
+                self.program,
+                interface,
+                gl::ACTIVE_RESOURCES,
+                &mut count,
+            );
+        }
+
+        count as u32
+    }
+
+    fn properties<const N: usize>(
+        &self,
+        interface: u32,
+        index: u32,
+        properties: [u32; N],
+    ) -> [i32; N] {
+        let mut values = [0; N];
+        unsafe {
+            self.gl.GetProgramResourceiv(
+                self.program,
+                interface,
+                index,
+                N as i32,
+                properties.as_ptr(),
+                N as i32,
+                std::ptr::null_mut(),
+                values.as_mut_ptr(),
+            );
+        }
+
+        values
+    }
+
+    fn name(&self, interface: u32, index: u32) -> String {
+        let [len] = self.properties(interface, index, [gl::NAME_LENGTH]);
+        let mut name = vec![0u8; len.max(1) as usize];
+        let mut written = 0;
+        unsafe {
+            self.gl.GetProgramResourceName(
+                self.program,
+                interface,
+                index,
+                name.len() as i32,
+                &mut written,
+                name.as_mut_ptr().cast(),
+            );
+        }
+        name.truncate(written as usize);
+
+        String::from_utf8(name).expect("Resource names are ASCII")
+    }
+
+    /// `member_interface` is the interface the block's active variables are in
+    fn block(&self, interface: u32, index: u32, member_interface: u32) -> Block {
+        let [binding, data_size, member_count] = self.properties(
+            interface,
+            index,
+            [
+                gl::BUFFER_BINDING,
+                gl::BUFFER_DATA_SIZE,
+                gl::NUM_ACTIVE_VARIABLES,
+            ],
+        );
+
+        let mut member_indices = vec![0; member_count as usize];
+        unsafe {
+            self.gl.GetProgramResourceiv(
+                self.program,
+                interface,
+                index,
+                1,
+                &gl::ACTIVE_VARIABLES,
+                member_count,
+                std::ptr::null_mut(),
+                member_indices.as_mut_ptr(),
+            );
+        }
+
+        let mut members: Vec<BlockMember> = member_indices
+            .into_iter()
+            .map(|member| {
+                let member = member as u32;
+                let [gl_type, offset, array_size, array_stride, matrix_stride] = self.properties(
+                    member_interface,
+                    member,
+                    [
+                        gl::TYPE,
+                        gl::OFFSET,
+                        gl::ARRAY_SIZE,
+                        gl::ARRAY_STRIDE,
+                        gl::MATRIX_STRIDE,
+                    ],
+                );
+                let name = self.name(member_interface, member);
+                let top_level_array = (member_interface == gl::BUFFER_VARIABLE).then(|| {
+                    let [stride, size] = self.properties(
+                        member_interface,
+                        member,
+                        [gl::TOP_LEVEL_ARRAY_STRIDE, gl::TOP_LEVEL_ARRAY_SIZE],
+                    );
+                    // Mesa reports 0 for top-level arrays of non-structs
+                    match stride {
+                        0 if !name.contains('.') => (array_stride as u32, array_size as u32),
+                        stride => (stride as u32, size as u32),
+                    }
+                });
+
+                BlockMember {
+                    name,
+                    gl_type: gl_type as u32,
+                    offset: offset as u32,
+                    array_size: array_size as u32,
+                    array_stride: array_stride as u32,
+                    matrix_stride: matrix_stride as u32,
+                    top_level_array_stride: top_level_array.map(|(stride, _)| stride),
+                    top_level_array_size: top_level_array.map(|(_, size)| size),
+                }
+            })
+            .collect();
+        members.sort_by_key(|member| member.offset);
+
+        Block {
+            name: self.name(interface, index),
+            binding: binding as u32,
+            data_size: data_size as u32,
+            members,
+        }
+    }
+}
+
+/// The GLSL name of a `GL_TYPE` value, e.g. `"mat4"` for `gl::FLOAT_MAT4`
+pub fn type_name(gl_type: u32) -> &'static str {
+    match gl_type {
+        gl::FLOAT => "float",
+        gl::FLOAT_VEC2 => "vec2",
+        gl::FLOAT_VEC3 => "vec3",
+        gl::FLOAT_VEC4 => "vec4",
+        gl::DOUBLE => "double",
+        gl::DOUBLE_VEC2 => "dvec2",
+        gl::DOUBLE_VEC3 => "dvec3",
+        gl::DOUBLE_VEC4 => "dvec4",
+        gl::INT => "int",
+        gl::INT_VEC2 => "ivec2",
+        gl::INT_VEC3 => "ivec3",
+        gl::INT_VEC4 => "ivec4",
+        gl::UNSIGNED_INT => "uint",
+        gl::UNSIGNED_INT_VEC2 => "uvec2",
+        gl::UNSIGNED_INT_VEC3 => "uvec3",
+        gl::UNSIGNED_INT_VEC4 => "uvec4",
+        gl::BOOL => "bool",
+        gl::BOOL_VEC2 => "bvec2",
+        gl::BOOL_VEC3 => "bvec3",
+        gl::BOOL_VEC4 => "bvec4",
+        gl::FLOAT_MAT2 => "mat2",
+        gl::FLOAT_MAT3 => "mat3",
+        gl::FLOAT_MAT4 => "mat4",
+        gl::FLOAT_MAT2x3 => "mat2x3",
+        gl::FLOAT_MAT2x4 => "mat2x4",
+        gl::FLOAT_MAT3x2 => "mat3x2",
+        gl::FLOAT_MAT3x4 => "mat3x4",
+        gl::FLOAT_MAT4x2 => "mat4x2",
+        gl::FLOAT_MAT4x3 => "mat4x3",
+        gl::SAMPLER_1D => "sampler1D",
+        gl::SAMPLER_2D => "sampler2D",
+        gl::SAMPLER_3D => "sampler3D",
+        gl::SAMPLER_CUBE => "samplerCube",
+        gl::SAMPLER_2D_SHADOW => "sampler2DShadow",
+        gl::SAMPLER_2D_ARRAY => "sampler2DArray",
+        gl::SAMPLER_2D_ARRAY_SHADOW => "sampler2DArrayShadow",
+        gl::SAMPLER_CUBE_SHADOW => "samplerCubeShadow",
+        gl::SAMPLER_2D_MULTISAMPLE => "sampler2DMS",
+        gl::INT_SAMPLER_2D => "isampler2D",
+        gl::UNSIGNED_INT_SAMPLER_2D => "usampler2D",
+        gl::IMAGE_2D => "image2D",
+        gl::IMAGE_3D => "image3D",
+        gl::IMAGE_CUBE => "imageCube",
+        gl::IMAGE_2D_ARRAY => "image2DArray",
+        _ => "unknown",
+    }
+}
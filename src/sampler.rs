@@ -0,0 +1,314 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{ffi::CStr, sync::OnceLock};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Wrap {
+    Repeat,
+    MirroredRepeat,
+    ClampToEdge,
+    /// Reads [`SamplerDesc::border_color`] outside of the texture
+    ClampToBorder,
+    MirrorClampToEdge,
+}
+
+impl Wrap {
+    fn gl_enum(self) -> u32 {
+        match self {
+            Self::Repeat => gl::REPEAT,
+            Self::MirroredRepeat => gl::MIRRORED_REPEAT,
+            Self::ClampToEdge => gl::CLAMP_TO_EDGE,
+            Self::ClampToBorder => gl::CLAMP_TO_BORDER,
+            Self::MirrorClampToEdge => gl::MIRROR_CLAMP_TO_EDGE,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Filter {
+    Nearest,
+    Linear,
+}
+
+/// The comparison of a depth texture sampled through a `sampler*Shadow`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Compare {
+    Never,
+    Less,
+    Equal,
+    LessEqual,
+    Greater,
+    NotEqual,
+    GreaterEqual,
+    Always,
+}
+
+impl Compare {
+    fn gl_enum(self) -> u32 {
+        match self {
+            Self::Never => gl::NEVER,
+            Self::Less => gl::LESS,
+            Self::Equal => gl::EQUAL,
+            Self::LessEqual => gl::LEQUAL,
+            Self::Greater => gl::GREATER,
+            Self::NotEqual => gl::NOTEQUAL,
+            Self::GreaterEqual => gl::GEQUAL,
+            Self::Always => gl::ALWAYS,
+        }
+    }
+}
+
+/// How a texture is sampled, applied with [`Texture::set_sampler`] or held by a [`Sampler`].
+///
+/// The default repeats and filters trilinearly.
+///
+/// [`Texture::set_sampler`]: crate::texture::Texture::set_sampler
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SamplerDesc {
+    /// S, T and R
+    pub wrap: [Wrap; 3],
+    pub min_filter: Filter,
+    pub mag_filter: Filter,
+    /// Filtering between mipmap levels, `None` only samples the base level
+    pub mip_filter: Option<Filter>,
+    /// 1 disables anisotropic filtering, clamped to what the driver supports
+    pub max_anisotropy: f32,
+    pub lod_bias: f32,
+    pub min_lod: f32,
+    pub max_lod: f32,
+    pub border_color: [f32; 4],
+    /// Compares against the reference value instead of returning depth, for shadow maps
+    pub compare: Option<Compare>,
+}
+
+impl Default for SamplerDesc {
+    fn default() -> Self {
+        Self {
+            wrap: [Wrap::Repeat; 3],
+            min_filter: Filter::Linear,
+            mag_filter: Filter::Linear,
+            mip_filter: Some(Filter::Linear),
+            max_anisotropy: 1.0,
+            lod_bias: 0.0,
+            min_lod: -1000.0,
+            max_lod: 1000.0,
+            border_color: [0.0; 4],
+            compare: None,
+        }
+    }
+}
+
+impl SamplerDesc {
+    /// Unfiltered and clamped, for render targets and data textures
+    pub fn nearest() -> Self {
+        Self {
+            wrap: [Wrap::ClampToEdge; 3],
+            min_filter: Filter::Nearest,
+            mag_filter: Filter::Nearest,
+            mip_filter: None,
+            ..Self::default()
+        }
+    }
+
+    /// Clamped, without mipmaps
+    pub fn clamped() -> Self {
+        Self {
+            wrap: [Wrap::ClampToEdge; 3],
+            mip_filter: None,
+            ..Self::default()
+        }
+    }
+
+    /// Hardware PCF for a depth texture read through a `sampler2DShadow`
+    pub fn shadow() -> Self {
+        Self {
+            wrap: [Wrap::ClampToBorder; 3],
+            mip_filter: None,
+            border_color: [1.0; 4],
+            compare: Some(Compare::LessEqual),
+            ..Self::default()
+        }
+    }
+
+    pub fn with_wrap(mut self, wrap: Wrap) -> Self {
+        self.wrap = [wrap; 3];
+        self
+    }
+
+    pub fn with_anisotropy(mut self, max_anisotropy: f32) -> Self {
+        self.max_anisotropy = max_anisotropy;
+        self
+    }
+
+    fn min_filter_enum(&self) -> u32 {
+        match (self.min_filter, self.mip_filter) {
+            (Filter::Nearest, None) => gl::NEAREST,
+            (Filter::Linear, None) => gl::LINEAR,
+            (Filter::Nearest, Some(Filter::Nearest)) => gl::NEAREST_MIPMAP_NEAREST,
+            (Filter::Linear, Some(Filter::Nearest)) => gl::LINEAR_MIPMAP_NEAREST,
+            (Filter::Nearest, Some(Filter::Linear)) => gl::NEAREST_MIPMAP_LINEAR,
+            (Filter::Linear, Some(Filter::Linear)) => gl::LINEAR_MIPMAP_LINEAR,
+        }
+    }
+
+    /// Sets every parameter on the texture or sampler object `target`
+    pub(crate) unsafe fn apply(&self, gl: &gl::Gl, target: Target) {
+        let int = |name, value: u32| match target {
+            Target::Texture(id) => gl.TextureParameteri(id, name, value as i32),
+            Target::Sampler(id) => gl.SamplerParameteri(id, name, value as i32),
+        };
+        let float = |name, value| match target {
+            Target::Texture(id) => gl.TextureParameterf(id, name, value),
+            Target::Sampler(id) => gl.SamplerParameterf(id, name, value),
+        };
+
+        int(gl::TEXTURE_WRAP_S, self.wrap[0].gl_enum());
+        int(gl::TEXTURE_WRAP_T, self.wrap[1].gl_enum());
+        int(gl::TEXTURE_WRAP_R, self.wrap[2].gl_enum());
+        int(gl::TEXTURE_MIN_FILTER, self.min_filter_enum());
+        int(
+            gl::TEXTURE_MAG_FILTER,
+            match self.mag_filter {
+                Filter::Nearest => gl::NEAREST,
+                Filter::Linear => gl::LINEAR,
+            },
+        );
+
+        // Anisotropy isn't core in 4.5, its enums are invalid without the extension
+        if has_anisotropy(gl) {
+            let mut current = 1.0;
+            match target {
+                Target::Texture(id) => {
+                    gl.GetTextureParameterfv(id, gl::TEXTURE_MAX_ANISOTROPY, &mut current)
+                }
+                Target::Sampler(id) => {
+                    gl.GetSamplerParameterfv(id, gl::TEXTURE_MAX_ANISOTROPY, &mut current)
+                }
+            }
+            // Also resets objects that had anisotropy before
+            if self.max_anisotropy > 1.0 || current > 1.0 {
+                let mut max_anisotropy = 1.0;
+                gl.GetFloatv(gl::MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy);
+                float(
+                    gl::TEXTURE_MAX_ANISOTROPY,
+                    self.max_anisotropy.clamp(1.0, max_anisotropy.max(1.0)),
+                );
+            }
+        } else if self.max_anisotropy > 1.0 {
+            tracing::warn!(
+                "Sampler: The driver doesn't support anisotropic filtering, ignoring it"
+            );
+        }
+        float(gl::TEXTURE_LOD_BIAS, self.lod_bias);
+        float(gl::TEXTURE_MIN_LOD, self.min_lod);
+        float(gl::TEXTURE_MAX_LOD, self.max_lod);
+        match target {
+            Target::Texture(id) => {
+                gl.TextureParameterfv(id, gl::TEXTURE_BORDER_COLOR, self.border_color.as_ptr())
+            }
+            Target::Sampler(id) => {
+                gl.SamplerParameterfv(id, gl::TEXTURE_BORDER_COLOR, self.border_color.as_ptr())
+            }
+        }
+
+        match self.compare {
+            Some(compare) => {
+                int(gl::TEXTURE_COMPARE_MODE, gl::COMPARE_REF_TO_TEXTURE);
+                int(gl::TEXTURE_COMPARE_FUNC, compare.gl_enum());
+            }
+            None => int(gl::TEXTURE_COMPARE_MODE, gl::NONE),
+        }
+    }
+}
+
+/// Whether the driver has `GL_ARB_texture_filter_anisotropic` or its EXT predecessor.
+///
+/// Looked up once, every context of the process comes from the same driver.
+unsafe fn has_anisotropy(gl: &gl::Gl) -> bool {
+    static HAS_ANISOTROPY: OnceLock<bool> = OnceLock::new();
+
+    *HAS_ANISOTROPY.get_or_init(|| {
+        let mut count = 0;
+        gl.GetIntegerv(gl::NUM_EXTENSIONS, &mut count);
+        (0..count as u32).any(|i| {
+            let name = gl.GetStringi(gl::EXTENSIONS, i);
+            !name.is_null()
+                && matches!(
+                    CStr::from_ptr(name.cast()).to_bytes(),
+                    b"GL_ARB_texture_filter_anisotropic" | b"GL_EXT_texture_filter_anisotropic"
+                )
+        })
+    })
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Target {
+    Texture(u32),
+    Sampler(u32),
+}
+
+/// A sampler object, overriding the sampling state of whatever texture is bound
+/// to the same unit
+pub struct Sampler<'a> {
+    gl: &'a gl::Gl,
+    id: u32,
+    desc: SamplerDesc,
+    label: Option<&'a str>,
+}
+
+impl<'a> Sampler<'a> {
+    pub fn new(gl: &'a gl::Gl, desc: &SamplerDesc, label: Option<&'a str>) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl.CreateSamplers(1, &mut id);
+            tracing::trace!("Created Sampler ({}): {:?}", id, desc);
+
+            if let Some(label) = label {
+                gl.ObjectLabel(gl::SAMPLER, id, label.len() as i32, label.as_ptr().cast());
+                tracing::trace!("Adding label to Sampler ({}): {}", id, label);
+            }
+
+            desc.apply(gl, Target::Sampler(id));
+        }
+
+        Self {
+            gl,
+            id,
+            desc: *desc,
+            label,
+        }
+    }
+
+    pub fn set(&mut self, desc: &SamplerDesc) {
+        unsafe { desc.apply(self.gl, Target::Sampler(self.id)) };
+        self.desc = *desc;
+    }
+
+    pub unsafe fn bind(&self, unit: u32) {
+        self.gl.BindSampler(unit, self.id);
+    }
+
+    /// Goes back to the texture's own sampling state
+    pub unsafe fn unbind(&self, unit: u32) {
+        self.gl.BindSampler(unit, 0);
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    pub fn desc(&self) -> &SamplerDesc {
+        &self.desc
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
+
+impl Drop for Sampler<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteSamplers(1, &self.id);
+        }
+    }
+}
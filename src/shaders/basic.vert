@@ -5,10 +5,10 @@ layout (location = 1) in vec2 uv;
 
 layout (location = 0) out vec2 oUv;
 
-layout (location = 0) uniform mat4 uProjView;
+#include "camera.glsl"
 
 void main()
 {
-	gl_Position = uProjView * vec4(position, 1.0);
+	gl_Position = camera.proj_view * vec4(position, 1.0);
 	oUv = uv;
 }
\ No newline at end of file
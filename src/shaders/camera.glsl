@@ -0,0 +1,6 @@
+// Matches gl_playground::uniform_buffer::CameraBlock
+layout (std140, binding = 0) uniform Camera {
+	mat4 view;
+	mat4 proj;
+	mat4 proj_view;
+} camera;
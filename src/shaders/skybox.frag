@@ -0,0 +1,12 @@
+#version 450 core
+
+layout (location = 0) in vec3 oDirection;
+
+layout (location = 0) out vec4 fColor;
+
+layout (binding = 0) uniform samplerCube uSkybox;
+
+void main()
+{
+	fColor = texture(uSkybox, oDirection);
+}
@@ -0,0 +1,15 @@
+#version 450 core
+
+layout (location = 0) in vec3 position;
+
+layout (location = 0) out vec3 oDirection;
+
+// The camera's projection and rotation, without its translation
+layout (location = 0) uniform mat4 uViewProj;
+
+void main()
+{
+	oDirection = position;
+	// At the far plane, so everything else is drawn in front of it
+	gl_Position = (uViewProj * vec4(position, 1.0)).xyww;
+}
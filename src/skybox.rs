@@ -0,0 +1,74 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use glam::{Mat3, Mat4};
+
+use crate::{
+    buffer::BufferError,
+    camera::Camera,
+    cubemap::Cubemap,
+    mesh::{Mesh, MeshData},
+    preprocessor::Defines,
+    program::{Program, ProgramError},
+    vertex::{Vertex, CUBE},
+};
+
+const VERTEX: &str = include_str!("shaders/skybox.vert");
+const FRAGMENT: &str = include_str!("shaders/skybox.frag");
+
+/// Draws a [`Cubemap`] around the camera, at the far plane
+pub struct Skybox<'a> {
+    gl: &'a gl::Gl,
+    program: Program<'a>,
+    cube: Mesh<'a, Vertex>,
+}
+
+impl<'a> Skybox<'a> {
+    pub fn new(gl: &'a gl::Gl) -> Result<Self, SkyboxError> {
+        let program = Program::from_source(gl, VERTEX, FRAGMENT, &Defines::new(), Some("Skybox"))?;
+        let cube = Mesh::new(gl, &MeshData::from_vertices(&CUBE), Some("Skybox"))?;
+
+        Ok(Self { gl, program, cube })
+    }
+
+    /// The camera's projection and rotation, with the translation stripped out so
+    /// the sky never gets closer
+    pub fn view_proj(camera: &Camera) -> Mat4 {
+        camera.proj_matrix() * Mat4::from_mat3(Mat3::from_mat4(camera.view_matrix()))
+    }
+
+    /// Draws `cubemap` wherever nothing else was drawn, so it's cheapest after the
+    /// opaque geometry. Leaves texture unit 0 and the program bound.
+    pub unsafe fn draw(&self, camera: &Camera, cubemap: &Cubemap) {
+        let gl = self.gl;
+
+        // The cube is seen from the inside, at a depth of exactly 1
+        let mut depth_func = 0;
+        gl.GetIntegerv(gl::DEPTH_FUNC, &mut depth_func);
+        let cull_face = gl.IsEnabled(gl::CULL_FACE) == gl::TRUE;
+        gl.DepthFunc(gl::LEQUAL);
+        gl.Disable(gl::CULL_FACE);
+
+        self.program.bind();
+        self.program.set("uViewProj", Self::view_proj(camera));
+        cubemap.bind(0);
+        self.cube.draw();
+
+        gl.DepthFunc(depth_func as u32);
+        if cull_face {
+            gl.Enable(gl::CULL_FACE);
+        }
+    }
+
+    pub fn program(&self) -> &Program<'a> {
+        &self.program
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SkyboxError {
+    #[error("Program error: {0}")]
+    ProgramError(#[from] ProgramError),
+    #[error("Buffer error: {0}")]
+    BufferError(#[from] BufferError),
+}
@@ -0,0 +1,124 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+const MAGIC: u32 = 0x0723_0203;
+
+/// A precompiled SPIR-V module for one stage, see [`Program::from_spirv`].
+///
+/// Uniforms of SPIR-V programs only have the names the compiler kept, if any,
+/// so they are best set through explicit locations and bindings.
+///
+/// [`Program::from_spirv`]: crate::program::Program::from_spirv
+#[derive(Clone, Debug)]
+pub struct SpirvShader {
+    /// In native endianness
+    code: Vec<u32>,
+    entry_point: String,
+    /// `(constant_id, value)`
+    constants: Vec<(u32, u32)>,
+}
+
+impl SpirvShader {
+    /// Reads a module in either endianness, its entry point defaults to `main`
+    pub fn new(bytes: &[u8]) -> Result<Self, SpirvError> {
+        if bytes.len() < 20 || !bytes.len().is_multiple_of(4) {
+            return Err(SpirvError::Length(bytes.len()));
+        }
+
+        let words = bytes
+            .chunks_exact(4)
+            .map(|word| u32::from_ne_bytes(word.try_into().unwrap()));
+        let code: Vec<u32> = match u32::from_ne_bytes(bytes[..4].try_into().unwrap()) {
+            MAGIC => words.collect(),
+            magic if magic.swap_bytes() == MAGIC => words.map(u32::swap_bytes).collect(),
+            magic => return Err(SpirvError::Magic(magic)),
+        };
+
+        Ok(Self {
+            code,
+            entry_point: "main".to_owned(),
+            constants: Vec::new(),
+        })
+    }
+
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Self, SpirvError> {
+        let path = path.as_ref();
+        let bytes = fs::read(path).map_err(|source| SpirvError::Io {
+            path: path.to_owned(),
+            source,
+        })?;
+
+        Self::new(&bytes)
+    }
+
+    /// Uses the `OpEntryPoint` called `name` instead of `main`
+    pub fn entry_point(mut self, name: impl Into<String>) -> Self {
+        self.entry_point = name.into();
+        self
+    }
+
+    /// Overrides `layout (constant_id = id) const` with `value`, replacing earlier values
+    pub fn constant(mut self, id: u32, value: impl SpecConstant) -> Self {
+        self.constants.retain(|&(constant, _)| constant != id);
+        self.constants.push((id, value.bits()));
+        self
+    }
+
+    /// The module as bytes for `glShaderBinary`
+    pub fn bytes(&self) -> &[u8] {
+        bytemuck::cast_slice(&self.code)
+    }
+    pub fn entry_point_name(&self) -> &str {
+        &self.entry_point
+    }
+    pub fn constants(&self) -> &[(u32, u32)] {
+        &self.constants
+    }
+}
+
+/// A scalar that can specialize a SPIR-V constant, passed to the driver as 32 bits
+pub trait SpecConstant {
+    fn bits(self) -> u32;
+}
+
+impl SpecConstant for u32 {
+    fn bits(self) -> u32 {
+        self
+    }
+}
+
+impl SpecConstant for i32 {
+    fn bits(self) -> u32 {
+        self as u32
+    }
+}
+
+impl SpecConstant for f32 {
+    fn bits(self) -> u32 {
+        self.to_bits()
+    }
+}
+
+impl SpecConstant for bool {
+    fn bits(self) -> u32 {
+        self as u32
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum SpirvError {
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("A SPIR-V module is a whole number of words with a 5 word header, got {0} bytes")]
+    Length(usize),
+    #[error("Not a SPIR-V module, it starts with {0:#010x}")]
+    Magic(u32),
+}
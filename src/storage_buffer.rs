@@ -0,0 +1,236 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{any::type_name, mem::size_of, ops::Range};
+
+use crate::{
+    block_layout::Std430,
+    buffer::{Buffer, BufferError},
+    reflection::ProgramInterface,
+};
+
+/// A shader storage buffer of `T`s, for `layout (std430, binding = N) buffer Block { T items[]; }`
+/// or a block that is a single `T`.
+///
+/// All offsets and ranges are in elements, not bytes.
+pub struct StorageBuffer<'a, T: Std430> {
+    gl: &'a gl::Gl,
+    buffer: Buffer<'a, T>,
+    binding: u32,
+}
+
+impl<'a, T: Std430> StorageBuffer<'a, T> {
+    /// Creates the buffer, it's bound to `binding` by [`StorageBuffer::bind`]
+    pub fn from_data(
+        gl: &'a gl::Gl,
+        data: &[T],
+        binding: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, BufferError> {
+        let buffer = Buffer::from_data(gl, data, gl::DYNAMIC_STORAGE_BIT, label)?;
+
+        Ok(Self {
+            gl,
+            buffer,
+            binding,
+        })
+    }
+
+    /// Creates a buffer with room for `len` zeroed elements, e.g. for compute output
+    pub fn with_len(
+        gl: &'a gl::Gl,
+        len: usize,
+        binding: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, BufferError> {
+        let buffer = Buffer::with_len(gl, len, gl::DYNAMIC_STORAGE_BIT, label)?;
+
+        Ok(Self {
+            gl,
+            buffer,
+            binding,
+        })
+    }
+
+    pub fn set_data(&self, offset: usize, data: &[T]) -> Result<(), BufferError> {
+        self.buffer.set_data(offset, data)
+    }
+
+    /// Waits for shader writes and reads the whole buffer back
+    pub fn read(&self) -> Vec<T> {
+        self.read_range(0..self.len())
+            .expect("The whole buffer is always in range")
+    }
+
+    /// Waits for shader writes and reads `range` back
+    pub fn read_range(&self, range: Range<usize>) -> Result<Vec<T>, BufferError> {
+        unsafe {
+            self.gl.MemoryBarrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+        }
+
+        self.buffer.read_range(range)
+    }
+
+    /// Waits for shader writes and reads the element at `index` back
+    pub fn get(&self, index: usize) -> Result<T, BufferError> {
+        Ok(self.read_range(index..index + 1)?[0])
+    }
+
+    /// Checks the program's storage block `block` against this buffer: the binding
+    /// point, the std430 element size and whether the buffer is big enough
+    pub fn validate(
+        &self,
+        interface: &ProgramInterface,
+        block: &str,
+    ) -> Result<(), StorageBufferError> {
+        let reflected = interface
+            .storage_block(block)
+            .ok_or_else(|| StorageBufferError::MissingBlock(block.to_owned()))?;
+
+        if reflected.binding != self.binding {
+            return Err(StorageBufferError::Binding {
+                block: block.to_owned(),
+                expected: reflected.binding,
+                binding: self.binding,
+            });
+        }
+
+        let members = &reflected.members;
+        let first_stride = members
+            .first()
+            .and_then(|member| member.top_level_array_stride);
+        let array_only = first_stride.is_some_and(|stride| stride != 0)
+            && members
+                .iter()
+                .all(|member| member.top_level_array_stride == first_stride);
+        let runtime_sized = members
+            .last()
+            .is_some_and(|member| member.top_level_array_size == Some(0));
+        let (expected, what) = match first_stride {
+            // The block is nothing but one array, each `T` is an element
+            Some(stride) if array_only => (stride as usize, "element size"),
+            // A header followed by a runtime-sized array needs two types
+            _ if runtime_sized => return Err(StorageBufferError::MixedMembers(block.to_owned())),
+            // Anything else, fixed-size arrays included, is a single `T`
+            _ => (reflected.data_size as usize, "size"),
+        };
+        if expected != size_of::<T>() {
+            return Err(StorageBufferError::Layout {
+                block: block.to_owned(),
+                what,
+                expected,
+                ty: type_name::<T>(),
+                size: size_of::<T>(),
+            });
+        }
+
+        if self.buffer.size() < reflected.data_size as usize {
+            return Err(StorageBufferError::TooSmall {
+                block: block.to_owned(),
+                expected: reflected.data_size as usize,
+                size: self.buffer.size(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Binds the whole buffer to its `GL_SHADER_STORAGE_BUFFER` binding point
+    pub unsafe fn bind(&self) {
+        self.gl
+            .BindBufferBase(gl::SHADER_STORAGE_BUFFER, self.binding, self.buffer.id());
+    }
+
+    /// Binds only `range`, its start has to be a multiple of
+    /// `GL_SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT` in bytes
+    pub unsafe fn bind_range(&self, range: Range<usize>) -> Result<(), StorageBufferError> {
+        if range.start > range.end || range.end > self.len() {
+            return Err(BufferError::OutOfBounds {
+                range,
+                len: self.len(),
+            }
+            .into());
+        }
+
+        let mut alignment = 0;
+        self.gl
+            .GetIntegerv(gl::SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT, &mut alignment);
+        let offset = range.start * size_of::<T>();
+        if !offset.is_multiple_of(alignment as usize) {
+            return Err(StorageBufferError::MisalignedOffset {
+                offset,
+                alignment: alignment as usize,
+            });
+        }
+
+        self.gl.BindBufferRange(
+            gl::SHADER_STORAGE_BUFFER,
+            self.binding,
+            self.buffer.id(),
+            offset as isize,
+            (range.len() * size_of::<T>()) as isize,
+        );
+
+        Ok(())
+    }
+
+    pub unsafe fn unbind(&self) {
+        self.gl
+            .BindBufferBase(gl::SHADER_STORAGE_BUFFER, self.binding, 0);
+    }
+
+    pub fn buffer(&self) -> &Buffer<'a, T> {
+        &self.buffer
+    }
+    pub fn binding(&self) -> u32 {
+        self.binding
+    }
+    pub fn id(&self) -> u32 {
+        self.buffer.id()
+    }
+    pub fn len(&self) -> usize {
+        self.buffer.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.buffer.is_empty()
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.buffer.label()
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum StorageBufferError {
+    #[error("Buffer error: {0}")]
+    BufferError(#[from] BufferError),
+    #[error("The program has no storage block {0}")]
+    MissingBlock(String),
+    #[error("Storage block {block} is at binding {expected}, the buffer uses {binding}")]
+    Binding {
+        block: String,
+        expected: u32,
+        binding: u32,
+    },
+    #[error("Storage block {0} mixes an array with other members, it can't be held by one StorageBuffer")]
+    MixedMembers(String),
+    #[error(
+        "Storage block {block} has an std430 {what} of {expected} bytes, but {ty} is {size} bytes"
+    )]
+    Layout {
+        block: String,
+        what: &'static str,
+        expected: usize,
+        ty: &'static str,
+        size: usize,
+    },
+    #[error("Storage block {block} needs at least {expected} bytes, the buffer has {size}")]
+    TooSmall {
+        block: String,
+        expected: usize,
+        size: usize,
+    },
+    #[error(
+        "Offset {offset} isn't a multiple of the {alignment}-byte storage buffer offset alignment"
+    )]
+    MisalignedOffset { offset: usize, alignment: usize },
+}
@@ -1,41 +1,117 @@
 // Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
 // SPDX-License-Identifier: MIT
 
-use std::path::Path;
+use std::{fs, path::Path};
 
+use image::{
+    codecs::hdr::{HdrDecoder, HdrMetadata},
+    DynamicImage, ImageError, Rgb32FImage,
+};
 use tracing::instrument;
 
+use crate::{
+    compressed::{level_size, CompressedImage},
+    sampler::{SamplerDesc, Target},
+};
+
+/// How the 8-bit color channels of an image are interpreted when sampled
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorSpace {
+    /// Decoded to linear when sampled, for albedo and other colors
+    Srgb,
+    /// Sampled as stored, for normal maps, masks and other data
+    #[default]
+    Linear,
+}
+
+/// How texels are stored (`internal_format`) and laid out in memory (`format`, `data_type`)
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PixelFormat {
+    pub internal_format: u32,
+    pub format: u32,
+    pub data_type: u32,
+    pub channels: u8,
+}
+
+impl PixelFormat {
+    /// The format storing `image` without conversion.
+    ///
+    /// Grayscale ends up in red and its alpha in green. `color_space` only
+    /// applies to 8-bit RGB and RGBA, there are no core sRGB formats for the rest.
+    pub fn of(image: &DynamicImage, color_space: ColorSpace) -> Result<Self, TextureError> {
+        let srgb = color_space == ColorSpace::Srgb;
+        let (internal_format, format, data_type, channels) = match image {
+            DynamicImage::ImageLuma8(..) => (gl::R8, gl::RED, gl::UNSIGNED_BYTE, 1),
+            DynamicImage::ImageLumaA8(..) => (gl::RG8, gl::RG, gl::UNSIGNED_BYTE, 2),
+            DynamicImage::ImageRgb8(..) if srgb => (gl::SRGB8, gl::RGB, gl::UNSIGNED_BYTE, 3),
+            DynamicImage::ImageRgb8(..) => (gl::RGB8, gl::RGB, gl::UNSIGNED_BYTE, 3),
+            DynamicImage::ImageRgba8(..) if srgb => {
+                (gl::SRGB8_ALPHA8, gl::RGBA, gl::UNSIGNED_BYTE, 4)
+            }
+            DynamicImage::ImageRgba8(..) => (gl::RGBA8, gl::RGBA, gl::UNSIGNED_BYTE, 4),
+            DynamicImage::ImageLuma16(..) => (gl::R16, gl::RED, gl::UNSIGNED_SHORT, 1),
+            DynamicImage::ImageLumaA16(..) => (gl::RG16, gl::RG, gl::UNSIGNED_SHORT, 2),
+            DynamicImage::ImageRgb16(..) => (gl::RGB16, gl::RGB, gl::UNSIGNED_SHORT, 3),
+            DynamicImage::ImageRgba16(..) => (gl::RGBA16, gl::RGBA, gl::UNSIGNED_SHORT, 4),
+            DynamicImage::ImageRgb32F(..) => (gl::RGB32F, gl::RGB, gl::FLOAT, 3),
+            DynamicImage::ImageRgba32F(..) => (gl::RGBA32F, gl::RGBA, gl::FLOAT, 4),
+            _ => return Err(TextureError::UnsupportedFormat),
+        };
+
+        Ok(Self {
+            internal_format,
+            format,
+            data_type,
+            channels,
+        })
+    }
+
+    /// Bytes per texel of the data
+    pub fn texel_size(&self) -> usize {
+        let channel_size = match self.data_type {
+            gl::UNSIGNED_SHORT | gl::SHORT | gl::HALF_FLOAT => 2,
+            gl::UNSIGNED_INT | gl::INT | gl::FLOAT => 4,
+            _ => 1,
+        };
+        self.channels as usize * channel_size
+    }
+}
+
 pub struct Texture<'a> {
     gl: &'a gl::Gl,
     id: u32,
     image_size: (u32, u32),
     color_channels: u8,
+    internal_format: u32,
+    levels: u32,
     label: Option<&'a str>,
 }
 
 impl<'a> Texture<'a> {
+    /// Uploads tightly packed `data` in `format` and generates its mipmaps
     pub fn from_raw(
         gl: &'a gl::Gl,
         image_size: (u32, u32),
-        channels: u8,
+        format: PixelFormat,
         data: &[u8],
         label: Option<&'a str>,
     ) -> Result<Self, TextureError> {
         let (width, height) = image_size;
         let mut id = 0;
 
-        if channels as usize * width as usize * height as usize != data.len() {
+        if format.texel_size() * width as usize * height as usize != data.len() {
             return Err(TextureError::WrongSizedData);
         }
+        let levels = mip_levels(image_size);
 
         unsafe {
             gl.CreateTextures(gl::TEXTURE_2D, 1, &mut id);
             tracing::trace!(
-                "Created Texture ({}) ({} x {}) with {} channels",
+                "Created Texture ({}) ({} x {}) with internal format {:#x}",
                 id,
                 width,
                 height,
-                channels
+                format.internal_format
             );
 
             if let Some(label) = label {
@@ -43,49 +119,215 @@ impl<'a> Texture<'a> {
                 tracing::trace!("Adding label to Texture ({}): {}", id, label);
             }
 
-            gl.TextureParameteri(id, gl::TEXTURE_WRAP_S, gl::REPEAT as i32);
-            gl.TextureParameteri(id, gl::TEXTURE_WRAP_T, gl::REPEAT as i32);
-            gl.TextureParameteri(id, gl::TEXTURE_MIN_FILTER, gl::LINEAR as i32);
-            gl.TextureParameteri(id, gl::TEXTURE_MAG_FILTER, gl::LINEAR as i32);
+            let sampler = SamplerDesc::default();
+            sampler.apply(gl, Target::Texture(id));
+            tracing::trace!("Texture ({}) sampler: {:?}", id, sampler);
+
+            gl.TextureStorage2D(
+                id,
+                levels as i32,
+                format.internal_format,
+                width as i32,
+                height as i32,
+            );
+
+            with_tight_unpacking(gl, || {
+                gl.TextureSubImage2D(
+                    id,
+                    0,
+                    0,
+                    0,
+                    width as i32,
+                    height as i32,
+                    format.format,
+                    format.data_type,
+                    data.as_ptr().cast(),
+                )
+            });
+            gl.GenerateTextureMipmap(id);
+        }
+
+        Ok(Self {
+            gl,
+            id,
+            color_channels: format.channels,
+            internal_format: format.internal_format,
+            levels,
+            label,
+            image_size,
+        })
+    }
+
+    /// Uploads `image` in the format matching its own, see [`PixelFormat::of`]
+    pub fn from_image(
+        gl: &'a gl::Gl,
+        image: &DynamicImage,
+        color_space: ColorSpace,
+        label: Option<&'a str>,
+    ) -> Result<Self, TextureError> {
+        let format = PixelFormat::of(image, color_space)?;
+
+        Texture::from_raw(
+            gl,
+            (image.width(), image.height()),
+            format,
+            image.as_bytes(),
+            label,
+        )
+    }
+
+    /// Uploads every stored level of a block-compressed image as is, there's no
+    /// decoding or mipmap generation
+    pub fn from_compressed(
+        gl: &'a gl::Gl,
+        image: &CompressedImage,
+        label: Option<&'a str>,
+    ) -> Result<Self, TextureError> {
+        let (width, height) = image.size();
+        let format = image.format();
+        let levels = image.levels().len() as u32;
+        let mut id = 0;
+
+        // S3TC is an extension, and a driver may lack the rest too
+        let mut supported = 0;
+        unsafe {
+            gl.GetInternalformativ(
+                gl::TEXTURE_2D,
+                format.internal_format,
+                gl::INTERNALFORMAT_SUPPORTED,
+                1,
+                &mut supported,
+            );
+        }
+        if supported != gl::TRUE as i32 {
+            return Err(TextureError::UnsupportedByDriver(format.internal_format));
+        }
+
+        unsafe {
+            gl.CreateTextures(gl::TEXTURE_2D, 1, &mut id);
             tracing::trace!(
-                r#"Texture parameters:
-                Wrap S: Repeat,
-                Wrap T: Repeat,
-                Min Filter: Linear,
-                Mag Filter: Linear
-                "#
+                "Created Texture ({}) ({} x {}) with compressed format {:#x} and {} levels",
+                id,
+                width,
+                height,
+                format.internal_format,
+                levels
             );
 
-            let internal_format = match channels {
-                3 => gl::RGB8,
-                4 => gl::RGBA8,
-                _ => return Err(TextureError::UnsupportedFormat),
-            };
-            let data_format = match channels {
-                3 => gl::RGB,
-                4 => gl::RGBA,
-                _ => return Err(TextureError::UnsupportedFormat),
-            };
+            if let Some(label) = label {
+                gl.ObjectLabel(gl::TEXTURE, id, label.len() as i32, label.as_ptr().cast());
+                tracing::trace!("Adding label to Texture ({}): {}", id, label);
+            }
 
-            gl.TextureStorage2D(id, 1, internal_format, width as i32, height as i32);
-            gl.TextureSubImage2D(
+            SamplerDesc::default().apply(gl, Target::Texture(id));
+            gl.TextureStorage2D(
                 id,
-                0,
-                0,
-                0,
+                levels as i32,
+                format.internal_format,
                 width as i32,
                 height as i32,
-                data_format,
-                gl::UNSIGNED_BYTE,
-                data.as_ptr().cast(),
             );
-            gl.GenerateTextureMipmap(id);
+
+            for (level, data) in image.levels().iter().enumerate() {
+                let (level_width, level_height) = level_size(image.size(), level as u32);
+                gl.CompressedTextureSubImage2D(
+                    id,
+                    level as i32,
+                    0,
+                    0,
+                    level_width as i32,
+                    level_height as i32,
+                    format.internal_format,
+                    data.len() as i32,
+                    data.as_ptr().cast(),
+                );
+            }
+        }
+
+        Ok(Self {
+            gl,
+            id,
+            color_channels: format.channels,
+            internal_format: format.internal_format,
+            levels,
+            label,
+            image_size: image.size(),
+        })
+    }
+
+    /// Loads a KTX2 or DDS file, see [`CompressedImage::from_memory`]
+    #[instrument(skip(gl))]
+    pub fn from_compressed_file(
+        gl: &'a gl::Gl,
+        path: impl AsRef<Path> + std::fmt::Debug,
+        color_space: ColorSpace,
+        label: Option<&'a str>,
+    ) -> Result<Self, TextureError> {
+        let image = CompressedImage::from_file(path.as_ref(), color_space)?;
+
+        Texture::from_compressed(gl, &image, label)
+    }
+
+    /// Creates a texture with uninitialized storage, e.g. for a framebuffer attachment
+    pub fn with_storage(
+        gl: &'a gl::Gl,
+        image_size: (u32, u32),
+        internal_format: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, TextureError> {
+        let (width, height) = image_size;
+        let mut id = 0;
+
+        let channels = match internal_format {
+            gl::R8
+            | gl::R16
+            | gl::R16F
+            | gl::R32F
+            | gl::DEPTH_COMPONENT16
+            | gl::DEPTH_COMPONENT24
+            | gl::DEPTH_COMPONENT32F => 1,
+            gl::RG8
+            | gl::RG16
+            | gl::RG16F
+            | gl::RG32F
+            | gl::DEPTH24_STENCIL8
+            | gl::DEPTH32F_STENCIL8 => 2,
+            gl::RGB8 | gl::SRGB8 | gl::RGB16 | gl::RGB16F | gl::RGB32F | gl::R11F_G11F_B10F => 3,
+            gl::RGBA8
+            | gl::SRGB8_ALPHA8
+            | gl::RGBA16
+            | gl::RGBA16F
+            | gl::RGBA32F
+            | gl::RGB10_A2 => 4,
+            _ => return Err(TextureError::UnsupportedFormat),
+        };
+
+        unsafe {
+            gl.CreateTextures(gl::TEXTURE_2D, 1, &mut id);
+            tracing::trace!(
+                "Created Texture ({}) ({} x {}) with internal format {:#x}",
+                id,
+                width,
+                height,
+                internal_format
+            );
+
+            if let Some(label) = label {
+                gl.ObjectLabel(gl::TEXTURE, id, label.len() as i32, label.as_ptr().cast());
+                tracing::trace!("Adding label to Texture ({}): {}", id, label);
+            }
+
+            SamplerDesc::clamped().apply(gl, Target::Texture(id));
+
+            gl.TextureStorage2D(id, 1, internal_format, width as i32, height as i32);
         }
 
         Ok(Self {
             gl,
             id,
             color_channels: channels,
+            internal_format,
+            levels: 1,
             label,
             image_size,
         })
@@ -95,52 +337,31 @@ impl<'a> Texture<'a> {
     pub fn from_file(
         gl: &'a gl::Gl,
         path: impl AsRef<Path> + std::fmt::Debug,
+        color_space: ColorSpace,
         label: Option<&'a str>,
     ) -> Result<Self, TextureError> {
-        fn inner<'b>(
-            gl: &'b gl::Gl,
-            path: &Path,
-            label: Option<&'b str>,
-        ) -> Result<Texture<'b>, TextureError> {
-            let image = image::open(path)?;
-            let channels = match image {
-                image::DynamicImage::ImageRgb8(..) => 3,
-                image::DynamicImage::ImageRgba8(..) => 4,
-                _ => return Err(TextureError::UnsupportedFormat),
-            };
-
-            Texture::from_raw(
-                gl,
-                (image.width(), image.height()),
-                channels,
-                image.as_bytes(),
-                label,
-            )
-        }
+        let image = open_image(path.as_ref())?;
 
-        inner(gl, path.as_ref(), label)
+        Texture::from_image(gl, &image, color_space, label)
     }
 
     #[instrument(skip(gl))]
     pub fn from_memory(
         gl: &'a gl::Gl,
         data: &[u8],
+        color_space: ColorSpace,
         label: Option<&'a str>,
     ) -> Result<Self, TextureError> {
-        let image = image::load_from_memory(data)?;
-        let channels = match image {
-            image::DynamicImage::ImageRgb8(..) => 3,
-            image::DynamicImage::ImageRgba8(..) => 4,
-            _ => return Err(TextureError::UnsupportedFormat),
-        };
+        let image = load_image(data)?;
 
-        Texture::from_raw(
-            gl,
-            (image.width(), image.height()),
-            channels,
-            image.as_bytes(),
-            label,
-        )
+        Texture::from_image(gl, &image, color_space, label)
+    }
+
+    /// Replaces how the texture is sampled, a bound [`Sampler`] still takes precedence
+    ///
+    /// [`Sampler`]: crate::sampler::Sampler
+    pub fn set_sampler(&self, desc: &SamplerDesc) {
+        unsafe { desc.apply(self.gl, Target::Texture(self.id)) };
     }
 
     pub unsafe fn bind(&self, slot: u32) {
@@ -160,6 +381,12 @@ impl<'a> Texture<'a> {
     pub fn color_channels(&self) -> u8 {
         self.color_channels
     }
+    pub fn internal_format(&self) -> u32 {
+        self.internal_format
+    }
+    pub fn levels(&self) -> u32 {
+        self.levels
+    }
     pub fn label(&self) -> Option<&'a str> {
         self.label
     }
@@ -173,6 +400,49 @@ impl Drop for Texture<'_> {
     }
 }
 
+/// Like `image::open`, but keeps Radiance HDR files in floats, see [`load_image`]
+pub(crate) fn open_image(path: &Path) -> Result<DynamicImage, TextureError> {
+    let data = fs::read(path).map_err(ImageError::from)?;
+
+    load_image(&data)
+}
+
+/// Like `image::load_from_memory`, but keeps Radiance HDR data in floats instead of
+/// tone mapping it to 8 bits. It's recognized by its `#?RADIANCE` (or `#?RGBE`) magic.
+pub(crate) fn load_image(data: &[u8]) -> Result<DynamicImage, TextureError> {
+    let is_hdr = data.starts_with(b"#?RADIANCE") || data.starts_with(b"#?RGBE");
+    if !is_hdr {
+        return Ok(image::load_from_memory(data)?);
+    }
+
+    let decoder = HdrDecoder::new(data)?;
+    let HdrMetadata { width, height, .. } = decoder.metadata();
+    let pixels = decoder.read_image_hdr()?;
+    let image = Rgb32FImage::from_raw(
+        width,
+        height,
+        pixels.into_iter().flat_map(|p| p.0).collect(),
+    )
+    .ok_or(TextureError::WrongSizedData)?;
+
+    Ok(DynamicImage::ImageRgb32F(image))
+}
+
+/// Runs `upload` with an unpack alignment of 1, rows of RGB8 or R8 data aren't
+/// necessarily 4-byte aligned
+pub(crate) unsafe fn with_tight_unpacking(gl: &gl::Gl, upload: impl FnOnce()) {
+    let mut previous_alignment = 0;
+    gl.GetIntegerv(gl::UNPACK_ALIGNMENT, &mut previous_alignment);
+    gl.PixelStorei(gl::UNPACK_ALIGNMENT, 1);
+    upload();
+    gl.PixelStorei(gl::UNPACK_ALIGNMENT, previous_alignment);
+}
+
+/// The number of levels in a full mipmap chain down to 1 x 1
+pub fn mip_levels((width, height): (u32, u32)) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
 #[derive(Debug, thiserror::Error)]
 pub enum TextureError {
     #[error("Image format not supported")]
@@ -181,4 +451,16 @@ pub enum TextureError {
     WrongSizedData,
     #[error("Image error: {0}")]
     ImageError(#[from] image::ImageError),
+    #[error("{0} isn't supported, only BC1-BC7 are uploaded compressed")]
+    UnsupportedCompressedFormat(String),
+    #[error("The driver doesn't support the compressed format {0:#x}")]
+    UnsupportedByDriver(u32),
+    #[error("Only 2D textures are supported, this one has {0}")]
+    Not2d(String),
+    #[error("Not a KTX2 or DDS file")]
+    UnknownContainer,
+    #[error("KTX2 error: {0}")]
+    Ktx2Error(#[from] ktx2::ParseError),
+    #[error("DDS error: {0}")]
+    DdsError(#[from] ddsfile::Error),
 }
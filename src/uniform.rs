@@ -0,0 +1,195 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use glam::{IVec2, IVec3, IVec4, Mat2, Mat3, Mat4, Quat, UVec2, UVec3, UVec4, Vec2, Vec3, Vec4};
+
+/// A value that can be written to a uniform with `glProgramUniform*`, see [`Program::set`]
+///
+/// [`Program::set`]: crate::program::Program::set
+pub trait Uniform {
+    /// The `GL_TYPE`s of the uniforms this can be written to
+    const GL_TYPES: &'static [u32];
+
+    unsafe fn set(&self, gl: &gl::Gl, program: u32, location: i32);
+}
+
+/// A [`Uniform`] that can fill an array uniform in a single call
+pub trait UniformArray: Uniform + Sized {
+    unsafe fn set_array(values: &[Self], gl: &gl::Gl, program: u32, location: i32);
+}
+
+/// The texture unit a sampler reads from, like the slot passed to `Texture::bind`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextureUnit(pub u32);
+
+macro_rules! uniform {
+    ($ty:ty, [$($gl_type:expr),+ $(,)?], |$values:ident, $gl:ident, $program:ident, $location:ident| $set:expr) => {
+        impl Uniform for $ty {
+            const GL_TYPES: &'static [u32] = &[$($gl_type),+];
+
+            unsafe fn set(&self, gl: &gl::Gl, program: u32, location: i32) {
+                Self::set_array(std::slice::from_ref(self), gl, program, location);
+            }
+        }
+
+        impl UniformArray for $ty {
+            unsafe fn set_array($values: &[Self], $gl: &gl::Gl, $program: u32, $location: i32) {
+                $set
+            }
+        }
+    };
+}
+
+macro_rules! uniform_vector {
+    ($ty:ty, $gl_type:expr, $function:ident) => {
+        uniform!($ty, [$gl_type], |values, gl, program, location| {
+            gl.$function(
+                program,
+                location,
+                values.len() as i32,
+                values.as_ptr().cast(),
+            )
+        });
+    };
+}
+
+macro_rules! uniform_matrix {
+    ($ty:ty, $gl_type:expr, $function:ident) => {
+        uniform!($ty, [$gl_type], |values, gl, program, location| {
+            gl.$function(
+                program,
+                location,
+                values.len() as i32,
+                gl::FALSE,
+                values.as_ptr().cast(),
+            )
+        });
+    };
+}
+
+// GLSL bools can be set through any of the scalar types
+uniform_vector!(f32, gl::FLOAT, ProgramUniform1fv);
+uniform!(i32, [gl::INT, gl::BOOL], |values, gl, program, location| {
+    gl.ProgramUniform1iv(program, location, values.len() as i32, values.as_ptr())
+});
+uniform!(
+    u32,
+    [gl::UNSIGNED_INT, gl::BOOL],
+    |values, gl, program, location| {
+        gl.ProgramUniform1uiv(program, location, values.len() as i32, values.as_ptr())
+    }
+);
+uniform!(bool, [gl::BOOL], |values, gl, program, location| {
+    let values: Vec<i32> = values.iter().map(|&value| value as i32).collect();
+    gl.ProgramUniform1iv(program, location, values.len() as i32, values.as_ptr())
+});
+
+uniform_vector!(Vec2, gl::FLOAT_VEC2, ProgramUniform2fv);
+uniform_vector!(Vec3, gl::FLOAT_VEC3, ProgramUniform3fv);
+uniform_vector!(Vec4, gl::FLOAT_VEC4, ProgramUniform4fv);
+// As (x, y, z, w)
+uniform_vector!(Quat, gl::FLOAT_VEC4, ProgramUniform4fv);
+uniform_vector!(IVec2, gl::INT_VEC2, ProgramUniform2iv);
+uniform_vector!(IVec3, gl::INT_VEC3, ProgramUniform3iv);
+uniform_vector!(IVec4, gl::INT_VEC4, ProgramUniform4iv);
+uniform_vector!(UVec2, gl::UNSIGNED_INT_VEC2, ProgramUniform2uiv);
+uniform_vector!(UVec3, gl::UNSIGNED_INT_VEC3, ProgramUniform3uiv);
+uniform_vector!(UVec4, gl::UNSIGNED_INT_VEC4, ProgramUniform4uiv);
+
+uniform_matrix!(Mat2, gl::FLOAT_MAT2, ProgramUniformMatrix2fv);
+uniform_matrix!(Mat3, gl::FLOAT_MAT3, ProgramUniformMatrix3fv);
+uniform_matrix!(Mat4, gl::FLOAT_MAT4, ProgramUniformMatrix4fv);
+
+uniform!(
+    TextureUnit,
+    [
+        gl::SAMPLER_1D,
+        gl::SAMPLER_2D,
+        gl::SAMPLER_3D,
+        gl::SAMPLER_CUBE,
+        gl::SAMPLER_1D_SHADOW,
+        gl::SAMPLER_2D_SHADOW,
+        gl::SAMPLER_1D_ARRAY,
+        gl::SAMPLER_2D_ARRAY,
+        gl::SAMPLER_1D_ARRAY_SHADOW,
+        gl::SAMPLER_2D_ARRAY_SHADOW,
+        gl::SAMPLER_2D_MULTISAMPLE,
+        gl::SAMPLER_2D_MULTISAMPLE_ARRAY,
+        gl::SAMPLER_CUBE_SHADOW,
+        gl::SAMPLER_CUBE_MAP_ARRAY,
+        gl::SAMPLER_CUBE_MAP_ARRAY_SHADOW,
+        gl::SAMPLER_BUFFER,
+        gl::SAMPLER_2D_RECT,
+        gl::SAMPLER_2D_RECT_SHADOW,
+        gl::INT_SAMPLER_1D,
+        gl::INT_SAMPLER_2D,
+        gl::INT_SAMPLER_3D,
+        gl::INT_SAMPLER_CUBE,
+        gl::INT_SAMPLER_1D_ARRAY,
+        gl::INT_SAMPLER_2D_ARRAY,
+        gl::INT_SAMPLER_2D_MULTISAMPLE,
+        gl::INT_SAMPLER_2D_MULTISAMPLE_ARRAY,
+        gl::INT_SAMPLER_CUBE_MAP_ARRAY,
+        gl::INT_SAMPLER_BUFFER,
+        gl::INT_SAMPLER_2D_RECT,
+        gl::UNSIGNED_INT_SAMPLER_1D,
+        gl::UNSIGNED_INT_SAMPLER_2D,
+        gl::UNSIGNED_INT_SAMPLER_3D,
+        gl::UNSIGNED_INT_SAMPLER_CUBE,
+        gl::UNSIGNED_INT_SAMPLER_1D_ARRAY,
+        gl::UNSIGNED_INT_SAMPLER_2D_ARRAY,
+        gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE,
+        gl::UNSIGNED_INT_SAMPLER_2D_MULTISAMPLE_ARRAY,
+        gl::UNSIGNED_INT_SAMPLER_CUBE_MAP_ARRAY,
+        gl::UNSIGNED_INT_SAMPLER_BUFFER,
+        gl::UNSIGNED_INT_SAMPLER_2D_RECT,
+    ],
+    |values, gl, program, location| {
+        let units: Vec<i32> = values.iter().map(|unit| unit.0 as i32).collect();
+        gl.ProgramUniform1iv(program, location, units.len() as i32, units.as_ptr())
+    }
+);
+
+impl<T: UniformArray, const N: usize> Uniform for [T; N] {
+    const GL_TYPES: &'static [u32] = T::GL_TYPES;
+
+    unsafe fn set(&self, gl: &gl::Gl, program: u32, location: i32) {
+        T::set_array(self, gl, program, location);
+    }
+}
+
+impl<T: UniformArray> Uniform for [T] {
+    const GL_TYPES: &'static [u32] = T::GL_TYPES;
+
+    unsafe fn set(&self, gl: &gl::Gl, program: u32, location: i32) {
+        T::set_array(self, gl, program, location);
+    }
+}
+
+impl<T: UniformArray> Uniform for Vec<T> {
+    const GL_TYPES: &'static [u32] = T::GL_TYPES;
+
+    unsafe fn set(&self, gl: &gl::Gl, program: u32, location: i32) {
+        T::set_array(self, gl, program, location);
+    }
+}
+
+impl<T: Uniform + ?Sized> Uniform for &T {
+    const GL_TYPES: &'static [u32] = T::GL_TYPES;
+
+    unsafe fn set(&self, gl: &gl::Gl, program: u32, location: i32) {
+        T::set(self, gl, program, location);
+    }
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum UniformError {
+    #[error("Uniform {0} isn't active, it's misspelled or was optimized out")]
+    NotActive(String),
+    #[error("Uniform {name} is a {expected} in the shader, it can't be set from {found}")]
+    TypeMismatch {
+        name: String,
+        expected: &'static str,
+        found: &'static str,
+    },
+}
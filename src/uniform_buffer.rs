@@ -0,0 +1,98 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::slice;
+
+use glam::Mat4;
+
+use crate::{
+    block_layout::Std140,
+    buffer::{Buffer, BufferError},
+    camera::Camera,
+};
+
+/// A uniform buffer holding one `T`, for `layout (std140, binding = N) uniform Block { ... }`
+pub struct UniformBuffer<'a, T: Std140> {
+    gl: &'a gl::Gl,
+    buffer: Buffer<'a, T>,
+    binding: u32,
+}
+
+impl<'a, T: Std140> UniformBuffer<'a, T> {
+    /// Creates the buffer, it's bound to `binding` by [`UniformBuffer::bind`]
+    pub fn new(
+        gl: &'a gl::Gl,
+        value: &T,
+        binding: u32,
+        label: Option<&'a str>,
+    ) -> Result<Self, BufferError> {
+        let buffer = Buffer::from_data(gl, slice::from_ref(value), gl::DYNAMIC_STORAGE_BIT, label)?;
+
+        Ok(Self {
+            gl,
+            buffer,
+            binding,
+        })
+    }
+
+    pub fn set(&self, value: &T) {
+        self.buffer
+            .set_data(0, slice::from_ref(value))
+            .expect("Uniform buffers are created with DYNAMIC_STORAGE_BIT");
+    }
+
+    /// Reads the value back from the GPU
+    pub fn get(&self) -> T {
+        self.buffer.read()[0]
+    }
+
+    /// Binds the buffer to its `GL_UNIFORM_BUFFER` binding point
+    pub unsafe fn bind(&self) {
+        self.gl
+            .BindBufferBase(gl::UNIFORM_BUFFER, self.binding, self.buffer.id());
+    }
+
+    pub unsafe fn unbind(&self) {
+        self.gl.BindBufferBase(gl::UNIFORM_BUFFER, self.binding, 0);
+    }
+
+    pub fn buffer(&self) -> &Buffer<'a, T> {
+        &self.buffer
+    }
+    pub fn binding(&self) -> u32 {
+        self.binding
+    }
+    pub fn id(&self) -> u32 {
+        self.buffer.id()
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.buffer.label()
+    }
+}
+
+/// The built-in per-frame camera block, at binding [`CameraBlock::BINDING`].
+///
+/// Shaders declare it with [`CameraBlock::GLSL`].
+#[repr(C)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable, Std140)]
+pub struct CameraBlock {
+    pub view: Mat4,
+    pub proj: Mat4,
+    pub proj_view: Mat4,
+}
+
+impl CameraBlock {
+    pub const BINDING: u32 = 0;
+    /// The block declaration, as `camera.view`, `camera.proj` and `camera.proj_view`
+    pub const GLSL: &'static str = include_str!("shaders/camera.glsl");
+}
+
+impl From<&Camera> for CameraBlock {
+    fn from(camera: &Camera) -> Self {
+        Self {
+            view: camera.view_matrix(),
+            proj: camera.proj_matrix(),
+            proj_view: camera.proj_view_matrix(),
+        }
+    }
+}
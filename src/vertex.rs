@@ -1,8 +1,72 @@
 // Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
 // SPDX-License-Identifier: MIT
 
+pub use gl_playground_derive::VertexLayout;
+
+/// One vertex attribute, as passed to `glVertexArrayAttrib*Format`
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct VertexAttribute {
+    pub location: u32,
+    pub components: i32,
+    /// e.g. `gl::FLOAT` or `gl::UNSIGNED_BYTE`
+    pub data_type: u32,
+    pub normalized: bool,
+    /// Passed as an integer (`ivec`/`uvec` in the shader) instead of being converted to float
+    pub integer: bool,
+    /// Offset in bytes from the start of the vertex
+    pub offset: u32,
+}
+
+/// Describes how a vertex type is laid out in a vertex buffer.
+///
+/// Usually derived with `#[derive(VertexLayout)]`.
+pub trait VertexLayout: bytemuck::Pod {
+    const ATTRIBUTES: &'static [VertexAttribute];
+}
+
+/// The GL component count and type of a vertex attribute field
+pub trait AttributeFormat {
+    const COMPONENTS: i32;
+    const DATA_TYPE: u32;
+    const INTEGER: bool;
+}
+
+macro_rules! impl_attribute_format {
+    ($ty:ty, $data_type:expr, $integer:expr) => {
+        impl_attribute_format!(@impl $ty, 1, $data_type, $integer);
+        impl_attribute_format!(@impl [$ty; 1], 1, $data_type, $integer);
+        impl_attribute_format!(@impl [$ty; 2], 2, $data_type, $integer);
+        impl_attribute_format!(@impl [$ty; 3], 3, $data_type, $integer);
+        impl_attribute_format!(@impl [$ty; 4], 4, $data_type, $integer);
+    };
+    (@impl $ty:ty, $components:expr, $data_type:expr, $integer:expr) => {
+        impl AttributeFormat for $ty {
+            const COMPONENTS: i32 = $components;
+            const DATA_TYPE: u32 = $data_type;
+            const INTEGER: bool = $integer;
+        }
+    };
+}
+
+impl_attribute_format!(f32, gl::FLOAT, false);
+impl_attribute_format!(i8, gl::BYTE, true);
+impl_attribute_format!(u8, gl::UNSIGNED_BYTE, true);
+impl_attribute_format!(i16, gl::SHORT, true);
+impl_attribute_format!(u16, gl::UNSIGNED_SHORT, true);
+impl_attribute_format!(i32, gl::INT, true);
+impl_attribute_format!(u32, gl::UNSIGNED_INT, true);
+impl_attribute_format!(@impl glam::Vec2, 2, gl::FLOAT, false);
+impl_attribute_format!(@impl glam::Vec3, 3, gl::FLOAT, false);
+impl_attribute_format!(@impl glam::Vec4, 4, gl::FLOAT, false);
+impl_attribute_format!(@impl glam::IVec2, 2, gl::INT, true);
+impl_attribute_format!(@impl glam::IVec3, 3, gl::INT, true);
+impl_attribute_format!(@impl glam::IVec4, 4, gl::INT, true);
+impl_attribute_format!(@impl glam::UVec2, 2, gl::UNSIGNED_INT, true);
+impl_attribute_format!(@impl glam::UVec3, 3, gl::UNSIGNED_INT, true);
+impl_attribute_format!(@impl glam::UVec4, 4, gl::UNSIGNED_INT, true);
+
 #[repr(C)]
-#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable)]
+#[derive(Copy, Clone, Debug, Default, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
 pub struct Vertex {
     pub position: [f32; 3],
     pub uv: [f32; 2],
@@ -14,6 +78,27 @@ impl Vertex {
     }
 }
 
+/// A [`Vertex`] with a normal, as produced by the model loaders
+#[repr(C)]
+#[derive(
+    Copy, Clone, Debug, Default, PartialEq, bytemuck::Pod, bytemuck::Zeroable, VertexLayout,
+)]
+pub struct ModelVertex {
+    pub position: [f32; 3],
+    pub uv: [f32; 2],
+    pub normal: [f32; 3],
+}
+
+impl ModelVertex {
+    pub const fn new(position: [f32; 3], uv: [f32; 2], normal: [f32; 3]) -> Self {
+        Self {
+            position,
+            uv,
+            normal,
+        }
+    }
+}
+
 pub const CUBE: [Vertex; 36] = [
     Vertex::new([-0.5, -0.5, -0.5], [0.0, 0.0]),
     Vertex::new([0.5, -0.5, -0.5], [1.0, 0.0]),
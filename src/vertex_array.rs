@@ -0,0 +1,116 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::mem::size_of;
+
+use crate::{buffer::Buffer, vertex::VertexLayout};
+
+pub struct VertexArray<'a> {
+    gl: &'a gl::Gl,
+    id: u32,
+    label: Option<&'a str>,
+}
+
+impl<'a> VertexArray<'a> {
+    /// Creates a vertex array without any attributes
+    pub fn new(gl: &'a gl::Gl, label: Option<&'a str>) -> Self {
+        let mut id = 0;
+        unsafe {
+            gl.CreateVertexArrays(1, &mut id);
+            tracing::trace!("Created VertexArray ({})", id);
+
+            if let Some(label) = label {
+                gl.ObjectLabel(
+                    gl::VERTEX_ARRAY,
+                    id,
+                    label.len() as i32,
+                    label.as_ptr().cast(),
+                );
+                tracing::trace!("Adding label to VertexArray ({}): {}", id, label);
+            }
+        }
+
+        Self { gl, id, label }
+    }
+
+    /// Creates a vertex array with the attributes of `V` sourced from binding 0
+    pub fn from_layout<V: VertexLayout>(gl: &'a gl::Gl, label: Option<&'a str>) -> Self {
+        let vertex_array = Self::new(gl, label);
+        vertex_array.set_layout::<V>(0);
+
+        vertex_array
+    }
+
+    /// Enables the attributes of `V` and sources them from `binding`
+    pub fn set_layout<V: VertexLayout>(&self, binding: u32) {
+        for attribute in V::ATTRIBUTES {
+            unsafe {
+                self.gl.EnableVertexArrayAttrib(self.id, attribute.location);
+
+                if attribute.integer {
+                    self.gl.VertexArrayAttribIFormat(
+                        self.id,
+                        attribute.location,
+                        attribute.components,
+                        attribute.data_type,
+                        attribute.offset,
+                    );
+                } else {
+                    self.gl.VertexArrayAttribFormat(
+                        self.id,
+                        attribute.location,
+                        attribute.components,
+                        attribute.data_type,
+                        attribute.normalized as u8,
+                        attribute.offset,
+                    );
+                }
+
+                self.gl
+                    .VertexArrayAttribBinding(self.id, attribute.location, binding);
+            }
+            tracing::trace!(
+                "VertexArray ({}) attribute {:?} from binding {}",
+                self.id,
+                attribute,
+                binding
+            );
+        }
+    }
+
+    /// Sources `binding` from `buffer`, with a stride of one `V`
+    pub fn set_vertex_buffer<V: VertexLayout>(&self, binding: u32, buffer: &Buffer<V>) {
+        unsafe {
+            self.gl.VertexArrayVertexBuffer(
+                self.id,
+                binding,
+                buffer.id(),
+                0,
+                size_of::<V>() as i32,
+            );
+        }
+    }
+
+    pub unsafe fn bind(&self) {
+        self.gl.BindVertexArray(self.id);
+    }
+
+    pub unsafe fn unbind(&self) {
+        self.gl.BindVertexArray(0);
+    }
+
+    pub fn id(&self) -> u32 {
+        self.id
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
+
+impl Drop for VertexArray<'_> {
+    fn drop(&mut self) {
+        unsafe {
+            self.gl.DeleteVertexArrays(1, &self.id);
+        }
+    }
+}
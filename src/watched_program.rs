@@ -0,0 +1,272 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt,
+    path::{Path, PathBuf},
+    sync::mpsc::{self, Receiver},
+};
+
+use notify::{EventKind, RecursiveMode, Watcher};
+use tracing::instrument;
+
+use crate::{
+    preprocessor::Preprocessor,
+    program::{Program, ProgramError},
+    program_cache::ProgramCache,
+    uniform::Uniform,
+};
+
+/// A [`Program`] loaded from shader files that recompiles itself when they change.
+///
+/// `#include`s are resolved relative to the directory of the including shader,
+/// and changes to included files trigger a reload too.
+/// Call [`WatchedProgram::reload_if_changed`] once per frame. If the new sources fail
+/// to build, the last good program is kept and the error is logged.
+pub struct WatchedProgram<'a> {
+    gl: &'a gl::Gl,
+    program: Program<'a>,
+    vertex_path: PathBuf,
+    fragment_path: PathBuf,
+    cache: Option<&'a ProgramCache>,
+    uniforms: HashMap<String, Option<i32>>,
+    /// Every file the program was built from, canonicalized
+    dependencies: HashSet<PathBuf>,
+    watched_dirs: HashSet<PathBuf>,
+    watcher: notify::RecommendedWatcher,
+    events: Receiver<notify::Result<notify::Event>>,
+    label: Option<&'a str>,
+}
+
+impl<'a> WatchedProgram<'a> {
+    #[instrument(skip(gl))]
+    pub fn from_files(
+        gl: &'a gl::Gl,
+        vertex_path: impl AsRef<Path> + fmt::Debug,
+        fragment_path: impl AsRef<Path> + fmt::Debug,
+        label: Option<&'a str>,
+    ) -> Result<Self, WatchedProgramError> {
+        Self::create(
+            gl,
+            vertex_path.as_ref(),
+            fragment_path.as_ref(),
+            None,
+            label,
+        )
+    }
+
+    /// Like [`WatchedProgram::from_files`], loading and storing every build through `cache`
+    #[instrument(skip(gl, cache))]
+    pub fn from_files_cached(
+        gl: &'a gl::Gl,
+        vertex_path: impl AsRef<Path> + fmt::Debug,
+        fragment_path: impl AsRef<Path> + fmt::Debug,
+        cache: &'a ProgramCache,
+        label: Option<&'a str>,
+    ) -> Result<Self, WatchedProgramError> {
+        Self::create(
+            gl,
+            vertex_path.as_ref(),
+            fragment_path.as_ref(),
+            Some(cache),
+            label,
+        )
+    }
+
+    fn create(
+        gl: &'a gl::Gl,
+        vertex_path: &Path,
+        fragment_path: &Path,
+        cache: Option<&'a ProgramCache>,
+        label: Option<&'a str>,
+    ) -> Result<Self, WatchedProgramError> {
+        let vertex_path = canonicalize(vertex_path)?;
+        let fragment_path = canonicalize(fragment_path)?;
+        let (program, dependencies) = build(gl, &vertex_path, &fragment_path, cache, label)?;
+
+        let (sender, events) = mpsc::channel();
+        let watcher = notify::recommended_watcher(sender)?;
+
+        let mut watched = Self {
+            gl,
+            program,
+            vertex_path,
+            fragment_path,
+            cache,
+            uniforms: HashMap::new(),
+            dependencies: HashSet::new(),
+            watched_dirs: HashSet::new(),
+            watcher,
+            events,
+            label,
+        };
+        watched.watch(dependencies)?;
+
+        Ok(watched)
+    }
+
+    /// Reloads if one of the files changed since the last call, returns whether the
+    /// program was replaced
+    pub fn reload_if_changed(&mut self) -> bool {
+        let mut changed = false;
+        for event in self.events.try_iter() {
+            match event {
+                Ok(event) => {
+                    changed |= matches!(event.kind, EventKind::Create(..) | EventKind::Modify(..))
+                        && event
+                            .paths
+                            .iter()
+                            .any(|path| self.dependencies.contains(path))
+                }
+                Err(e) => tracing::warn!("Shader: File watcher error: {}", e),
+            }
+        }
+
+        changed && self.reload().is_ok()
+    }
+
+    /// Rebuilds the program from the files now, keeping the old one on failure
+    pub fn reload(&mut self) -> Result<(), WatchedProgramError> {
+        match build(
+            self.gl,
+            &self.vertex_path,
+            &self.fragment_path,
+            self.cache,
+            self.label,
+        ) {
+            Ok((program, dependencies)) => {
+                self.program = program;
+                // Includes may have been added or removed
+                self.watch(dependencies)?;
+                for (name, location) in &mut self.uniforms {
+                    *location = unsafe { self.program.get_unifrom(name) };
+                }
+                tracing::info!(
+                    "Shader: Reloaded {} and {}",
+                    self.vertex_path.display(),
+                    self.fragment_path.display()
+                );
+
+                Ok(())
+            }
+            Err(e) => {
+                tracing::error!(
+                    "Shader: Failed to reload, keeping the last good program: {}",
+                    e
+                );
+
+                Err(e)
+            }
+        }
+    }
+
+    /// The location of a uniform, looked up again after every reload
+    pub fn uniform(&mut self, name: &str) -> Option<i32> {
+        if let Some(location) = self.uniforms.get(name) {
+            return *location;
+        }
+
+        let location = unsafe { self.program.get_unifrom(name) };
+        self.uniforms.insert(name.to_owned(), location);
+        location
+    }
+
+    /// See [`Program::set`], locations are looked up again after every reload
+    pub fn set(&self, name: &str, value: impl Uniform) {
+        self.program.set(name, value);
+    }
+
+    pub unsafe fn bind(&self) {
+        self.program.bind();
+    }
+
+    pub unsafe fn unbind(&self) {
+        self.program.unbind();
+    }
+
+    pub fn program(&self) -> &Program<'a> {
+        &self.program
+    }
+    pub fn vertex_path(&self) -> &Path {
+        &self.vertex_path
+    }
+    pub fn fragment_path(&self) -> &Path {
+        &self.fragment_path
+    }
+    pub fn label(&self) -> Option<&'a str> {
+        self.label
+    }
+}
+
+impl WatchedProgram<'_> {
+    fn watch(&mut self, dependencies: HashSet<PathBuf>) -> Result<(), WatchedProgramError> {
+        // Editors often save by replacing the file, so watch the directories instead
+        for path in &dependencies {
+            let dir = path.parent().expect("Canonical file paths have a parent");
+            if self.watched_dirs.insert(dir.to_owned()) {
+                self.watcher.watch(dir, RecursiveMode::NonRecursive)?;
+                tracing::trace!("Shader: Watching {} for changes", dir.display());
+            }
+        }
+        self.dependencies = dependencies;
+
+        Ok(())
+    }
+}
+
+/// Builds the program and returns it with every file it was built from
+fn build<'a>(
+    gl: &'a gl::Gl,
+    vertex_path: &Path,
+    fragment_path: &Path,
+    cache: Option<&'a ProgramCache>,
+    label: Option<&'a str>,
+) -> Result<(Program<'a>, HashSet<PathBuf>), WatchedProgramError> {
+    let load = |path: &Path| {
+        let dir = path.parent().expect("Canonical file paths have a parent");
+        Preprocessor::new(dir)
+            .load(path.file_name().expect("Canonical file paths have a name"))
+            .map_err(ProgramError::from)
+    };
+    let vertex = load(vertex_path)?;
+    let fragment = load(fragment_path)?;
+
+    let dependencies = vertex
+        .files
+        .iter()
+        .chain(&fragment.files)
+        .map(|path| canonicalize(path))
+        .collect::<Result<_, _>>()?;
+    let mut builder = Program::builder(gl).vertex(vertex).fragment(fragment);
+    if let Some(cache) = cache {
+        builder = builder.cache(cache);
+    }
+    if let Some(label) = label {
+        builder = builder.label(label);
+    }
+    let program = builder.build()?;
+
+    Ok((program, dependencies))
+}
+
+fn canonicalize(path: &Path) -> Result<PathBuf, WatchedProgramError> {
+    path.canonicalize()
+        .map_err(|source| WatchedProgramError::Io {
+            path: path.to_owned(),
+            source,
+        })
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum WatchedProgramError {
+    #[error("{}: {source}", path.display())]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("Program error: {0}")]
+    ProgramError(#[from] ProgramError),
+    #[error("File watcher error: {0}")]
+    NotifyError(#[from] notify::Error),
+}
@@ -0,0 +1,10 @@
+#version 450 core
+
+#include "common/broken.glsl"
+
+layout (location = 0) out vec4 fColor;
+
+void main()
+{
+	fColor = vec4(broken());
+}
@@ -0,0 +1,4 @@
+float broken()
+{
+	return undefined_variable;
+}
@@ -0,0 +1,7 @@
+#ifndef CONSTANTS_GLSL
+#define CONSTANTS_GLSL
+
+const vec3 LIGHT_DIR = vec3(0.0, 0.0, 1.0);
+const float AMBIENT = 0.5;
+
+#endif
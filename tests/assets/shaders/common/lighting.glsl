@@ -0,0 +1,7 @@
+#pragma once
+#include "common/constants.glsl"
+
+float lambert(vec3 normal)
+{
+	return max(dot(normal, LIGHT_DIR), 0.0);
+}
@@ -0,0 +1,2 @@
+// Includes the file that included it
+#include "cycle_a.glsl"
@@ -0,0 +1,11 @@
+#version 450 core
+
+#include "common/lighting.glsl"
+#include "common/lighting.glsl"
+
+layout (location = 0) out vec4 fColor;
+
+void main()
+{
+	fColor = vec4(vec3(lambert(vec3(0.0, 0.0, 1.0)) * AMBIENT), 1.0);
+}
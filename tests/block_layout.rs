@@ -0,0 +1,131 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::mem::{offset_of, size_of};
+
+use gl_playground::{
+    block_layout::{Pad, Std140, Std430},
+    compute::ComputeProgram,
+    headless::HeadlessContext,
+    preprocessor::Defines,
+};
+use glam::{Mat4, Vec2, Vec3, Vec4};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable, Std140, Std430)]
+struct Light {
+    position: Vec3,
+    intensity: f32,
+    color: Vec3,
+    _pad: Pad<4>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable, Std140)]
+struct Scene {
+    model: Mat4,
+    offset: Vec2,
+    _pad0: Pad<8>,
+    tint: Vec3,
+    exposure: f32,
+    lights: [Light; 2],
+    weights: [Vec4; 2],
+    count: u32,
+    _pad1: Pad<12>,
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, bytemuck::Pod, bytemuck::Zeroable, Std430)]
+struct Particle {
+    position: Vec2,
+    velocity: Vec2,
+    weights: [f32; 3],
+    age: f32,
+}
+
+const BLOCKS: &str = "#version 450 core
+layout (local_size_x = 1) in;
+
+struct Light {
+    vec3 position;
+    float intensity;
+    vec3 color;
+};
+
+layout (std140, binding = 0) uniform Scene {
+    mat4 model;
+    vec2 offset;
+    vec3 tint;
+    float exposure;
+    Light lights[2];
+    vec4 weights[2];
+    uint count;
+};
+
+struct Particle {
+    vec2 position;
+    vec2 velocity;
+    float weights[3];
+    float age;
+};
+
+layout (std430, binding = 1) buffer Particles {
+    Particle particles[];
+};
+
+void main()
+{
+    particles[count].position = model[0].xy + offset + tint.xy * exposure
+        + lights[1].color.xy * lights[0].intensity + lights[0].position.xy + weights[1].xy;
+    particles[0].weights[2] = particles[1].age + particles[1].velocity.x;
+}
+";
+
+#[test]
+fn derived_alignments_follow_the_rules() {
+    assert_eq!(<Light as Std140>::ALIGN, 16);
+    assert_eq!(<Light as Std430>::ALIGN, 16);
+    assert_eq!(<Scene as Std140>::ALIGN, 16);
+    assert_eq!(size_of::<Scene>(), 208);
+    // std430 structs aren't rounded up to a vec4
+    assert_eq!(<Particle as Std430>::ALIGN, 8);
+    assert_eq!(size_of::<Particle>(), 32);
+}
+
+#[test]
+fn derived_offsets_match_the_driver() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let program = ComputeProgram::from_source(context.gl(), BLOCKS, &Defines::new(), None)
+        .expect("Failed to build compute program");
+    let interface = program.program().reflect();
+
+    let scene = interface.uniform_block("Scene").unwrap();
+    assert_eq!(scene.data_size as usize, size_of::<Scene>());
+    let offset = |name: &str| scene.member(name).unwrap().offset as usize;
+    assert_eq!(offset("model"), offset_of!(Scene, model));
+    assert_eq!(offset("offset"), offset_of!(Scene, offset));
+    assert_eq!(offset("tint"), offset_of!(Scene, tint));
+    assert_eq!(offset("exposure"), offset_of!(Scene, exposure));
+    assert_eq!(offset("lights[0].position"), offset_of!(Scene, lights));
+    assert_eq!(
+        offset("lights[1].color"),
+        offset_of!(Scene, lights) + size_of::<Light>() + offset_of!(Light, color)
+    );
+    assert_eq!(offset("weights[0]"), offset_of!(Scene, weights));
+    assert_eq!(
+        scene.member("weights[0]").unwrap().array_stride as usize,
+        size_of::<Vec4>()
+    );
+    assert_eq!(offset("count"), offset_of!(Scene, count));
+
+    let particles = interface.storage_block("Particles").unwrap();
+    let age = particles.member("particles[0].age").unwrap();
+    assert_eq!(age.offset as usize, offset_of!(Particle, age));
+    assert_eq!(
+        age.top_level_array_stride,
+        Some(size_of::<Particle>() as u32)
+    );
+    let weights = particles.member("particles[0].weights[0]").unwrap();
+    assert_eq!(weights.offset as usize, offset_of!(Particle, weights));
+    assert_eq!(weights.array_stride, 4);
+}
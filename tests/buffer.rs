@@ -0,0 +1,145 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    buffer::{Buffer, BufferError},
+    headless::HeadlessContext,
+};
+
+#[test]
+fn uploads_and_reads_back() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let buffer = Buffer::from_data(gl, &[1u32, 2, 3, 4], gl::DYNAMIC_STORAGE_BIT, Some("Data"))
+        .expect("Failed to create buffer");
+    assert_eq!(buffer.len(), 4);
+    assert_eq!(buffer.size(), 16);
+    assert_eq!(buffer.read(), vec![1, 2, 3, 4]);
+
+    buffer.set_data(1, &[20, 30]).expect("Failed to set data");
+    assert_eq!(buffer.read(), vec![1, 20, 30, 4]);
+    assert_eq!(buffer.read_range(2..4).unwrap(), vec![30, 4]);
+
+    assert!(matches!(
+        buffer.set_data(3, &[0, 0]),
+        Err(BufferError::OutOfBounds { .. })
+    ));
+}
+
+#[test]
+fn requires_storage_flags() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let mut buffer = Buffer::<f32>::with_len(gl, 8, 0, None).expect("Failed to create buffer");
+    assert_eq!(buffer.read(), vec![0.0; 8]);
+
+    assert!(matches!(
+        buffer.set_data(0, &[1.0]),
+        Err(BufferError::MissingFlag("DYNAMIC_STORAGE_BIT"))
+    ));
+    assert!(matches!(
+        buffer.map_range_mut(0..8, gl::MAP_WRITE_BIT),
+        Err(BufferError::MissingFlag("MAP_WRITE_BIT"))
+    ));
+    assert!(matches!(
+        buffer.map_persistent(gl::MAP_READ_BIT),
+        Err(BufferError::MissingFlag("MAP_PERSISTENT_BIT"))
+    ));
+    assert!(matches!(
+        Buffer::<f32>::with_len(gl, 0, 0, None),
+        Err(BufferError::Empty)
+    ));
+}
+
+#[test]
+fn maps_ranges() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let mut buffer = Buffer::from_data(gl, &[0u8; 8], gl::MAP_READ_BIT | gl::MAP_WRITE_BIT, None)
+        .expect("Failed to create buffer");
+
+    {
+        let mut mapped = buffer
+            .map_range_mut(2..6, gl::MAP_WRITE_BIT)
+            .expect("Failed to map buffer");
+        mapped.copy_from_slice(&[1, 2, 3, 4]);
+    }
+
+    {
+        let mapped = buffer
+            .map_range(0..8, gl::MAP_READ_BIT)
+            .expect("Failed to map buffer");
+        assert_eq!(&*mapped, &[0, 0, 1, 2, 3, 4, 0, 0]);
+    }
+
+    // Read guards can't be written through, so they need MAP_READ_BIT
+    assert!(matches!(
+        buffer.map_range(0..8, gl::MAP_WRITE_BIT),
+        Err(BufferError::MissingAccess("MAP_READ_BIT"))
+    ));
+    assert!(matches!(
+        buffer.map_range_mut(0..8, gl::MAP_READ_BIT),
+        Err(BufferError::MissingAccess("MAP_WRITE_BIT"))
+    ));
+
+    assert_eq!(buffer.read(), vec![0, 0, 1, 2, 3, 4, 0, 0]);
+}
+
+#[test]
+fn maps_persistently() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let flags = gl::MAP_WRITE_BIT | gl::MAP_PERSISTENT_BIT | gl::MAP_COHERENT_BIT;
+    let mut buffer = Buffer::<i32>::with_len(gl, 4, flags, None).expect("Failed to create buffer");
+    assert!(matches!(
+        unsafe { buffer.persistent_mut() },
+        Err(BufferError::NotMapped)
+    ));
+
+    buffer.map_persistent(flags).expect("Failed to map buffer");
+    assert!(matches!(
+        buffer.map_persistent(flags),
+        Err(BufferError::AlreadyMapped)
+    ));
+
+    // Nothing else uses the buffer yet
+    unsafe { buffer.persistent_mut() }
+        .unwrap()
+        .copy_from_slice(&[5, 6, 7, 8]);
+    unsafe { gl.Finish() };
+    // Mapped write-only, reading it back through the pointer isn't allowed
+    assert!(matches!(
+        unsafe { buffer.persistent() },
+        Err(BufferError::MissingAccess("MAP_READ_BIT"))
+    ));
+
+    // Reading with GetNamedBufferSubData is allowed while persistently mapped
+    assert_eq!(buffer.read(), vec![5, 6, 7, 8]);
+
+    buffer.unmap_persistent();
+    assert!(matches!(
+        unsafe { buffer.persistent() },
+        Err(BufferError::NotMapped)
+    ));
+}
+
+#[test]
+fn copies_between_buffers() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let source = Buffer::from_data(gl, &[1u16, 2, 3, 4], 0, None).unwrap();
+    let target = Buffer::<u16>::with_len(gl, 6, 0, None).unwrap();
+
+    source.copy_to(&target, 1..4, 2).expect("Failed to copy");
+    assert_eq!(target.read(), vec![0, 0, 2, 3, 4, 0]);
+
+    assert!(matches!(
+        source.copy_to(&target, 0..4, 4),
+        Err(BufferError::OutOfBounds { .. })
+    ));
+}
@@ -0,0 +1,153 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+#![allow(dead_code)]
+
+use std::path::PathBuf;
+
+use gl_playground::{
+    camera::Camera,
+    headless::HeadlessContext,
+    mesh::{Mesh, MeshData},
+    preprocessor::{Defines, Preprocessor},
+    program::Program,
+    readback,
+    texture::{ColorSpace, Texture},
+    uniform_buffer::{CameraBlock, UniformBuffer},
+    vertex::CUBE,
+};
+use image::{Rgba, RgbaImage};
+
+/// Draws the textured `CUBE` seen through `camera` with the binary's shaders.
+///
+/// The binary uses [`ColorSpace::Srgb`], which also encodes the output, so it needs
+/// an sRGB color attachment. The headless framebuffer is RGBA8 and gets `Linear`.
+pub fn draw_cube(gl: &gl::Gl, camera: &Camera, color_space: ColorSpace) {
+    let program = Program::from_files(
+        gl,
+        &Preprocessor::new(concat!(env!("CARGO_MANIFEST_DIR"), "/src/shaders")),
+        "basic.vert",
+        "basic.frag",
+        &Defines::new(),
+        Some("Basic Shader"),
+    )
+    .expect("Failed to create shader program");
+    let camera_block = UniformBuffer::new(
+        gl,
+        &CameraBlock::from(camera),
+        CameraBlock::BINDING,
+        Some("Camera block"),
+    )
+    .expect("Failed to create camera block");
+    let texture = Texture::from_file(gl, "assets/brick.webp", color_space, Some("Brick wall"))
+        .expect("Failed to load texture");
+
+    unsafe {
+        gl.ClearColor(0.2, 0.2, 0.2, 1.0);
+        gl.Enable(gl::DEPTH_TEST);
+        if color_space == ColorSpace::Srgb {
+            gl.Enable(gl::FRAMEBUFFER_SRGB);
+        }
+
+        let cube = Mesh::new(gl, &MeshData::from_vertices(&CUBE), Some("Cube"))
+            .expect("Failed to create cube mesh");
+
+        gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        program.bind();
+        texture.bind(0);
+        camera_block.bind();
+
+        cube.draw();
+        gl.Finish();
+        gl.Disable(gl::FRAMEBUFFER_SRGB);
+    }
+}
+
+/// Reads the color attachment of the headless framebuffer, top row first
+pub fn read_framebuffer(context: &HeadlessContext) -> RgbaImage {
+    let (width, height) = context.size();
+    readback::read_framebuffer(
+        context.gl(),
+        context.framebuffer(),
+        gl::COLOR_ATTACHMENT0,
+        (0, 0, width, height),
+    )
+}
+
+/// Compares `actual` against `tests/golden/{name}.png`.
+///
+/// A pixel matches when no channel differs by more than `tolerance`.
+/// On a mismatch the actual frame and a diff image (mismatching pixels in red)
+/// are written to `$CARGO_TARGET_TMPDIR/golden/` before panicking.
+/// Run with `UPDATE_GOLDEN=1` to (re)write the stored image instead.
+pub fn assert_golden(name: &str, actual: &RgbaImage, tolerance: u8) {
+    let golden_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/golden")
+        .join(format!("{}.png", name));
+
+    if std::env::var_os("UPDATE_GOLDEN").is_some() {
+        actual
+            .save(&golden_path)
+            .expect("Failed to write golden image");
+        return;
+    }
+
+    let expected = image::open(&golden_path)
+        .unwrap_or_else(|e| {
+            panic!(
+                "Failed to open golden image {} ({}), run with UPDATE_GOLDEN=1 to create it",
+                golden_path.display(),
+                e
+            )
+        })
+        .to_rgba8();
+
+    assert_eq!(
+        expected.dimensions(),
+        actual.dimensions(),
+        "Golden image {} has a different size",
+        name
+    );
+
+    let mut diff = RgbaImage::new(actual.width(), actual.height());
+    let mut mismatched = 0;
+    for (x, y, expected_pixel) in expected.enumerate_pixels() {
+        let actual_pixel = actual.get_pixel(x, y);
+        let matches = expected_pixel
+            .0
+            .iter()
+            .zip(actual_pixel.0.iter())
+            .all(|(e, a)| e.abs_diff(*a) <= tolerance);
+
+        if matches {
+            // Dimmed copy of the expected image, so the differences stand out
+            let [r, g, b, _] = expected_pixel.0;
+            diff.put_pixel(x, y, Rgba([r / 4, g / 4, b / 4, 255]));
+        } else {
+            mismatched += 1;
+            diff.put_pixel(x, y, Rgba([255, 0, 0, 255]));
+        }
+    }
+
+    if mismatched > 0 {
+        let out_dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("golden");
+        std::fs::create_dir_all(&out_dir).expect("Failed to create golden output directory");
+
+        let actual_path = out_dir.join(format!("{}-actual.png", name));
+        let diff_path = out_dir.join(format!("{}-diff.png", name));
+        actual
+            .save(&actual_path)
+            .expect("Failed to write actual image");
+        diff.save(&diff_path).expect("Failed to write diff image");
+
+        panic!(
+            "{} of {} pixels differ from golden image {} by more than {}\n  actual: {}\n  diff: {}",
+            mismatched,
+            actual.width() * actual.height(),
+            golden_path.display(),
+            tolerance,
+            actual_path.display(),
+            diff_path.display(),
+        );
+    }
+}
@@ -0,0 +1,335 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use ddsfile::{AlphaMode, Caps2, D3D10ResourceDimension, D3DFormat, Dds, DxgiFormat};
+use gl_playground::{
+    compressed::{level_size, CompressedFormat, CompressedImage},
+    headless::HeadlessContext,
+    texture::{ColorSpace, Texture, TextureError},
+};
+use ktx2::{Format, Header, Index, LevelIndex, SupercompressionScheme};
+
+/// A BC1 block with both endpoints set to an RGB565 color, so every texel is that color
+fn bc1_block(rgb565: u16) -> [u8; 8] {
+    let [low, high] = rgb565.to_le_bytes();
+    [low, high, low, high, 0, 0, 0, 0]
+}
+
+/// Red, green, blue then white, one color per level
+const LEVEL_COLORS: [(u16, [u8; 4]); 4] = [
+    (0xF800, [255, 0, 0, 255]),
+    (0x07E0, [0, 255, 0, 255]),
+    (0x001F, [0, 0, 255, 255]),
+    (0xFFFF, [255, 255, 255, 255]),
+];
+
+fn bc1_levels(size: (u32, u32)) -> Vec<Vec<u8>> {
+    LEVEL_COLORS
+        .iter()
+        .enumerate()
+        .map(|(level, (rgb565, _))| {
+            let bytes = CompressedFormat::BC1_RGB.level_size(level_size(size, level as u32));
+            bc1_block(*rgb565).repeat(bytes / 8)
+        })
+        .collect()
+}
+
+/// A KTX2 file with an empty data format descriptor, which is all the reader needs
+fn ktx2_file(format: Format, size: (u32, u32), faces: u32, levels: &[Vec<u8>]) -> Vec<u8> {
+    let index_end = Header::LENGTH + levels.len() * LevelIndex::LENGTH;
+    let dfd = 4u32.to_le_bytes();
+    let mut offset = (index_end + dfd.len()) as u64;
+
+    let mut index = Vec::new();
+    for level in levels {
+        let length = level.len() as u64;
+        index.extend(
+            LevelIndex {
+                byte_offset: offset,
+                byte_length: length,
+                uncompressed_byte_length: length,
+            }
+            .as_bytes(),
+        );
+        offset += length;
+    }
+
+    let header = Header {
+        format: Some(format),
+        type_size: 1,
+        pixel_width: size.0,
+        pixel_height: size.1,
+        pixel_depth: 0,
+        layer_count: 0,
+        face_count: faces,
+        level_count: levels.len() as u32,
+        supercompression_scheme: None,
+        index: Index {
+            dfd_byte_offset: index_end as u32,
+            dfd_byte_length: dfd.len() as u32,
+            kvd_byte_offset: 0,
+            kvd_byte_length: 0,
+            sgd_byte_offset: 0,
+            sgd_byte_length: 0,
+        },
+    };
+
+    let mut file = header.as_bytes().to_vec();
+    file.extend(index);
+    file.extend(dfd);
+    file.extend(levels.concat());
+    file
+}
+
+fn dds_file(mut dds: Dds, data: &[u8]) -> Vec<u8> {
+    dds.data = data.to_vec();
+    let mut file = Vec::new();
+    dds.write(&mut file).unwrap();
+    file
+}
+
+/// Decodes `level` of `texture` to RGBA8
+fn read_level(gl: &gl::Gl, texture: &Texture, level: u32) -> Vec<u8> {
+    let (width, height) = level_size(texture.image_size(), level);
+    let mut data = vec![0u8; 4 * width as usize * height as usize];
+    unsafe {
+        gl.GetTextureImage(
+            texture.id(),
+            level as i32,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            data.len() as i32,
+            data.as_mut_ptr().cast(),
+        );
+    }
+    data
+}
+
+#[test]
+fn uploads_every_ktx2_level() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let size = (8, 8);
+    let file = ktx2_file(Format::BC1_RGB_UNORM_BLOCK, size, 1, &bc1_levels(size));
+    let image = CompressedImage::from_memory(&file, ColorSpace::Linear).unwrap();
+    assert_eq!(image.size(), size);
+    assert_eq!(image.format(), CompressedFormat::BC1_RGB);
+    assert_eq!(image.levels().len(), 4);
+
+    let texture = Texture::from_compressed(gl, &image, Some("BC1")).unwrap();
+    assert_eq!(texture.levels(), 4);
+    assert_eq!(texture.internal_format(), gl::COMPRESSED_RGB_S3TC_DXT1_EXT);
+    assert_eq!(texture.color_channels(), 3);
+
+    for (level, (_, color)) in LEVEL_COLORS.iter().enumerate() {
+        let texels = read_level(gl, &texture, level as u32);
+        assert!(
+            texels.chunks_exact(4).all(|texel| texel == color),
+            "level {level}: {texels:?}"
+        );
+    }
+    assert_eq!(unsafe { gl.GetError() }, gl::NO_ERROR);
+}
+
+#[test]
+fn uploads_bptc_from_ktx2() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    // 6 x 6 rounds up to 2 x 2 blocks, then 1 block for 3 x 3 and 1 x 1
+    let levels = vec![vec![0; 64], vec![0; 16], vec![0; 16]];
+    let file = ktx2_file(Format::BC7_SRGB_BLOCK, (6, 6), 1, &levels);
+    let path = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bc7.ktx2");
+    std::fs::write(&path, file).unwrap();
+
+    let texture = Texture::from_compressed_file(gl, &path, ColorSpace::Linear, None).unwrap();
+    assert_eq!(texture.levels(), 3);
+    assert_eq!(
+        texture.internal_format(),
+        gl::COMPRESSED_SRGB_ALPHA_BPTC_UNORM
+    );
+    assert_eq!(unsafe { gl.GetError() }, gl::NO_ERROR);
+}
+
+#[test]
+fn uploads_rgtc_from_dds() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    // Both endpoints at 200, two levels of one block each
+    let block = [200, 200, 0, 0, 0, 0, 0, 0];
+    let dds = Dds::new_dxgi(ddsfile::NewDxgiParams {
+        height: 2,
+        width: 2,
+        depth: None,
+        format: DxgiFormat::BC4_UNorm,
+        mipmap_levels: Some(2),
+        array_layers: None,
+        caps2: None,
+        is_cubemap: false,
+        resource_dimension: D3D10ResourceDimension::Texture2D,
+        alpha_mode: AlphaMode::Unknown,
+    })
+    .unwrap();
+    let file = dds_file(dds, &block.repeat(2));
+
+    let image = CompressedImage::from_memory(&file, ColorSpace::Linear).unwrap();
+    assert_eq!(image.format(), CompressedFormat::BC4);
+    let texture = Texture::from_compressed(gl, &image, None).unwrap();
+    assert_eq!(texture.levels(), 2);
+    assert_eq!(texture.internal_format(), gl::COMPRESSED_RED_RGTC1);
+    assert_eq!(read_level(gl, &texture, 1), [200, 0, 0, 255]);
+}
+
+#[test]
+fn legacy_dds_follows_the_color_space() {
+    let dds = || {
+        Dds::new_d3d(ddsfile::NewD3dParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: D3DFormat::DXT5,
+            mipmap_levels: None,
+            caps2: None,
+        })
+        .unwrap()
+    };
+
+    let file = dds_file(dds(), &[0; 16]);
+    let linear = CompressedImage::from_memory(&file, ColorSpace::Linear).unwrap();
+    assert_eq!(linear.format(), CompressedFormat::BC3);
+    let srgb = CompressedImage::from_memory(&file, ColorSpace::Srgb).unwrap();
+    assert_eq!(srgb.format(), CompressedFormat::BC3_SRGB);
+
+    assert!(matches!(
+        CompressedImage::from_memory(&dds_file(dds(), &[0; 8]), ColorSpace::Linear),
+        Err(TextureError::WrongSizedData)
+    ));
+}
+
+#[test]
+fn rejects_unsupported_files() {
+    let size = (4, 4);
+
+    let uncompressed = ktx2_file(Format::R8G8B8A8_UNORM, size, 1, &[vec![0; 64]]);
+    let error = CompressedImage::from_memory(&uncompressed, ColorSpace::Linear).unwrap_err();
+    assert!(matches!(
+        error,
+        TextureError::UnsupportedCompressedFormat(_)
+    ));
+    assert_eq!(
+        error.to_string(),
+        "KTX2 R8G8B8A8_UNORM isn't supported, only BC1-BC7 are uploaded compressed"
+    );
+
+    let cubemap = ktx2_file(Format::BC1_RGB_UNORM_BLOCK, size, 6, &[vec![0; 48]]);
+    let error = CompressedImage::from_memory(&cubemap, ColorSpace::Linear).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Only 2D textures are supported, this one has 6 faces"
+    );
+
+    let mut supercompressed = ktx2_file(Format::BC1_RGB_UNORM_BLOCK, size, 1, &[vec![0; 8]]);
+    supercompressed[44..48]
+        .copy_from_slice(&SupercompressionScheme::Zstandard.value().to_le_bytes());
+    assert!(matches!(
+        CompressedImage::from_memory(&supercompressed, ColorSpace::Linear),
+        Err(TextureError::UnsupportedCompressedFormat(_))
+    ));
+
+    let truncated = ktx2_file(Format::BC1_RGB_UNORM_BLOCK, (8, 8), 1, &[vec![0; 8]]);
+    assert!(matches!(
+        CompressedImage::from_memory(&truncated, ColorSpace::Linear),
+        Err(TextureError::WrongSizedData)
+    ));
+
+    let uncompressed_dds = Dds::new_dxgi(ddsfile::NewDxgiParams {
+        height: 4,
+        width: 4,
+        depth: None,
+        format: DxgiFormat::R8G8B8A8_UNorm,
+        mipmap_levels: None,
+        array_layers: None,
+        caps2: None,
+        is_cubemap: false,
+        resource_dimension: D3D10ResourceDimension::Texture2D,
+        alpha_mode: AlphaMode::Unknown,
+    })
+    .unwrap();
+    let error =
+        CompressedImage::from_memory(&dds_file(uncompressed_dds, &[0; 64]), ColorSpace::Linear)
+            .unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "DDS R8G8B8A8_UNorm isn't supported, only BC1-BC7 are uploaded compressed"
+    );
+
+    let dxgi = |is_cubemap: bool, resource_dimension| {
+        Dds::new_dxgi(ddsfile::NewDxgiParams {
+            height: 4,
+            width: 4,
+            depth: None,
+            format: DxgiFormat::BC1_UNorm,
+            mipmap_levels: None,
+            array_layers: is_cubemap.then_some(6),
+            caps2: None,
+            is_cubemap,
+            resource_dimension,
+            alpha_mode: AlphaMode::Unknown,
+        })
+        .unwrap()
+    };
+    // A single cubemap has an array size of 1, the faces are only in the flags
+    let cubemap = dds_file(dxgi(true, D3D10ResourceDimension::Texture2D), &[0; 48]);
+    let error = CompressedImage::from_memory(&cubemap, ColorSpace::Linear).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Only 2D textures are supported, this one has 6 faces"
+    );
+    let legacy_cubemap = Dds::new_d3d(ddsfile::NewD3dParams {
+        height: 4,
+        width: 4,
+        depth: None,
+        format: D3DFormat::DXT1,
+        mipmap_levels: None,
+        caps2: Some(Caps2::CUBEMAP | Caps2::CUBEMAP_ALLFACES),
+    })
+    .unwrap();
+    assert!(matches!(
+        CompressedImage::from_memory(&dds_file(legacy_cubemap, &[0; 48]), ColorSpace::Linear),
+        Err(TextureError::Not2d(_))
+    ));
+    let texture_1d = dds_file(dxgi(false, D3D10ResourceDimension::Texture1D), &[0; 8]);
+    let error = CompressedImage::from_memory(&texture_1d, ColorSpace::Linear).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "Only 2D textures are supported, this one has a Texture1D resource dimension"
+    );
+
+    assert!(matches!(
+        CompressedImage::from_memory(b"\x89PNG\r\n\x1a\n", ColorSpace::Linear),
+        Err(TextureError::UnknownContainer)
+    ));
+}
+
+#[test]
+fn rejects_formats_the_driver_lacks() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    // Not a format at all, no driver supports it
+    let format = CompressedFormat {
+        internal_format: 0x1234,
+        ..CompressedFormat::BC1_RGB
+    };
+    let image = CompressedImage::new((4, 4), format, vec![vec![0; 8]]).unwrap();
+    let Err(error) = Texture::from_compressed(gl, &image, None) else {
+        panic!("Uploaded an unknown format");
+    };
+    assert!(matches!(error, TextureError::UnsupportedByDriver(0x1234)));
+    assert_eq!(
+        error.to_string(),
+        "The driver doesn't support the compressed format 0x1234"
+    );
+}
@@ -0,0 +1,85 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    buffer::Buffer,
+    compute::{ComputeProgram, DispatchIndirectCommand},
+    headless::HeadlessContext,
+    preprocessor::Defines,
+};
+
+const SQUARES: &str = "#version 450 core
+layout (local_size_x = 8) in;
+layout (std430, binding = 0) buffer Values { uint values[]; };
+void main()
+{
+    uint i = gl_GlobalInvocationID.x;
+    if (i < values.length()) {
+        values[i] = i * i + OFFSET;
+    }
+}
+";
+
+#[test]
+fn dispatches_directly() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let program = ComputeProgram::from_source(
+        gl,
+        SQUARES,
+        &Defines::new().with_value("OFFSET", "1u"),
+        Some("Squares"),
+    )
+    .expect("Failed to build compute program");
+    assert_eq!(program.local_size(), [8, 1, 1]);
+    assert_eq!(program.groups_for([20, 1, 1]), [3, 1, 1]);
+
+    let values = Buffer::<u32>::with_len(gl, 20, 0, Some("Values")).unwrap();
+    unsafe {
+        gl.BindBufferBase(gl::SHADER_STORAGE_BUFFER, 0, values.id());
+        program.dispatch(program.groups_for([20, 1, 1]));
+        program.memory_barrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+    }
+
+    let expected: Vec<u32> = (0..20).map(|i| i * i + 1).collect();
+    assert_eq!(values.read(), expected);
+    assert_eq!(unsafe { gl.GetError() }, gl::NO_ERROR);
+}
+
+#[test]
+fn dispatches_indirectly() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let program = ComputeProgram::from_source(
+        gl,
+        SQUARES,
+        &Defines::new().with_value("OFFSET", "0u"),
+        None,
+    )
+    .expect("Failed to build compute program");
+
+    let values = Buffer::<u32>::with_len(gl, 32, 0, None).unwrap();
+    // Only the second command covers the first 16 values
+    let commands = Buffer::from_data(
+        gl,
+        &[
+            DispatchIndirectCommand::new([4, 1, 1]),
+            DispatchIndirectCommand::new([2, 1, 1]),
+        ],
+        0,
+        None,
+    )
+    .unwrap();
+    unsafe {
+        gl.BindBufferBase(gl::SHADER_STORAGE_BUFFER, 0, values.id());
+        program.dispatch_indirect(&commands, 1);
+        program.memory_barrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+    }
+
+    let read = values.read();
+    assert_eq!(read[15], 225);
+    assert!(read[16..].iter().all(|&value| value == 0));
+    assert_eq!(unsafe { gl.GetError() }, gl::NO_ERROR);
+}
@@ -0,0 +1,176 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+mod common;
+
+use std::{fs::File, io::BufWriter, path::PathBuf};
+
+use gl_playground::{
+    camera::Camera,
+    compute::ComputeProgram,
+    cubemap::{CubeFace, Cubemap, CubemapError},
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    skybox::Skybox,
+    storage_buffer::StorageBuffer,
+    texture::ColorSpace,
+};
+use glam::{Vec3, Vec4};
+use image::{codecs::hdr::HdrEncoder, DynamicImage, Rgb, Rgb32FImage, RgbImage};
+
+/// Samples the cubemap along +X, -X, +Y, -Y, +Z and -Z
+const SAMPLE_AXES: &str = "#version 450 core
+layout (local_size_x = 6) in;
+layout (binding = 0) uniform samplerCube uCubemap;
+layout (std430, binding = 0) buffer Output { vec4 texels[6]; };
+const vec3 AXES[6] = vec3[](
+    vec3(1.0, 0.0, 0.0), vec3(-1.0, 0.0, 0.0),
+    vec3(0.0, 1.0, 0.0), vec3(0.0, -1.0, 0.0),
+    vec3(0.0, 0.0, 1.0), vec3(0.0, 0.0, -1.0)
+);
+void main()
+{
+    uint i = gl_LocalInvocationID.x;
+    texels[i] = textureLod(uCubemap, AXES[i], 0.0);
+}
+";
+
+const FACE_COLORS: [[u8; 3]; 6] = [
+    [255, 0, 0],
+    [0, 255, 255],
+    [0, 255, 0],
+    [255, 0, 255],
+    [0, 0, 255],
+    [255, 255, 0],
+];
+
+fn faces(size: u32) -> [DynamicImage; 6] {
+    FACE_COLORS.map(|color| DynamicImage::ImageRgb8(RgbImage::from_pixel(size, size, Rgb(color))))
+}
+
+fn sample_axes(gl: &gl::Gl, cubemap: &Cubemap) -> Vec<Vec4> {
+    let program = ComputeProgram::from_source(gl, SAMPLE_AXES, &Defines::new(), None)
+        .expect("Failed to build compute program");
+    let output = StorageBuffer::<Vec4>::with_len(gl, 6, 0, None).unwrap();
+    unsafe {
+        cubemap.bind(0);
+        output.bind();
+        program.dispatch([1, 1, 1]);
+    }
+    output.read()
+}
+
+#[test]
+fn loads_six_faces() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let cubemap = Cubemap::from_images(gl, &faces(8), ColorSpace::Linear, Some("Faces")).unwrap();
+    assert_eq!(cubemap.size(), 8);
+    assert_eq!(cubemap.levels(), 4);
+    assert_eq!(cubemap.internal_format(), gl::RGB8);
+
+    let texels = sample_axes(gl, &cubemap);
+    for (texel, color) in texels.iter().zip(FACE_COLORS) {
+        let expected = Vec3::from(color.map(|channel| channel as f32 / 255.0)).extend(1.0);
+        assert_eq!(*texel, expected);
+    }
+
+    let srgb = Cubemap::from_images(gl, &faces(1), ColorSpace::Srgb, None).unwrap();
+    assert_eq!(srgb.internal_format(), gl::SRGB8);
+}
+
+#[test]
+fn rejects_mismatched_faces() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let mut wrong_size = faces(4);
+    wrong_size[3] = DynamicImage::new_rgb8(4, 2);
+    assert!(matches!(
+        Cubemap::from_images(gl, &wrong_size, ColorSpace::Linear, None),
+        Err(CubemapError::FaceSize {
+            face: CubeFace::NegativeY,
+            size: (4, 2),
+            expected: 4,
+        })
+    ));
+
+    let mut wrong_format = faces(4);
+    wrong_format[5] = DynamicImage::new_rgba8(4, 4);
+    assert!(matches!(
+        Cubemap::from_images(gl, &wrong_format, ColorSpace::Linear, None),
+        Err(CubemapError::FaceFormat(CubeFace::NegativeZ))
+    ));
+
+    let missing = ["tests/assets/missing.png"; 6];
+    assert!(matches!(
+        Cubemap::from_files(gl, &missing, ColorSpace::Linear, None),
+        Err(CubemapError::TextureError(_))
+    ));
+}
+
+#[test]
+fn converts_equirect_hdr_on_the_gpu() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    // Bright sky over a dim ground, green goes from 0 to 1 around the horizon
+    let (width, height) = (64, 32);
+    let equirect = Rgb32FImage::from_fn(width, height, |x, y| {
+        let sky = if y < height / 2 { 4.0 } else { 0.25 };
+        Rgb([sky, (x as f32 + 0.5) / width as f32, 0.0])
+    });
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("equirect.hdr");
+    let pixels: Vec<_> = equirect.pixels().copied().collect();
+    HdrEncoder::new(BufWriter::new(File::create(&path).unwrap()))
+        .encode(&pixels, width as usize, height as usize)
+        .unwrap();
+
+    let cubemap = Cubemap::from_equirect_file(gl, &path, 16, Some("Sky")).unwrap();
+    assert_eq!(cubemap.internal_format(), gl::RGBA16F);
+    assert_eq!(cubemap.levels(), 5);
+
+    let texels = sample_axes(gl, &cubemap);
+    let close = |actual: f32, expected: f32| (actual - expected).abs() < 0.05;
+    // Up and down
+    assert!(close(texels[2].x, 4.0), "{:?}", texels[2]);
+    assert!(close(texels[3].x, 0.25), "{:?}", texels[3]);
+    // The center of the image faces +X, a quarter turn left of it is -Z
+    assert!(close(texels[0].y, 0.5), "{:?}", texels[0]);
+    assert!(close(texels[5].y, 0.25), "{:?}", texels[5]);
+    assert!(close(texels[4].y, 0.75), "{:?}", texels[4]);
+    assert!(texels.iter().all(|texel| texel.w == 1.0));
+}
+
+#[test]
+fn skybox_ignores_camera_translation() {
+    let mut camera = Camera::default();
+    camera.set_direction(Vec3::new(1.0, 0.5, -1.0));
+    let at_origin = Skybox::view_proj(&camera);
+    camera.set_position(Vec3::new(10.0, -3.0, 7.0));
+
+    assert!(Skybox::view_proj(&camera).abs_diff_eq(at_origin, 1e-5));
+    assert!(!camera.proj_view_matrix().abs_diff_eq(at_origin, 1e-5));
+}
+
+#[test]
+fn draws_skybox_behind_the_scene() {
+    let context = HeadlessContext::new((320, 180)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let mut camera = Camera::default();
+    camera.set_position(Vec3::new(0.6, 0.4, 1.5));
+    camera.set_direction(Vec3::new(-0.4, -0.3, -1.0));
+    common::draw_cube(gl, &camera, ColorSpace::Linear);
+
+    let cubemap = Cubemap::from_images(gl, &faces(8), ColorSpace::Linear, None).unwrap();
+    let skybox = Skybox::new(gl).expect("Failed to create skybox");
+    unsafe {
+        skybox.draw(&camera, &cubemap);
+        gl.Finish();
+    }
+
+    let frame = common::read_framebuffer(&context);
+    common::assert_golden("skybox", &frame, 2);
+}
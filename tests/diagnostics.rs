@@ -0,0 +1,191 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::path::PathBuf;
+
+use gl_playground::{
+    diagnostics::{self, CompileLog, Severity, SourceLines},
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    program::{Program, ProgramError},
+};
+
+const SOURCE: &str = "#version 450 core
+void main()
+{
+\tgl_Position = vec4(nope);
+}
+";
+
+#[test]
+fn parses_mesa_logs() {
+    let diagnostics = diagnostics::parse_log(
+        "0:4(21): error: `nope' undeclared\n1:2(1): warning: unused variable\n",
+    );
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].source_string, 0);
+    assert_eq!(diagnostics[0].line, Some(4));
+    assert_eq!(diagnostics[0].column, Some(21));
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, "`nope' undeclared");
+    assert_eq!(diagnostics[1].source_string, 1);
+    assert_eq!(diagnostics[1].severity, Severity::Warning);
+}
+
+#[test]
+fn parses_mesa_preprocessor_logs() {
+    let diagnostics = diagnostics::parse_log("0:4(1): preprocessor error: #endif without #if\n\n");
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].source_string, 0);
+    assert_eq!(diagnostics[0].line, Some(4));
+    assert_eq!(diagnostics[0].column, Some(1));
+    assert_eq!(diagnostics[0].severity, Severity::Error);
+    assert_eq!(diagnostics[0].message, "#endif without #if");
+}
+
+#[test]
+fn parses_nvidia_logs() {
+    let diagnostics = diagnostics::parse_log(
+        "0(4) : error C1008: undefined variable \"nope\"\n2(10) : warning C7050: \"x\" might be used before being initialized\n",
+    );
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].line, Some(4));
+    assert_eq!(diagnostics[0].column, None);
+    assert_eq!(diagnostics[0].message, "undefined variable \"nope\"");
+    assert_eq!(diagnostics[1].source_string, 2);
+    assert_eq!(diagnostics[1].line, Some(10));
+    assert_eq!(diagnostics[1].severity, Severity::Warning);
+}
+
+#[test]
+fn parses_amd_logs() {
+    let diagnostics = diagnostics::parse_log(
+        "ERROR: 0:4: 'nope' : undeclared identifier \nERROR: 1 compilation errors.  No code generated.\n\n",
+    );
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].line, Some(4));
+    assert_eq!(diagnostics[0].message, "'nope' : undeclared identifier");
+    assert_eq!(diagnostics[1].line, None);
+    assert_eq!(
+        diagnostics[1].message,
+        "1 compilation errors.  No code generated."
+    );
+}
+
+#[test]
+fn keeps_unknown_lines() {
+    let diagnostics =
+        diagnostics::parse_log("Something went wrong\n0:1(1): error: a\n  more about a\n");
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].line, None);
+    assert_eq!(diagnostics[0].message, "Something went wrong");
+    assert_eq!(diagnostics[1].message, "a\nmore about a");
+}
+
+#[test]
+fn renders_like_rustc() {
+    let log = CompileLog::new(
+        "0:4(21): error: `nope' undeclared\n".to_owned(),
+        SOURCE,
+        &[PathBuf::from("basic.vert")],
+        "<vertex shader>",
+    );
+
+    assert_eq!(
+        log.to_string(),
+        "error: `nope' undeclared
+ --> basic.vert:4:21
+  |
+4 |     gl_Position = vec4(nope);
+  |                        ^"
+    );
+
+    // Without a column the whole line is underlined
+    let log = CompileLog::new(
+        "ERROR: 0:3: '{' : syntax error\n".to_owned(),
+        SOURCE,
+        &[],
+        "<vertex shader>",
+    );
+    assert!(log
+        .to_string()
+        .ends_with(" --> <vertex shader>:3\n  |\n3 | {\n  | ^"));
+}
+
+#[test]
+fn maps_lines_through_line_directives() {
+    let source =
+        "#version 450 core\n#define A\n#line 2\nfirst\n#line 1 1\nincluded\n#line 3 0\nsecond\n";
+    let lines = SourceLines::new(source);
+
+    assert_eq!(lines.get(0, 2), Some("first"));
+    assert_eq!(lines.get(1, 1), Some("included"));
+    assert_eq!(lines.get(0, 3), Some("second"));
+}
+
+#[test]
+fn reports_compile_errors() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let result = Program::from_source(
+        context.gl(),
+        SOURCE,
+        "#version 450 core\nvoid main() {}\n",
+        &Defines::new(),
+        Some("Broken"),
+    );
+
+    let Err(ProgramError::ShaderCompilationError(log)) = result else {
+        panic!("Expected a compilation error");
+    };
+    let error = log.errors().next().unwrap();
+    assert_eq!(error.line, Some(4));
+    assert!(error.column.is_some());
+    assert!(error.file.is_none());
+    assert!(log.rendered().contains("--> Broken - vertex shader:4:"));
+    assert!(log.rendered().contains("4 |     gl_Position = vec4(nope);"));
+}
+
+#[test]
+fn reports_preprocessor_errors() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let result = Program::from_source(
+        context.gl(),
+        "#version 450 core\n#define SCALE 1.0\n#define SCALE 2.0\nvoid main() {}\n",
+        "#version 450 core\nvoid main() {}\n",
+        &Defines::new(),
+        Some("Redefined"),
+    );
+
+    let Err(ProgramError::ShaderCompilationError(log)) = result else {
+        panic!("Expected a compilation error");
+    };
+    let error = log.errors().next().unwrap();
+    assert_eq!(error.line, Some(3));
+    assert!(log.rendered().contains("--> Redefined - vertex shader:3:"));
+}
+
+#[test]
+fn reports_link_errors() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let result = Program::from_source(
+        context.gl(),
+        "#version 450 core
+layout (location = 0) out vec3 value;
+void main() { value = vec3(1.0); gl_Position = vec4(0.0); }
+",
+        "#version 450 core
+layout (location = 0) in vec4 value;
+layout (location = 0) out vec4 fColor;
+void main() { fColor = value; }
+",
+        &Defines::new(),
+        None,
+    );
+
+    assert!(matches!(result, Err(ProgramError::ProgramLinkageError(..))));
+}
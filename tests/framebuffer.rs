@@ -0,0 +1,149 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    framebuffer::{AttachmentDesc, Framebuffer, FramebufferError, FramebufferStatus},
+    headless::HeadlessContext,
+    readback,
+};
+use image::Rgba;
+
+#[test]
+fn renders_to_texture() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let framebuffer = Framebuffer::new(
+        gl,
+        (16, 8),
+        &[AttachmentDesc::texture(gl::RGBA8)],
+        Some(AttachmentDesc::renderbuffer(gl::DEPTH24_STENCIL8)),
+        Some("Offscreen"),
+    )
+    .expect("Failed to create framebuffer");
+
+    unsafe {
+        framebuffer.bind();
+        gl.ClearColor(0.0, 0.0, 1.0, 1.0);
+        gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        framebuffer.unbind();
+    }
+
+    let texture = framebuffer
+        .color_texture(0)
+        .expect("Color attachment isn't a texture");
+    assert_eq!(texture.image_size(), (16, 8));
+    assert!(framebuffer.depth_stencil_attachment().is_some());
+
+    let image = readback::read_texture(gl, texture, 0);
+    assert!(image.pixels().all(|p| *p == Rgba([0, 0, 255, 255])));
+    assert_eq!(unsafe { gl.GetError() }, gl::NO_ERROR);
+}
+
+#[test]
+fn multiple_color_attachments() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let framebuffer = Framebuffer::new(
+        gl,
+        (4, 4),
+        &[
+            AttachmentDesc::texture(gl::RGBA8),
+            AttachmentDesc::renderbuffer(gl::RGBA8),
+        ],
+        None,
+        None,
+    )
+    .expect("Failed to create framebuffer");
+
+    assert!(framebuffer.color_texture(0).is_some());
+    assert!(framebuffer.color_attachment(1).is_some());
+    assert!(framebuffer.color_texture(1).is_none());
+    assert!(framebuffer.color_attachment(2).is_none());
+}
+
+#[test]
+fn resizes_attachments() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let mut framebuffer = Framebuffer::new(
+        gl,
+        (8, 8),
+        &[AttachmentDesc::texture(gl::RGBA8)],
+        Some(AttachmentDesc::texture(gl::DEPTH24_STENCIL8)),
+        None,
+    )
+    .expect("Failed to create framebuffer");
+
+    framebuffer.resize((32, 16)).expect("Failed to resize");
+    assert_eq!(framebuffer.size(), (32, 16));
+    assert_eq!(framebuffer.color_texture(0).unwrap().image_size(), (32, 16));
+    assert_eq!(
+        framebuffer
+            .depth_stencil_attachment()
+            .and_then(|a| a.texture())
+            .unwrap()
+            .image_size(),
+        (32, 16)
+    );
+}
+
+#[test]
+fn blits_into_headless_framebuffer() {
+    let context = HeadlessContext::new((8, 8)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let framebuffer = Framebuffer::new(
+        gl,
+        (2, 2),
+        &[AttachmentDesc::renderbuffer(gl::RGBA8)],
+        None,
+        None,
+    )
+    .expect("Failed to create framebuffer");
+
+    unsafe {
+        framebuffer.bind();
+        gl.ClearColor(0.0, 1.0, 0.0, 1.0);
+        gl.Clear(gl::COLOR_BUFFER_BIT);
+    }
+
+    framebuffer.blit_to(
+        context.framebuffer(),
+        (0, 0, 8, 8),
+        gl::COLOR_BUFFER_BIT,
+        gl::NEAREST,
+    );
+
+    let image = readback::read_framebuffer(
+        gl,
+        context.framebuffer(),
+        gl::COLOR_ATTACHMENT0,
+        (0, 0, 8, 8),
+    );
+    assert!(image.pixels().all(|p| *p == Rgba([0, 255, 0, 255])));
+}
+
+#[test]
+fn reports_incomplete_framebuffer() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    // A depth format can't be used as a color attachment
+    let result = Framebuffer::new(
+        gl,
+        (4, 4),
+        &[AttachmentDesc::renderbuffer(gl::DEPTH_COMPONENT24)],
+        None,
+        None,
+    );
+
+    match result {
+        Err(FramebufferError::Incomplete(status)) => {
+            assert_eq!(status, FramebufferStatus::IncompleteAttachment)
+        }
+        _ => panic!("Expected an incomplete framebuffer"),
+    }
+}
@@ -0,0 +1,238 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use base64::Engine;
+use gl_playground::{
+    gltf::{self, GltfError, GltfModel},
+    headless::HeadlessContext,
+    readback,
+};
+use glam::{vec3, Mat4};
+
+fn check_model(context: &HeadlessContext, model: &GltfModel) {
+    assert_eq!(model.roots, [0, 3]);
+    assert_eq!(model.nodes.len(), 4);
+    assert_eq!(model.nodes[0].name.as_deref(), Some("Root"));
+    assert_eq!(model.nodes[0].children, [1, 2]);
+
+    let quad_node = &model.nodes[1];
+    assert_eq!(quad_node.mesh, Some(0));
+    assert_eq!(quad_node.transform, Mat4::from_scale(vec3(2.0, 2.0, 2.0)));
+    assert_eq!(
+        quad_node.world_transform,
+        Mat4::from_translation(vec3(0.0, 0.0, -2.0)) * Mat4::from_scale(vec3(2.0, 2.0, 2.0))
+    );
+    assert_eq!(
+        model.nodes[2].world_transform,
+        Mat4::from_translation(vec3(1.0, 0.0, -2.0))
+    );
+
+    let quad = &model.meshes[0];
+    assert_eq!(quad.name.as_deref(), Some("Quad"));
+    assert_eq!(quad.primitives.len(), 1);
+    let primitive = &quad.primitives[0];
+    assert_eq!(primitive.mesh.index_count(), 6);
+    assert_eq!(primitive.mesh.index_type(), ::gl::UNSIGNED_SHORT);
+    let vertices = primitive.mesh.vertex_buffer().read();
+    assert_eq!(vertices.len(), 4);
+    assert_eq!(vertices[0].position, [-1.0, -1.0, 0.0]);
+    assert_eq!(vertices[0].uv, [0.0, 1.0]);
+    assert_eq!(vertices[0].normal, [0.0, 0.0, 1.0]);
+
+    // No normals in the file, so they're flat
+    let triangle = &model.meshes[1].primitives[0];
+    assert_eq!(triangle.material, None);
+    assert_eq!(triangle.mesh.index_count(), 3);
+    assert!(triangle
+        .mesh
+        .vertex_buffer()
+        .read()
+        .iter()
+        .all(|vertex| vertex.normal == [0.0, 0.0, 1.0]));
+
+    let material = model.material(primitive).unwrap();
+    assert_eq!(material.name.as_deref(), Some("Checker"));
+    assert_eq!(material.base_color_factor, [1.0, 0.5, 0.5, 1.0]);
+    assert_eq!(material.metallic_factor, 0.25);
+    assert_eq!(material.roughness_factor, 0.75);
+    assert_eq!(material.metallic_roughness_texture, None);
+    assert_eq!(material.normal_scale, 0.5);
+
+    let base_color = model.texture(material.base_color_texture).unwrap();
+    assert_eq!(base_color.image_size(), (2, 2));
+    let pixels = readback::read_texture(context.gl(), base_color, 0);
+    // Uploaded top row first, so read_texture's flip puts the top row at the bottom
+    assert_eq!(pixels.get_pixel(0, 1).0, [255, 0, 0, 255]);
+    assert_eq!(pixels.get_pixel(1, 0).0, [255, 255, 255, 255]);
+    let normal = model.texture(material.normal_texture).unwrap();
+    assert_eq!(normal.color_channels(), 3);
+
+    assert_eq!(model.cameras.len(), 1);
+    assert_eq!(model.nodes[3].camera, Some(0));
+    let camera = &model.cameras[0];
+    assert_eq!(camera.position(), vec3(0.0, 1.0, 3.0));
+    assert!(camera.front().abs_diff_eq(vec3(-1.0, 0.0, 0.0), 1e-5));
+    assert!((camera.fov() - 0.8f32.to_degrees()).abs() < 1e-4);
+    assert_eq!(camera.aspect_ratio(), 1.5);
+    assert_eq!(camera.clip_planes(), (0.5, 50.0));
+}
+
+#[test]
+fn loads_embedded_gltf() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let model = gltf::load_gltf(context.gl(), "tests/assets/scene_embedded.gltf", None)
+        .expect("Failed to load glTF");
+
+    check_model(&context, &model);
+}
+
+#[test]
+fn loads_gltf_with_external_files() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let model = gltf::load_gltf(context.gl(), "tests/assets/scene/scene.gltf", Some("Scene"))
+        .expect("Failed to load glTF");
+
+    check_model(&context, &model);
+    assert_eq!(model.textures[0].label(), Some("Scene"));
+}
+
+#[test]
+fn loads_glb() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let model =
+        gltf::load_gltf(context.gl(), "tests/assets/scene.glb", None).expect("Failed to load GLB");
+
+    check_model(&context, &model);
+}
+
+#[test]
+fn reports_missing_files() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let source = br#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{ "byteLength": 4, "uri": "missing.bin" }]
+    }"#;
+
+    assert!(matches!(
+        gltf::load_gltf_from_memory(context.gl(), source, "tests/assets".as_ref(), None),
+        Err(GltfError::Io { path, .. }) if path.ends_with("missing.bin")
+    ));
+}
+
+#[test]
+fn rejects_views_past_their_buffer() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    // The buffer holds 4 bytes, the image's view asks for 8
+    let source = br#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{ "byteLength": 4, "uri": "data:application/octet-stream;base64,AAAAAA==" }],
+        "bufferViews": [{ "buffer": 0, "byteOffset": 2, "byteLength": 8 }],
+        "images": [{ "bufferView": 0, "mimeType": "image/png" }]
+    }"#;
+
+    assert!(matches!(
+        gltf::load_gltf_from_memory(context.gl(), source, "tests/assets".as_ref(), None),
+        Err(GltfError::ViewOutOfRange(0))
+    ));
+}
+
+#[test]
+fn rejects_node_cycles() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    // 0 is the root, 1 and 2 are each other's children
+    let source = br#"{
+        "asset": { "version": "2.0" },
+        "nodes": [{ "children": [1] }, { "children": [2] }, { "children": [1] }]
+    }"#;
+
+    assert!(matches!(
+        gltf::load_gltf_from_memory(context.gl(), source, "tests/assets".as_ref(), None),
+        Err(GltfError::NodeCycle(1))
+    ));
+
+    // Without a root every node is a child, the cycle is never entered
+    let source = br#"{
+        "asset": { "version": "2.0" },
+        "nodes": [{ "children": [1] }, { "children": [0] }]
+    }"#;
+    assert!(matches!(
+        gltf::load_gltf_from_memory(context.gl(), source, "tests/assets".as_ref(), None),
+        Err(GltfError::NodeCycle(0))
+    ));
+}
+
+#[test]
+fn rejects_short_attributes() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    // 3 positions but only 2 UVs, in 52 zeroed bytes
+    let source = br#"{
+        "asset": { "version": "2.0" },
+        "buffers": [{
+            "byteLength": 52,
+            "uri": "data:application/octet-stream;base64,AAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAAA=="
+        }],
+        "bufferViews": [
+            { "buffer": 0, "byteOffset": 0, "byteLength": 36 },
+            { "buffer": 0, "byteOffset": 36, "byteLength": 16 }
+        ],
+        "accessors": [
+            { "bufferView": 0, "componentType": 5126, "count": 3, "type": "VEC3",
+              "min": [0, 0, 0], "max": [0, 0, 0] },
+            { "bufferView": 1, "componentType": 5126, "count": 2, "type": "VEC2" }
+        ],
+        "meshes": [{ "primitives": [{ "attributes": { "POSITION": 0, "TEXCOORD_0": 1 } }] }]
+    }"#;
+
+    assert!(matches!(
+        gltf::load_gltf_from_memory(context.gl(), source, "tests/assets".as_ref(), None),
+        Err(GltfError::AttributeCount {
+            mesh: 0,
+            attribute: "TEXCOORD_0",
+            count: 2,
+            expected: 3,
+        })
+    ));
+}
+
+#[test]
+fn loads_color_textures_as_srgb() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let mut png = Vec::new();
+    image::DynamicImage::new_rgb8(1, 1)
+        .write_to(
+            &mut std::io::Cursor::new(&mut png),
+            image::ImageOutputFormat::Png,
+        )
+        .unwrap();
+    let uri = format!(
+        "data:image/png;base64,{}",
+        base64::engine::general_purpose::STANDARD.encode(png)
+    );
+    // Base color, emissive and normal map, in that order
+    let source = format!(
+        r#"{{
+            "asset": {{ "version": "2.0" }},
+            "images": [{{ "uri": "{uri}" }}, {{ "uri": "{uri}" }}, {{ "uri": "{uri}" }}],
+            "textures": [{{ "source": 0 }}, {{ "source": 1 }}, {{ "source": 2 }}],
+            "materials": [{{
+                "pbrMetallicRoughness": {{ "baseColorTexture": {{ "index": 0 }} }},
+                "emissiveTexture": {{ "index": 1 }},
+                "normalTexture": {{ "index": 2 }}
+            }}]
+        }}"#
+    );
+
+    let model = gltf::load_gltf_from_memory(
+        context.gl(),
+        source.as_bytes(),
+        "tests/assets".as_ref(),
+        None,
+    )
+    .expect("Failed to load glTF");
+    let formats: Vec<_> = model
+        .textures
+        .iter()
+        .map(|texture| texture.internal_format())
+        .collect();
+    assert_eq!(formats, [gl::SRGB8, gl::SRGB8, gl::RGB8]);
+}
@@ -0,0 +1,68 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+mod common;
+
+use gl_playground::{
+    camera::Camera,
+    framebuffer::{AttachmentDesc, Framebuffer},
+    headless::HeadlessContext,
+    readback,
+    texture::ColorSpace,
+};
+
+const TOLERANCE: u8 = 2;
+
+#[test]
+fn textured_cube() {
+    let context = HeadlessContext::new((320, 180)).expect("Failed to create headless context");
+
+    let mut camera = Camera::default();
+    camera.set_position(glam::vec3(0.0, 0.0, 1.0));
+    common::draw_cube(context.gl(), &camera, ColorSpace::Linear);
+
+    let frame = common::read_framebuffer(&context);
+    common::assert_golden("textured_cube", &frame, TOLERANCE);
+}
+
+#[test]
+fn textured_cube_off_center() {
+    let context = HeadlessContext::new((320, 180)).expect("Failed to create headless context");
+
+    let mut camera = Camera::default();
+    camera.set_position(glam::vec3(0.6, 0.4, 1.5));
+    common::draw_cube(context.gl(), &camera, ColorSpace::Linear);
+
+    let frame = common::read_framebuffer(&context);
+    common::assert_golden("textured_cube_off_center", &frame, TOLERANCE);
+}
+
+#[test]
+fn textured_cube_srgb() {
+    let size = (320, 180);
+    let context = HeadlessContext::new(size).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    // Like the binary: the texture is decoded when sampled and the output encoded
+    let framebuffer = Framebuffer::new(
+        gl,
+        size,
+        &[AttachmentDesc::renderbuffer(gl::SRGB8_ALPHA8)],
+        Some(AttachmentDesc::renderbuffer(gl::DEPTH24_STENCIL8)),
+        Some("sRGB"),
+    )
+    .expect("Failed to create framebuffer");
+    unsafe { framebuffer.bind() };
+
+    let mut camera = Camera::default();
+    camera.set_position(glam::vec3(0.0, 0.0, 1.0));
+    common::draw_cube(gl, &camera, ColorSpace::Srgb);
+
+    let frame = readback::read_framebuffer(
+        gl,
+        framebuffer.id(),
+        gl::COLOR_ATTACHMENT0,
+        (0, 0, size.0, size.1),
+    );
+    common::assert_golden("textured_cube_srgb", &frame, TOLERANCE);
+}
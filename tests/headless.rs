@@ -0,0 +1,38 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+mod common;
+
+use gl_playground::{camera::Camera, headless::HeadlessContext, texture::ColorSpace};
+
+#[test]
+fn creates_context_and_framebuffer() {
+    let context = HeadlessContext::new((64, 32)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    assert_eq!(context.size(), (64, 32));
+    assert_ne!(context.framebuffer(), 0);
+
+    unsafe {
+        let mut bound = 0;
+        gl.GetIntegerv(gl::DRAW_FRAMEBUFFER_BINDING, &mut bound);
+        assert_eq!(bound as u32, context.framebuffer());
+
+        gl.ClearColor(0.2, 0.2, 0.2, 1.0);
+        gl.Clear(gl::COLOR_BUFFER_BIT | gl::DEPTH_BUFFER_BIT);
+        gl.Finish();
+
+        assert_eq!(gl.GetError(), gl::NO_ERROR);
+    }
+}
+
+#[test]
+fn draws_textured_cube() {
+    let context = HeadlessContext::new((128, 72)).expect("Failed to create headless context");
+
+    let mut camera = Camera::default();
+    camera.set_position(glam::vec3(0.0, 0.0, 1.0));
+    common::draw_cube(context.gl(), &camera, ColorSpace::Linear);
+
+    assert_eq!(unsafe { context.gl().GetError() }, gl::NO_ERROR);
+}
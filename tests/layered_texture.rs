@@ -0,0 +1,204 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::path::PathBuf;
+
+use gl_playground::{
+    compute::ComputeProgram,
+    headless::HeadlessContext,
+    layered_texture::{LayeredTarget, LayeredTexture, LayeredTextureError},
+    preprocessor::Defines,
+    storage_buffer::StorageBuffer,
+    texture::{ColorSpace, PixelFormat, TextureError},
+};
+use glam::Vec4;
+use image::{DynamicImage, Luma, Rgba, RgbaImage};
+
+/// Fetches texel (0, 0) of every layer at level 0
+const FETCH_LAYERS: &str = "#version 450 core
+layout (local_size_x = LAYERS) in;
+layout (binding = 0) uniform SAMPLER uTexture;
+layout (std430, binding = 0) buffer Output { vec4 texels[LAYERS]; };
+void main()
+{
+    int i = int(gl_LocalInvocationID.x);
+    texels[i] = texelFetch(uTexture, ivec3(0, 0, i), 0);
+}
+";
+
+fn fetch_layers(gl: &gl::Gl, texture: &LayeredTexture) -> Vec<Vec4> {
+    let layers = texture.size().2;
+    let sampler = match texture.target() {
+        LayeredTarget::Array => "sampler2DArray",
+        LayeredTarget::Volume => "sampler3D",
+    };
+    let defines = Defines::new()
+        .with_value("LAYERS", layers)
+        .with_value("SAMPLER", sampler);
+
+    let program = ComputeProgram::from_source(gl, FETCH_LAYERS, &defines, None)
+        .expect("Failed to build compute program");
+    let output = StorageBuffer::<Vec4>::with_len(gl, layers as usize, 0, None).unwrap();
+    unsafe {
+        texture.bind(0);
+        output.bind();
+        program.dispatch([1, 1, 1]);
+    }
+    output.read()
+}
+
+fn layer(color: [u8; 4]) -> DynamicImage {
+    DynamicImage::ImageRgba8(RgbaImage::from_pixel(4, 4, Rgba(color)))
+}
+
+/// Back to 8 bits, the normalized values don't always convert exactly
+fn to_bytes(texel: Vec4) -> [u8; 4] {
+    texel
+        .to_array()
+        .map(|channel| (channel * 255.0).round() as u8)
+}
+
+#[test]
+fn loads_array_layers_from_images() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let colors = [[255, 0, 0, 255], [0, 255, 0, 255], [0, 0, 255, 128]];
+    let images = colors.map(layer);
+    let texture = LayeredTexture::from_images(
+        gl,
+        LayeredTarget::Array,
+        &images,
+        ColorSpace::Linear,
+        Some("Splat layers"),
+    )
+    .unwrap();
+    assert_eq!(texture.size(), (4, 4, 3));
+    // Layers aren't mipmapped together, only their width and height count
+    assert_eq!(texture.levels(), 3);
+    assert_eq!(texture.format().internal_format, gl::RGBA8);
+
+    let texels = fetch_layers(gl, &texture);
+    assert_eq!(texels.into_iter().map(to_bytes).collect::<Vec<_>>(), colors);
+
+    texture.set_layer(1, &[7; 4 * 4 * 4]).unwrap();
+    texture
+        .set_layer_image(2, &layer([1, 2, 3, 4]), ColorSpace::Linear)
+        .unwrap();
+    let texels = fetch_layers(gl, &texture);
+    assert_eq!(to_bytes(texels[0]), colors[0]);
+    assert_eq!(to_bytes(texels[1]), [7; 4]);
+    assert_eq!(to_bytes(texels[2]), [1, 2, 3, 4]);
+}
+
+#[test]
+fn loads_volume_from_a_raw_file() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    // 2 x 2 x 8 R8 voxels, every slice filled with its index
+    let data: Vec<u8> = (0..8).flat_map(|slice| [slice * 16; 4]).collect();
+    let path = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("volume.raw");
+    std::fs::write(&path, &data).unwrap();
+
+    let format = PixelFormat::of(&DynamicImage::new_luma8(1, 1), ColorSpace::Linear).unwrap();
+    let texture = LayeredTexture::from_raw_file(gl, &path, (2, 2, 8), format, None).unwrap();
+    assert_eq!(texture.target(), LayeredTarget::Volume);
+    // The depth is mipmapped too
+    assert_eq!(texture.levels(), 4);
+
+    let texels = fetch_layers(gl, &texture);
+    for (slice, texel) in texels.iter().enumerate() {
+        assert_eq!(to_bytes(*texel)[0], slice as u8 * 16);
+    }
+
+    assert!(matches!(
+        LayeredTexture::from_raw_file(gl, &path, (2, 2, 4), format, None),
+        Err(LayeredTextureError::TextureError(
+            TextureError::WrongSizedData
+        ))
+    ));
+    assert!(matches!(
+        LayeredTexture::from_raw_file(gl, "tests/assets/missing.raw", (2, 2, 8), format, None),
+        Err(LayeredTextureError::Io { .. })
+    ));
+}
+
+#[test]
+fn stacks_slices_into_a_volume() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let slices: Vec<_> = (0..4u8)
+        .map(|slice| DynamicImage::ImageLuma8(image::GrayImage::from_pixel(2, 2, Luma([slice]))))
+        .collect();
+    let texture =
+        LayeredTexture::from_images(gl, LayeredTarget::Volume, &slices, ColorSpace::Linear, None)
+            .unwrap();
+
+    let texels = fetch_layers(gl, &texture);
+    for (slice, texel) in texels.iter().enumerate() {
+        assert_eq!(to_bytes(*texel)[0], slice as u8);
+    }
+}
+
+#[test]
+fn rejects_mismatched_layers() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    assert!(matches!(
+        LayeredTexture::from_images(gl, LayeredTarget::Array, &[], ColorSpace::Linear, None),
+        Err(LayeredTextureError::NoLayers)
+    ));
+
+    let wrong_size = [layer([0; 4]), DynamicImage::new_rgba8(4, 2)];
+    assert!(matches!(
+        LayeredTexture::from_images(
+            gl,
+            LayeredTarget::Array,
+            &wrong_size,
+            ColorSpace::Linear,
+            None
+        ),
+        Err(LayeredTextureError::LayerSize {
+            layer: 1,
+            size: (4, 2),
+            expected: (4, 4),
+        })
+    ));
+
+    let wrong_format = [layer([0; 4]), layer([0; 4]), DynamicImage::new_rgb8(4, 4)];
+    assert!(matches!(
+        LayeredTexture::from_images(
+            gl,
+            LayeredTarget::Array,
+            &wrong_format,
+            ColorSpace::Linear,
+            None
+        ),
+        Err(LayeredTextureError::LayerFormat(2))
+    ));
+
+    let texture = LayeredTexture::from_images(
+        gl,
+        LayeredTarget::Array,
+        &[layer([0; 4])],
+        ColorSpace::Linear,
+        None,
+    )
+    .unwrap();
+    assert!(matches!(
+        texture.set_layer(1, &[0; 4 * 4 * 4]),
+        Err(LayeredTextureError::LayerOutOfRange {
+            layer: 1,
+            layers: 1
+        })
+    ));
+    assert!(matches!(
+        texture.set_layer(0, &[0; 3]),
+        Err(LayeredTextureError::TextureError(
+            TextureError::WrongSizedData
+        ))
+    ));
+}
@@ -0,0 +1,69 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    headless::HeadlessContext,
+    mesh::{Indices, Mesh, MeshData},
+    vertex::{Vertex, CUBE},
+};
+
+#[test]
+fn deduplicates_cube() {
+    let cube = MeshData::from_vertices(&CUBE);
+
+    // 4 corners per face, some shared between faces with the same uv
+    assert!(cube.vertices.len() < CUBE.len());
+    assert_eq!(cube.indices.len(), CUBE.len());
+    assert!(matches!(cube.indices, Indices::U16(..)));
+
+    let expanded = cube.expand();
+    assert_eq!(
+        bytemuck::cast_slice::<_, u8>(&expanded),
+        bytemuck::cast_slice::<_, u8>(&CUBE)
+    );
+}
+
+#[test]
+fn merges_only_identical_vertices() {
+    let a = Vertex::new([0.0, 0.0, 0.0], [0.0, 0.0]);
+    let b = Vertex::new([0.0, 0.0, 0.0], [1.0, 0.0]);
+    let mesh = MeshData::from_vertices(&[a, b, a, b, b]);
+
+    assert_eq!(mesh.vertices.len(), 2);
+    assert_eq!(mesh.indices, Indices::U16(vec![0, 1, 0, 1, 1]));
+}
+
+#[test]
+fn picks_index_type() {
+    assert_eq!(
+        Indices::from_u32(vec![0, 65535]).index_type(),
+        gl::UNSIGNED_SHORT
+    );
+    assert_eq!(
+        Indices::from_u32(vec![0, 65536]).index_type(),
+        gl::UNSIGNED_INT
+    );
+}
+
+#[test]
+fn uploads_mesh() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let data = MeshData::from_vertices(&CUBE);
+    let mesh = Mesh::new(gl, &data, Some("Cube")).expect("Failed to create mesh");
+
+    assert_eq!(mesh.index_count(), 36);
+    assert_eq!(mesh.index_type(), gl::UNSIGNED_SHORT);
+    assert_eq!(mesh.vertex_buffer().len(), data.vertices.len());
+
+    let mut element_buffer = 0;
+    unsafe {
+        gl.GetVertexArrayiv(
+            mesh.vertex_array().id(),
+            gl::ELEMENT_ARRAY_BUFFER_BINDING,
+            &mut element_buffer,
+        );
+    }
+    assert_eq!(element_buffer as u32, mesh.index_buffer_id());
+}
@@ -0,0 +1,131 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::path::Path;
+
+use gl_playground::{
+    headless::HeadlessContext,
+    obj::{self, ObjError, ParseErrorKind},
+};
+
+#[test]
+fn loads_objects_and_materials() {
+    let model = obj::load_obj("tests/assets/quad.obj").expect("Failed to load OBJ");
+
+    assert_eq!(model.materials.len(), 2);
+    assert_eq!(model.meshes.len(), 2);
+
+    let quad = &model.meshes[0];
+    assert_eq!(quad.object, "Quad");
+    // Triangulated into 2 triangles sharing 2 corners
+    assert_eq!(quad.data.indices.len(), 6);
+    assert_eq!(quad.data.vertices.len(), 4);
+    assert_eq!(quad.data.vertices[0].normal, [0.0, 0.0, 1.0]);
+    // Flipped to match how textures are uploaded
+    assert_eq!(quad.data.vertices[0].uv, [0.0, 1.0]);
+
+    let brick = model.material(quad).unwrap();
+    assert_eq!(brick.name, "brick");
+    assert_eq!(brick.diffuse, [0.8, 0.4, 0.3]);
+    assert_eq!(brick.shininess, 32.0);
+    assert_eq!(
+        brick.diffuse_texture.as_deref(),
+        Some(Path::new("tests/assets/../../assets/brick.webp"))
+    );
+
+    let triangle = &model.meshes[1];
+    assert_eq!(triangle.object, "Triangle");
+    assert_eq!(triangle.group, "Front");
+    assert_eq!(triangle.data.vertices[0].position, [0.0, 0.0, 1.0]);
+    // No normals in the file, so a flat one is computed
+    assert_eq!(triangle.data.vertices[0].normal, [0.0, 0.0, 1.0]);
+
+    let glass = model.material(triangle).unwrap();
+    assert_eq!(glass.dissolve, 0.25);
+    assert!(glass.diffuse_texture.is_none());
+}
+
+#[test]
+fn splits_meshes_on_material_change() {
+    let source = "\
+v 0 0 0
+v 1 0 0
+v 0 1 0
+newmtl_is_ignored
+f 1 2 3
+g Second
+f 3 2 1
+f 1 3 2
+";
+    let model = obj::parse_obj(source, Path::new("")).unwrap();
+
+    assert_eq!(model.meshes.len(), 2);
+    assert_eq!(model.meshes[0].group, "");
+    assert_eq!(model.meshes[1].group, "Second");
+    assert_eq!(model.meshes[1].data.indices.len(), 6);
+}
+
+#[test]
+fn reports_errors_with_line_numbers() {
+    let parse_error = |source: &str| match obj::parse_obj(source, Path::new("")) {
+        Err(ObjError::Parse { line, kind, .. }) => (line, kind),
+        other => panic!("Expected a parse error, got {:?}", other),
+    };
+
+    assert_eq!(
+        parse_error("v 0 0 0\nv 1 nope 0\n"),
+        (2, ParseErrorKind::InvalidNumber("nope".to_owned()))
+    );
+    assert_eq!(
+        parse_error("v 0 0 0\nv 1 0 0\n\nf 1 2\n"),
+        (4, ParseErrorKind::MissingValues("f"))
+    );
+    assert_eq!(
+        parse_error("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n"),
+        (4, ParseErrorKind::IndexOutOfRange(4))
+    );
+    assert_eq!(
+        parse_error("v 0 0 0\nf -2 -1 1\n"),
+        (2, ParseErrorKind::IndexOutOfRange(-2))
+    );
+    assert_eq!(
+        parse_error("usemtl missing\n"),
+        (1, ParseErrorKind::UnknownMaterial("missing".to_owned()))
+    );
+}
+
+#[test]
+fn reports_errors_in_mtl_files() {
+    let dir = std::env::temp_dir().join("gl_playground_obj_test");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("broken.mtl"), "newmtl a\nKd 1 1\n").unwrap();
+
+    match obj::parse_obj("mtllib broken.mtl\n", &dir) {
+        Err(ObjError::Parse { file, line, kind }) => {
+            assert_eq!(file, Some(dir.join("broken.mtl")));
+            assert_eq!(line, 2);
+            assert_eq!(kind, ParseErrorKind::MissingValues("Kd"));
+        }
+        other => panic!("Expected a parse error, got {:?}", other),
+    }
+
+    assert!(matches!(
+        obj::load_obj(dir.join("missing.obj")),
+        Err(ObjError::Io { .. })
+    ));
+}
+
+#[test]
+fn loads_diffuse_texture() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let model = obj::load_obj("tests/assets/quad.obj").expect("Failed to load OBJ");
+
+    let texture = model.materials[0]
+        .load_diffuse_texture(context.gl(), Some("Brick"))
+        .expect("Material has a diffuse texture")
+        .expect("Failed to load texture");
+    assert_eq!(texture.label(), Some("Brick"));
+    assert!(model.materials[1]
+        .load_diffuse_texture(context.gl(), None)
+        .is_none());
+}
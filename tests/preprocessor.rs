@@ -0,0 +1,111 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::path::{Path, PathBuf};
+
+use gl_playground::{
+    headless::HeadlessContext,
+    preprocessor::{Defines, PreprocessError, Preprocessor},
+    program::{Program, ProgramError},
+};
+
+const ROOT: &str = "tests/assets/shaders";
+const VERTEX: &str = "#version 450 core
+void main() { gl_Position = vec4(0.0); }
+";
+
+#[test]
+fn resolves_includes_with_line_directives() {
+    let shader = Preprocessor::new(ROOT).load("lit.frag").unwrap();
+
+    assert_eq!(
+        shader.files,
+        [
+            Path::new(ROOT).join("lit.frag"),
+            Path::new(ROOT).join("common/lighting.glsl"),
+            Path::new(ROOT).join("common/constants.glsl"),
+        ]
+    );
+    assert_eq!(
+        shader.file(1),
+        Some(Path::new(ROOT).join("common/lighting.glsl").as_path())
+    );
+    assert_eq!(shader.file(3), None);
+
+    let lines: Vec<&str> = shader.source.lines().collect();
+    assert_eq!(lines[0], "#version 450 core");
+    assert_eq!(lines[2], "#line 1 1");
+    assert_eq!(lines[4], "#line 1 2");
+    // Back in lighting.glsl after constants.glsl
+    assert!(lines.contains(&"#line 3 1"));
+    // Back in lit.frag after each #include, the second one skipped by #pragma once
+    assert!(lines.contains(&"#line 4 0"));
+    assert!(lines.contains(&"#line 5 0"));
+    assert_eq!(shader.source.matches("float lambert").count(), 1);
+}
+
+#[test]
+fn detects_include_cycles() {
+    match Preprocessor::new(ROOT).load("cycle_a.glsl") {
+        Err(PreprocessError::IncludeCycle(cycle)) => {
+            let names: Vec<_> = cycle.iter().map(|path| path.file_name().unwrap()).collect();
+            assert_eq!(names, ["cycle_a.glsl", "cycle_b.glsl", "cycle_a.glsl"]);
+        }
+        other => panic!("Expected an include cycle, got {:?}", other),
+    }
+}
+
+#[test]
+fn reports_bad_includes() {
+    let preprocessor = Preprocessor::new(ROOT);
+
+    assert!(matches!(
+        preprocessor.process("#version 450 core\n#include missing.glsl\n", "inline.frag"),
+        Err(PreprocessError::MalformedInclude { line: 2, .. })
+    ));
+    match preprocessor.process("\n\n#include \"missing.glsl\"\n", "inline.frag") {
+        Err(PreprocessError::IncludeNotFound {
+            file, line, path, ..
+        }) => {
+            assert_eq!(file, PathBuf::from("inline.frag"));
+            assert_eq!(line, 3);
+            assert_eq!(path, Path::new(ROOT).join("missing.glsl"));
+        }
+        other => panic!("Expected a missing include, got {:?}", other),
+    }
+}
+
+#[test]
+fn compiles_included_shaders() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let preprocessor = Preprocessor::new(ROOT);
+    let vertex = preprocessor.process(VERTEX, "inline.vert").unwrap();
+    let fragment = preprocessor.load("lit.frag").unwrap();
+
+    Program::from_preprocessed(context.gl(), &vertex, &fragment, &Defines::new(), None)
+        .expect("Failed to build program");
+}
+
+#[test]
+fn errors_point_to_included_file() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let preprocessor = Preprocessor::new(ROOT);
+    let vertex = preprocessor.process(VERTEX, "inline.vert").unwrap();
+    let fragment = preprocessor.load("broken.frag").unwrap();
+
+    let result =
+        Program::from_preprocessed(context.gl(), &vertex, &fragment, &Defines::new(), None);
+    match result {
+        Err(ProgramError::ShaderCompilationError(log)) => {
+            // Mesa reports some errors with source string 0 even inside includes
+            let error = log.errors().find(|error| error.source_string == 1).unwrap();
+            assert_eq!(error.line, Some(3));
+            assert_eq!(
+                error.file.as_deref(),
+                Some(Path::new("tests/assets/shaders/common/broken.glsl"))
+            );
+        }
+        Err(e) => panic!("Expected a compilation error, got {}", e),
+        Ok(_) => panic!("Broken shader compiled"),
+    }
+}
@@ -0,0 +1,121 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+mod common;
+
+use gl_playground::{
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    program::{Program, ProgramError},
+    vertex_array::VertexArray,
+};
+use image::Rgba;
+
+// A triangle covering the whole viewport, generated from gl_VertexID
+const VERTEX: &str = "#version 450 core
+void main()
+{
+    vec2 position = vec2((gl_VertexID & 1) * 4 - 1, (gl_VertexID >> 1) * 4 - 1);
+    gl_Position = vec4(position, 0.0, 1.0);
+}
+";
+
+const TESS_CONTROL: &str = "#version 450 core
+layout (vertices = 3) out;
+void main()
+{
+    gl_out[gl_InvocationID].gl_Position = gl_in[gl_InvocationID].gl_Position;
+    gl_TessLevelOuter[0] = 2.0;
+    gl_TessLevelOuter[1] = 2.0;
+    gl_TessLevelOuter[2] = 2.0;
+    gl_TessLevelInner[0] = 2.0;
+}
+";
+
+const TESS_EVALUATION: &str = "#version 450 core
+layout (triangles) in;
+void main()
+{
+    gl_Position = gl_TessCoord.x * gl_in[0].gl_Position
+        + gl_TessCoord.y * gl_in[1].gl_Position
+        + gl_TessCoord.z * gl_in[2].gl_Position;
+}
+";
+
+const GEOMETRY: &str = "#version 450 core
+layout (triangles) in;
+layout (triangle_strip, max_vertices = 3) out;
+layout (location = 0) out vec4 gColor;
+void main()
+{
+    for (int i = 0; i < 3; i++) {
+        gl_Position = gl_in[i].gl_Position;
+        gColor = COLOR;
+        EmitVertex();
+    }
+    EndPrimitive();
+}
+";
+
+const FRAGMENT: &str = "#version 450 core
+layout (location = 0) in vec4 gColor;
+layout (location = 0) out vec4 fColor;
+void main() { fColor = gColor; }
+";
+
+#[test]
+fn builds_every_graphics_stage() {
+    let context = HeadlessContext::new((4, 4)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let program = Program::builder(gl)
+        .vertex(VERTEX)
+        .tess_control(TESS_CONTROL)
+        .tess_evaluation(TESS_EVALUATION)
+        .geometry(GEOMETRY)
+        .fragment(FRAGMENT)
+        .defines(Defines::new().with_value("COLOR", "vec4(0.0, 1.0, 0.0, 1.0)"))
+        .label("All stages")
+        .build()
+        .expect("Failed to build program");
+    assert_eq!(program.label(), Some("All stages"));
+
+    let vertex_array = VertexArray::new(gl, None);
+    unsafe {
+        gl.ClearColor(0.0, 0.0, 0.0, 1.0);
+        gl.Clear(gl::COLOR_BUFFER_BIT);
+        program.bind();
+        vertex_array.bind();
+        gl.PatchParameteri(gl::PATCH_VERTICES, 3);
+        gl.DrawArrays(gl::PATCHES, 0, 3);
+        gl.Finish();
+    }
+
+    let image = common::read_framebuffer(&context);
+    assert!(image.pixels().all(|p| *p == Rgba([0, 255, 0, 255])));
+    assert_eq!(unsafe { gl.GetError() }, gl::NO_ERROR);
+}
+
+#[test]
+fn builds_vertex_and_geometry_only() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+
+    // A later stage replaces an earlier one of the same type
+    Program::builder(context.gl())
+        .vertex("#version 450 core\nvoid main() { nope; }\n")
+        .vertex(VERTEX)
+        .geometry(GEOMETRY)
+        .defines(Defines::new().with_value("COLOR", "vec4(1.0)"))
+        .build()
+        .expect("Failed to build program");
+}
+
+#[test]
+fn rejects_empty_programs() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+
+    assert!(matches!(
+        Program::builder(context.gl()).build(),
+        Err(ProgramError::NoStages)
+    ));
+}
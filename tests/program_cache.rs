@@ -0,0 +1,131 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{fs, path::PathBuf};
+
+use gl_playground::{
+    headless::HeadlessContext,
+    preprocessor::{Defines, ShaderSource},
+    program::Program,
+    program_cache::ProgramCache,
+};
+
+const VERTEX: &str = "#version 450 core
+layout (location = 0) in vec3 aPosition;
+void main()
+{
+    gl_Position = vec4(aPosition, 1.0);
+}
+";
+
+const FRAGMENT: &str = "#version 450 core
+out vec4 fColor;
+uniform vec4 uColor;
+void main()
+{
+#ifdef RED
+    fColor = vec4(1.0, 0.0, 0.0, 1.0);
+#else
+    fColor = uColor;
+#endif
+}
+";
+
+fn cache_dir(name: &str) -> PathBuf {
+    let dir = PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join(name);
+    let _ = fs::remove_dir_all(&dir);
+    dir
+}
+
+fn key(cache: &ProgramCache, defines: &Defines) -> u64 {
+    let vertex = ShaderSource::from(VERTEX);
+    let fragment = ShaderSource::from(FRAGMENT);
+    cache.key(
+        &[
+            (gl::VERTEX_SHADER, &vertex),
+            (gl::FRAGMENT_SHADER, &fragment),
+        ],
+        defines,
+    )
+}
+
+#[test]
+fn stores_and_loads_programs() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let cache = ProgramCache::new(gl, cache_dir("program_cache_roundtrip"));
+    // llvmpipe has program binary formats, a skip here would hide everything below
+    assert!(
+        cache.is_enabled(),
+        "The test driver has no program binary formats"
+    );
+
+    let key = key(&cache, &Defines::new());
+    assert!(cache.load(gl, key, None).is_none());
+
+    let built = Program::builder(gl)
+        .vertex(VERTEX)
+        .fragment(FRAGMENT)
+        .cache(&cache)
+        .build()
+        .expect("Failed to build program");
+    assert!(cache.path(key).is_file());
+    drop(built);
+
+    let loaded = cache.load(gl, key, Some("Cached")).expect("Cache miss");
+    assert_eq!(loaded.label(), Some("Cached"));
+    assert!(loaded.reflect().uniform("uColor").is_some());
+    loaded
+        .try_set("uColor", glam::Vec4::ONE)
+        .expect("Loaded program should have its uniforms");
+
+    cache.clear().unwrap();
+    assert!(!cache.dir().exists());
+}
+
+#[test]
+fn rejected_binaries_fall_back_to_compiling() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let cache = ProgramCache::new(gl, cache_dir("program_cache_rejected"));
+    // llvmpipe has program binary formats, a skip here would hide everything below
+    assert!(
+        cache.is_enabled(),
+        "The test driver has no program binary formats"
+    );
+
+    let key = key(&cache, &Defines::new());
+    fs::create_dir_all(cache.dir()).unwrap();
+    fs::write(cache.path(key), b"not a program").unwrap();
+    assert!(cache.load(gl, key, None).is_none());
+
+    // A well-formed entry the driver doesn't understand
+    let mut garbage = b"GLPB".to_vec();
+    garbage.extend_from_slice(&0u32.to_le_bytes());
+    garbage.extend_from_slice(&[0xAB; 64]);
+    fs::write(cache.path(key), &garbage).unwrap();
+    assert!(cache.load(gl, key, None).is_none());
+
+    Program::builder(gl)
+        .vertex(VERTEX)
+        .fragment(FRAGMENT)
+        .cache(&cache)
+        .build()
+        .expect("Failed to build program");
+    assert_ne!(fs::read(cache.path(key)).unwrap(), garbage);
+    assert!(cache.load(gl, key, None).is_some());
+}
+
+#[test]
+fn keys_depend_on_defines() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let cache = ProgramCache::new(context.gl(), cache_dir("program_cache_keys"));
+
+    let plain = key(&cache, &Defines::new());
+    assert_eq!(plain, key(&cache, &Defines::new()));
+    assert_ne!(plain, key(&cache, &Defines::new().with("RED")));
+    assert_ne!(
+        key(&cache, &Defines::new().with_value("RED", 1)),
+        key(&cache, &Defines::new().with_value("RED", 2))
+    );
+}
@@ -0,0 +1,79 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    program::{ProgramError, ProgramVariants},
+};
+
+const VERTEX: &str = "#version 450 core
+void main() { gl_Position = vec4(0.0); }
+";
+
+const FRAGMENT: &str = "#version 450 core
+layout (location = 0) out vec4 fColor;
+#ifdef HAS_TINT
+layout (location = 3) uniform vec4 uTint;
+#endif
+void main()
+{
+#ifdef HAS_TINT
+    fColor = uTint * SCALE;
+#else
+    fColor = vec4(1.0);
+#endif
+}
+";
+
+#[test]
+fn inserts_defines_after_version() {
+    let defines = Defines::new().with("B").with_value("A", 2);
+    let source = defines.apply("// Comment\n#version 450 core\nvoid main() {}\n");
+
+    assert_eq!(
+        source,
+        "// Comment\n#version 450 core\n#define A 2\n#define B\n#line 3\nvoid main() {}\n"
+    );
+    assert_eq!(Defines::new().apply(VERTEX), VERTEX);
+    assert!(Defines::new()
+        .with("A")
+        .apply("void main() {}\n")
+        .ends_with("#line 1\nvoid main() {}\n"));
+}
+
+#[test]
+fn defines_are_order_independent() {
+    let a = Defines::new().with("INSTANCED").with("ALPHA_TEST");
+    let b: Defines = ["ALPHA_TEST", "INSTANCED"].into_iter().collect();
+
+    assert_eq!(a, b);
+    assert!(a.contains("INSTANCED"));
+    assert_eq!(a.len(), 2);
+}
+
+#[test]
+fn caches_variants() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let mut variants = ProgramVariants::from_source(context.gl(), VERTEX, FRAGMENT, Some("Tint"));
+
+    let plain = variants.get(&Defines::new()).unwrap().id();
+    let tinted_defines = Defines::new().with("HAS_TINT").with_value("SCALE", 0.5);
+    let tinted = variants.get(&tinted_defines).unwrap();
+    assert_ne!(tinted.id(), plain);
+    assert_eq!(tinted.label(), Some("Tint"));
+    assert_eq!(unsafe { tinted.get_unifrom("uTint") }, Some(3));
+    let tinted = tinted.id();
+
+    let same_defines = Defines::new().with_value("SCALE", 0.5).with("HAS_TINT");
+    assert_eq!(variants.get(&same_defines).unwrap().id(), tinted);
+    assert_eq!(variants.get(&Defines::new()).unwrap().id(), plain);
+    assert_eq!(variants.len(), 2);
+
+    // SCALE is missing
+    assert!(matches!(
+        variants.get(&Defines::new().with("HAS_TINT")),
+        Err(ProgramError::ShaderCompilationError(..))
+    ));
+    assert_eq!(variants.len(), 2);
+}
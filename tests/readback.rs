@@ -0,0 +1,84 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    headless::HeadlessContext,
+    readback,
+    texture::{ColorSpace, Texture},
+};
+use image::{DynamicImage, Rgba, RgbaImage};
+
+#[test]
+fn reads_cleared_framebuffer_region() {
+    let context = HeadlessContext::new((32, 16)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    unsafe {
+        gl.ClearColor(1.0, 0.0, 0.0, 1.0);
+        gl.Clear(gl::COLOR_BUFFER_BIT);
+
+        // Paint the bottom-left 4x2 pixels green
+        gl.Enable(gl::SCISSOR_TEST);
+        gl.Scissor(0, 0, 4, 2);
+        gl.ClearColor(0.0, 1.0, 0.0, 1.0);
+        gl.Clear(gl::COLOR_BUFFER_BIT);
+        gl.Disable(gl::SCISSOR_TEST);
+    }
+
+    let image = readback::read_framebuffer(
+        gl,
+        context.framebuffer(),
+        gl::COLOR_ATTACHMENT0,
+        (0, 0, 32, 16),
+    );
+    assert_eq!(image.dimensions(), (32, 16));
+    assert_eq!(*image.get_pixel(0, 0), Rgba([255, 0, 0, 255]));
+    // Flipped, so the bottom rows come last
+    assert_eq!(*image.get_pixel(0, 15), Rgba([0, 255, 0, 255]));
+    assert_eq!(*image.get_pixel(3, 14), Rgba([0, 255, 0, 255]));
+    assert_eq!(*image.get_pixel(4, 15), Rgba([255, 0, 0, 255]));
+
+    let region = readback::read_framebuffer(
+        gl,
+        context.framebuffer(),
+        gl::COLOR_ATTACHMENT0,
+        (2, 0, 4, 4),
+    );
+    assert_eq!(region.dimensions(), (4, 4));
+    assert_eq!(*region.get_pixel(1, 3), Rgba([0, 255, 0, 255]));
+    assert_eq!(*region.get_pixel(2, 3), Rgba([255, 0, 0, 255]));
+}
+
+#[test]
+fn reads_texture() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let source = image::open("assets/brick.webp")
+        .expect("Failed to open image")
+        .to_rgba8();
+    let texture = Texture::from_file(gl, "assets/brick.webp", ColorSpace::Linear, None)
+        .expect("Failed to load texture");
+
+    let image = readback::read_texture(gl, &texture, 0);
+    assert_eq!(image, image::imageops::flip_vertical(&source));
+
+    // Each 2 x 2 quadrant has one color, so level 1 has exactly those colors
+    let colors = [
+        [255, 0, 0, 255],
+        [0, 255, 0, 255],
+        [0, 0, 255, 255],
+        [255; 4],
+    ];
+    let quadrants = RgbaImage::from_fn(4, 4, |x, y| Rgba(colors[(y / 2 * 2 + x / 2) as usize]));
+    let texture = Texture::from_image(
+        gl,
+        &DynamicImage::ImageRgba8(quadrants),
+        ColorSpace::Linear,
+        None,
+    )
+    .unwrap();
+    let level = readback::read_texture(gl, &texture, 1);
+    let expected = RgbaImage::from_fn(2, 2, |x, y| Rgba(colors[(y * 2 + x) as usize]));
+    assert_eq!(level, image::imageops::flip_vertical(&expected));
+}
@@ -0,0 +1,170 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    compute::ComputeProgram,
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    program::Program,
+    reflection::{type_name, ProgramInterface},
+};
+
+const VERTEX: &str = "#version 450 core
+layout (location = 0) in vec3 aPosition;
+layout (location = 2) in vec2 aTexCoord;
+
+layout (std140, binding = 1) uniform Camera {
+    mat4 view;
+    mat4 proj;
+    vec3 position;
+};
+
+layout (location = 3) uniform mat4 uModel;
+
+out vec2 vTexCoord;
+
+void main()
+{
+    vTexCoord = aTexCoord + position.xy;
+    gl_Position = proj * view * uModel * vec4(aPosition, 1.0);
+}
+";
+
+const FRAGMENT: &str = "#version 450 core
+in vec2 vTexCoord;
+
+layout (location = 0) out vec4 fColor;
+layout (location = 1) out vec4 fBright;
+
+uniform sampler2D uTexture;
+uniform float uWeights[4];
+
+void main()
+{
+    float weight = uWeights[0] + uWeights[1] + uWeights[2] + uWeights[3];
+    fColor = texture(uTexture, vTexCoord) * weight;
+    fBright = vec4(weight);
+}
+";
+
+const COMPUTE: &str = "#version 450 core
+layout (local_size_x = 1) in;
+
+struct Light {
+    vec3 color;
+    float intensity;
+};
+
+layout (std430, binding = 2) buffer Lights {
+    uint count;
+    Light lights[];
+};
+
+void main()
+{
+    lights[count].intensity = 1.0;
+}
+";
+
+fn reflect(gl: &gl::Gl) -> ProgramInterface {
+    Program::from_source(gl, VERTEX, FRAGMENT, &Defines::new(), Some("Reflected"))
+        .expect("Failed to build program")
+        .reflect()
+}
+
+#[test]
+fn lists_vertex_inputs_and_fragment_outputs() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let interface = reflect(context.gl());
+
+    let position = interface.input("aPosition").unwrap();
+    assert_eq!(position.gl_type, gl::FLOAT_VEC3);
+    assert_eq!(position.location, Some(0));
+    let tex_coord = interface.input("aTexCoord").unwrap();
+    assert_eq!(type_name(tex_coord.gl_type), "vec2");
+    assert_eq!(tex_coord.location, Some(2));
+
+    assert_eq!(interface.output("fColor").unwrap().location, Some(0));
+    assert_eq!(interface.output("fBright").unwrap().location, Some(1));
+    assert!(interface.output("vTexCoord").is_none());
+}
+
+#[test]
+fn lists_default_block_uniforms() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let interface = reflect(context.gl());
+
+    let model = interface.uniform("uModel").unwrap();
+    assert_eq!(model.gl_type, gl::FLOAT_MAT4);
+    assert_eq!(model.location, Some(3));
+    assert_eq!(model.block_index, None);
+    assert_eq!(model.offset, None);
+
+    let texture = interface.uniform("uTexture").unwrap();
+    assert_eq!(type_name(texture.gl_type), "sampler2D");
+    assert!(texture.location.is_some());
+
+    let weights = interface.uniform("uWeights[0]").unwrap();
+    assert_eq!(weights.gl_type, gl::FLOAT);
+    assert_eq!(weights.array_size, 4);
+    assert_eq!(interface.uniform("uWeights"), Some(weights));
+    assert_eq!(interface.uniform("uWeights[3]"), Some(weights));
+
+    assert!(interface.uniform("uTypo").is_none());
+}
+
+#[test]
+fn lists_uniform_blocks_with_std140_offsets() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let interface = reflect(context.gl());
+
+    let camera = interface.uniform_block("Camera").unwrap();
+    assert_eq!(camera.binding, 1);
+    assert_eq!(camera.data_size, 144);
+
+    let offsets: Vec<_> = camera
+        .members
+        .iter()
+        .map(|member| (member.name.as_str(), member.offset))
+        .collect();
+    assert_eq!(offsets, [("view", 0), ("proj", 64), ("position", 128)]);
+    assert_eq!(camera.member("view").unwrap().matrix_stride, 16);
+    assert_eq!(camera.member("view").unwrap().top_level_array_stride, None);
+
+    // Block members are uniforms too, without a location
+    let proj = interface.uniform("proj").unwrap();
+    assert_eq!(proj.location, None);
+    assert_eq!(
+        interface.uniform_blocks[proj.block_index.unwrap()].name,
+        "Camera"
+    );
+    assert_eq!(proj.offset, Some(64));
+}
+
+#[test]
+fn lists_storage_blocks_with_std430_offsets() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let program = ComputeProgram::from_source(context.gl(), COMPUTE, &Defines::new(), None)
+        .expect("Failed to build compute program");
+    let interface = program.program().reflect();
+
+    let lights = interface.storage_block("Lights").unwrap();
+    assert_eq!(lights.binding, 2);
+
+    let count = lights.member("count").unwrap();
+    assert_eq!(count.gl_type, gl::UNSIGNED_INT);
+    assert_eq!(count.offset, 0);
+
+    let intensity = lights.member("lights[0].intensity").unwrap();
+    assert_eq!(intensity.gl_type, gl::FLOAT);
+    assert_eq!(intensity.offset, 28);
+    assert_eq!(intensity.top_level_array_stride, Some(16));
+    // Runtime-sized
+    assert_eq!(intensity.top_level_array_size, Some(0));
+    assert_eq!(count.top_level_array_size, Some(1));
+    let color = lights.member("lights[0].color").unwrap();
+    assert_eq!(color.offset, 16);
+
+    assert!(interface.uniform_blocks.is_empty());
+    assert!(interface.outputs.is_empty());
+}
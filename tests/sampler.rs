@@ -0,0 +1,230 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    compute::ComputeProgram,
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    sampler::{Compare, Filter, Sampler, SamplerDesc, Wrap},
+    storage_buffer::StorageBuffer,
+    texture::{mip_levels, PixelFormat, Texture},
+};
+use glam::Vec4;
+
+const RGBA8: PixelFormat = PixelFormat {
+    internal_format: gl::RGBA8,
+    format: gl::RGBA,
+    data_type: gl::UNSIGNED_BYTE,
+    channels: 4,
+};
+
+/// Writes `texture(uTexture, uCoords)` to `Output`
+const SAMPLE: &str = "#version 450 core
+layout (local_size_x = 1) in;
+layout (binding = 0) uniform sampler2D uTexture;
+layout (location = 0) uniform vec2 uCoords;
+layout (std430, binding = 0) buffer Output { vec4 texel; };
+void main()
+{
+    texel = textureLod(uTexture, uCoords, 0.0);
+}
+";
+
+fn texture_parameter(gl: &gl::Gl, texture: &Texture, name: u32) -> i32 {
+    let mut value = 0;
+    unsafe { gl.GetTextureParameteriv(texture.id(), name, &mut value) };
+    value
+}
+
+#[test]
+fn textures_get_a_full_mip_chain() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    assert_eq!(mip_levels((1, 1)), 1);
+    assert_eq!(mip_levels((4, 2)), 3);
+    assert_eq!(mip_levels((640, 480)), 10);
+
+    let texture = Texture::from_raw(gl, (4, 2), RGBA8, &[255; 32], None).unwrap();
+    assert_eq!(texture.levels(), 3);
+    assert_eq!(
+        texture_parameter(gl, &texture, gl::TEXTURE_IMMUTABLE_LEVELS),
+        3
+    );
+    assert_eq!(
+        texture_parameter(gl, &texture, gl::TEXTURE_MIN_FILTER) as u32,
+        gl::LINEAR_MIPMAP_LINEAR
+    );
+
+    // The generated levels hold data
+    let mut texel = [0u8; 4];
+    unsafe {
+        gl.GetTextureImage(
+            texture.id(),
+            2,
+            gl::RGBA,
+            gl::UNSIGNED_BYTE,
+            4,
+            texel.as_mut_ptr().cast(),
+        )
+    };
+    assert_eq!(texel, [255; 4]);
+}
+
+#[test]
+fn applies_every_parameter() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let texture = Texture::from_raw(gl, (1, 1), RGBA8, &[0; 4], None).unwrap();
+
+    let desc = SamplerDesc {
+        wrap: [Wrap::MirroredRepeat, Wrap::ClampToBorder, Wrap::ClampToEdge],
+        min_filter: Filter::Nearest,
+        mag_filter: Filter::Nearest,
+        mip_filter: Some(Filter::Nearest),
+        max_anisotropy: 1000.0,
+        lod_bias: 0.5,
+        min_lod: 1.0,
+        max_lod: 4.0,
+        border_color: [0.25, 0.5, 0.75, 1.0],
+        compare: Some(Compare::Greater),
+    };
+    texture.set_sampler(&desc);
+
+    let parameter = |name| texture_parameter(gl, &texture, name) as u32;
+    assert_eq!(parameter(gl::TEXTURE_WRAP_S), gl::MIRRORED_REPEAT);
+    assert_eq!(parameter(gl::TEXTURE_WRAP_T), gl::CLAMP_TO_BORDER);
+    assert_eq!(parameter(gl::TEXTURE_WRAP_R), gl::CLAMP_TO_EDGE);
+    assert_eq!(
+        parameter(gl::TEXTURE_MIN_FILTER),
+        gl::NEAREST_MIPMAP_NEAREST
+    );
+    assert_eq!(parameter(gl::TEXTURE_MAG_FILTER), gl::NEAREST);
+    assert_eq!(
+        parameter(gl::TEXTURE_COMPARE_MODE),
+        gl::COMPARE_REF_TO_TEXTURE
+    );
+    assert_eq!(parameter(gl::TEXTURE_COMPARE_FUNC), gl::GREATER);
+
+    let float = |name| {
+        let mut value = [0.0; 4];
+        unsafe { gl.GetTextureParameterfv(texture.id(), name, value.as_mut_ptr()) };
+        value
+    };
+    assert_eq!(float(gl::TEXTURE_LOD_BIAS)[0], 0.5);
+    assert_eq!(float(gl::TEXTURE_MIN_LOD)[0], 1.0);
+    assert_eq!(float(gl::TEXTURE_MAX_LOD)[0], 4.0);
+    assert_eq!(float(gl::TEXTURE_BORDER_COLOR), [0.25, 0.5, 0.75, 1.0]);
+
+    let mut max_anisotropy = 0.0;
+    unsafe { gl.GetFloatv(gl::MAX_TEXTURE_MAX_ANISOTROPY, &mut max_anisotropy) };
+    assert_eq!(
+        float(gl::TEXTURE_MAX_ANISOTROPY)[0],
+        max_anisotropy.max(1.0)
+    );
+
+    texture.set_sampler(&SamplerDesc::clamped());
+    assert_eq!(parameter(gl::TEXTURE_MIN_FILTER), gl::LINEAR);
+    assert_eq!(parameter(gl::TEXTURE_COMPARE_MODE), gl::NONE);
+    assert_eq!(float(gl::TEXTURE_MAX_ANISOTROPY)[0], 1.0);
+    assert_eq!(unsafe { gl.GetError() }, gl::NO_ERROR);
+}
+
+#[test]
+fn samplers_override_the_texture_state() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = ComputeProgram::from_source(gl, SAMPLE, &Defines::new(), None)
+        .expect("Failed to build compute program");
+    let output = StorageBuffer::<Vec4>::with_len(gl, 1, 0, None).unwrap();
+
+    // Black on the left, white on the right
+    let texture =
+        Texture::from_raw(gl, (2, 1), RGBA8, &[0, 0, 0, 255, 255, 255, 255, 255], None).unwrap();
+    texture.set_sampler(&SamplerDesc::nearest().with_wrap(Wrap::Repeat));
+
+    let sample = |u: f32| {
+        unsafe {
+            texture.bind(0);
+            output.bind();
+            program.program().bind();
+            program.program().set("uCoords", glam::Vec2::new(u, 0.5));
+            program.dispatch([1, 1, 1]);
+        }
+        output.get(0).unwrap()
+    };
+    // Past the right edge, repeated back to black
+    assert_eq!(sample(1.25), Vec4::new(0.0, 0.0, 0.0, 1.0));
+
+    let mut sampler = Sampler::new(
+        gl,
+        &SamplerDesc::nearest().with_wrap(Wrap::ClampToEdge),
+        Some("Clamped"),
+    );
+    assert_eq!(sampler.label(), Some("Clamped"));
+    unsafe { sampler.bind(0) };
+    assert_eq!(sample(1.25), Vec4::ONE);
+
+    sampler.set(&SamplerDesc {
+        border_color: [1.0, 0.0, 0.0, 1.0],
+        ..SamplerDesc::nearest().with_wrap(Wrap::ClampToBorder)
+    });
+    assert_eq!(sampler.desc().wrap, [Wrap::ClampToBorder; 3]);
+    assert_eq!(sample(1.25), Vec4::new(1.0, 0.0, 0.0, 1.0));
+
+    unsafe { sampler.unbind(0) };
+    assert_eq!(sample(1.25), Vec4::new(0.0, 0.0, 0.0, 1.0));
+}
+
+#[test]
+fn compares_depth_for_shadow_maps() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = ComputeProgram::from_source(
+        gl,
+        "#version 450 core
+layout (local_size_x = 1) in;
+layout (binding = 0) uniform sampler2DShadow uShadowMap;
+layout (location = 0) uniform float uDepth;
+layout (std430, binding = 0) buffer Output { vec4 lit; };
+void main()
+{
+    lit = vec4(texture(uShadowMap, vec3(0.5, 0.5, uDepth)));
+}
+",
+        &Defines::new(),
+        None,
+    )
+    .expect("Failed to build compute program");
+    let output = StorageBuffer::<Vec4>::with_len(gl, 1, 0, None).unwrap();
+
+    let shadow_map = Texture::with_storage(gl, (1, 1), gl::DEPTH_COMPONENT32F, None).unwrap();
+    unsafe {
+        gl.TextureSubImage2D(
+            shadow_map.id(),
+            0,
+            0,
+            0,
+            1,
+            1,
+            gl::DEPTH_COMPONENT,
+            gl::FLOAT,
+            [0.5f32].as_ptr().cast(),
+        );
+    }
+    let sampler = Sampler::new(gl, &SamplerDesc::shadow(), None);
+
+    let lit = |depth: f32| {
+        unsafe {
+            shadow_map.bind(0);
+            sampler.bind(0);
+            output.bind();
+            program.program().bind();
+            program.program().set("uDepth", depth);
+            program.dispatch([1, 1, 1]);
+        }
+        output.get(0).unwrap().x
+    };
+    assert_eq!(lit(0.25), 1.0);
+    assert_eq!(lit(0.75), 0.0);
+}
@@ -0,0 +1,158 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    compute::ComputeProgram,
+    headless::HeadlessContext,
+    program::{Program, ProgramError},
+    spirv::{SpirvError, SpirvShader},
+    storage_buffer::StorageBuffer,
+};
+use rspirv::{
+    binary::Assemble,
+    dr::{Builder, Operand},
+    spirv::{
+        AddressingModel, BuiltIn, Capability, Decoration, ExecutionMode, ExecutionModel,
+        FunctionControl, MemoryModel, StorageClass,
+    },
+};
+
+/// A compute module with two entry points writing to `buffer Output { uint values[]; }`
+/// at binding 0:
+/// - `scaled`: `values[i] = i * SCALE + OFFSET`
+/// - `constant`: `values[i] = SCALE`
+///
+/// with `layout (constant_id = 0) const uint SCALE = 1` and
+/// `layout (constant_id = 1) const uint OFFSET = 0`
+fn module() -> Vec<u32> {
+    let mut b = Builder::new();
+    b.set_version(1, 0);
+    b.capability(Capability::Shader);
+    b.memory_model(AddressingModel::Logical, MemoryModel::GLSL450);
+
+    let void = b.type_void();
+    let main_type = b.type_function(void, []);
+    let uint = b.type_int(32, 0);
+    let uvec3 = b.type_vector(uint, 3);
+    let input_uvec3 = b.type_pointer(None, StorageClass::Input, uvec3);
+    let values = b.type_runtime_array(uint);
+    let output = b.type_struct([values]);
+    let uniform_output = b.type_pointer(None, StorageClass::Uniform, output);
+    let uniform_uint = b.type_pointer(None, StorageClass::Uniform, uint);
+    b.decorate(values, Decoration::ArrayStride, [Operand::LiteralInt32(4)]);
+    b.decorate(output, Decoration::BufferBlock, []);
+    b.member_decorate(output, 0, Decoration::Offset, [Operand::LiteralInt32(0)]);
+
+    let zero = b.constant_u32(uint, 0);
+    let scale = b.spec_constant_u32(uint, 1);
+    let offset = b.spec_constant_u32(uint, 0);
+    b.decorate(scale, Decoration::SpecId, [Operand::LiteralInt32(0)]);
+    b.decorate(offset, Decoration::SpecId, [Operand::LiteralInt32(1)]);
+
+    let invocation = b.variable(input_uvec3, None, StorageClass::Input, None);
+    b.decorate(
+        invocation,
+        Decoration::BuiltIn,
+        [Operand::BuiltIn(BuiltIn::GlobalInvocationId)],
+    );
+    let buffer = b.variable(uniform_output, None, StorageClass::Uniform, None);
+    b.decorate(buffer, Decoration::Binding, [Operand::LiteralInt32(0)]);
+
+    for (name, scaled) in [("scaled", true), ("constant", false)] {
+        let function = b
+            .begin_function(void, None, FunctionControl::NONE, main_type)
+            .unwrap();
+        b.begin_block(None).unwrap();
+        let id = b.load(uvec3, None, invocation, None, []).unwrap();
+        let i = b.composite_extract(uint, None, id, [0]).unwrap();
+        let value = if scaled {
+            let product = b.i_mul(uint, None, i, scale).unwrap();
+            b.i_add(uint, None, product, offset).unwrap()
+        } else {
+            scale
+        };
+        let element = b
+            .access_chain(uniform_uint, None, buffer, [zero, i])
+            .unwrap();
+        b.store(element, value, None, []).unwrap();
+        b.ret().unwrap();
+        b.end_function().unwrap();
+
+        b.entry_point(ExecutionModel::GLCompute, function, name, [invocation]);
+        b.execution_mode(function, ExecutionMode::LocalSize, [1, 1, 1]);
+    }
+
+    b.module().assemble()
+}
+
+fn bytes(words: &[u32]) -> Vec<u8> {
+    words.iter().flat_map(|word| word.to_ne_bytes()).collect()
+}
+
+#[test]
+fn specializes_entry_points_and_constants() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let module = SpirvShader::new(&bytes(&module())).unwrap();
+    let output = StorageBuffer::<u32>::with_len(gl, 4, 0, Some("Output")).unwrap();
+
+    let run = |shader: &SpirvShader| {
+        let program = ComputeProgram::from_spirv(gl, shader, Some("SPIR-V"))
+            .expect("Failed to build SPIR-V program");
+        assert_eq!(program.local_size(), [1, 1, 1]);
+        unsafe {
+            output.bind();
+            program.dispatch([4, 1, 1]);
+        }
+        output.read()
+    };
+
+    assert_eq!(run(&module.clone().entry_point("scaled")), [0, 1, 2, 3]);
+    let specialized = module
+        .clone()
+        .entry_point("scaled")
+        .constant(0, 3u32)
+        .constant(1, 10u32);
+    assert_eq!(run(&specialized), [10, 13, 16, 19]);
+    // Later values replace earlier ones
+    assert_eq!(run(&specialized.constant(1, 1u32)), [1, 4, 7, 10]);
+    assert_eq!(
+        run(&module.clone().entry_point("constant").constant(0, 7u32)),
+        [7; 4]
+    );
+}
+
+#[test]
+fn reports_bad_modules() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let words = module();
+
+    assert!(matches!(
+        SpirvShader::new(&bytes(&words)[..18]),
+        Err(SpirvError::Length(18))
+    ));
+    assert!(matches!(
+        SpirvShader::new(&[0; 20]),
+        Err(SpirvError::Magic(0))
+    ));
+    assert!(matches!(
+        SpirvShader::from_file("tests/assets/shaders/missing.spv"),
+        Err(SpirvError::Io { .. })
+    ));
+
+    // Modules written on the other endianness are swapped on load
+    let swapped: Vec<u32> = words.iter().map(|word| word.swap_bytes()).collect();
+    let shader = SpirvShader::new(&bytes(&swapped)).unwrap();
+    assert_eq!(shader.bytes(), bytes(&words));
+
+    // The module has no `main`
+    assert!(matches!(
+        ComputeProgram::from_spirv(gl, &shader, None),
+        Err(ProgramError::SpecializationError { .. })
+    ));
+    assert!(matches!(
+        Program::from_spirv(gl, &[], None),
+        Err(ProgramError::NoStages)
+    ));
+}
@@ -0,0 +1,225 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    block_layout::Std430,
+    compute::ComputeProgram,
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    storage_buffer::{StorageBuffer, StorageBufferError},
+};
+use glam::{Mat4, Vec3, Vec4};
+
+#[repr(C)]
+#[derive(Clone, Copy, Debug, PartialEq, bytemuck::Pod, bytemuck::Zeroable, Std430)]
+struct Particle {
+    position: Vec3,
+    mass: f32,
+    velocity: Vec3,
+    age: f32,
+}
+
+const STEP: &str = "#version 450 core
+layout (local_size_x = 4) in;
+
+struct Particle {
+    vec3 position;
+    float mass;
+    vec3 velocity;
+    float age;
+};
+
+layout (std430, binding = 3) buffer Particles {
+    Particle particles[];
+};
+
+void main()
+{
+    uint i = gl_GlobalInvocationID.x;
+    if (i < particles.length()) {
+        particles[i].position += particles[i].velocity;
+        particles[i].age += 1.0;
+    }
+}
+";
+
+const INDICES: &str = "#version 450 core
+layout (local_size_x = 1) in;
+layout (std430, binding = 0) buffer Indices { uint indices[]; };
+layout (std430, binding = 1) buffer Header { uint count; vec4 items[]; };
+layout (std430, binding = 2) buffer Settings { vec4 color; uint flags; };
+layout (std430, binding = 4) buffer Palette { vec4 colors[4]; };
+layout (std430, binding = 5) buffer Scene { mat4 transform; vec4 lights[4]; };
+void main()
+{
+    indices[gl_GlobalInvocationID.x] = gl_GlobalInvocationID.x + 1u + flags
+        + count + uint(items[0].x + color.x + colors[3].x + transform[0][0] + lights[3].x);
+}
+";
+
+fn particle(i: usize) -> Particle {
+    Particle {
+        position: Vec3::splat(i as f32),
+        mass: 1.0,
+        velocity: Vec3::new(1.0, 0.0, -1.0),
+        age: 0.0,
+    }
+}
+
+#[test]
+fn compute_output_reads_back() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = ComputeProgram::from_source(gl, STEP, &Defines::new(), Some("Step"))
+        .expect("Failed to build compute program");
+
+    let initial: Vec<_> = (0..10).map(particle).collect();
+    let particles = StorageBuffer::from_data(gl, &initial, 3, Some("Particles")).unwrap();
+    particles
+        .validate(&program.program().reflect(), "Particles")
+        .expect("Particle should match the std430 layout");
+
+    unsafe {
+        particles.bind();
+        program.dispatch(program.groups_for([10, 1, 1]));
+    }
+
+    let stepped = particles.read();
+    assert_eq!(stepped.len(), 10);
+    for (i, particle) in stepped.iter().enumerate() {
+        assert_eq!(
+            particle.position,
+            Vec3::new(i as f32 + 1.0, i as f32, i as f32 - 1.0)
+        );
+        assert_eq!(particle.mass, 1.0);
+        assert_eq!(particle.age, 1.0);
+    }
+    assert_eq!(particles.get(9).unwrap().age, 1.0);
+    assert_eq!(particles.read_range(2..4).unwrap(), &stepped[2..4]);
+    assert!(particles.get(10).is_err());
+}
+
+#[test]
+fn binds_ranges() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = ComputeProgram::from_source(gl, INDICES, &Defines::new(), None)
+        .expect("Failed to build compute program");
+
+    let indices = StorageBuffer::<u32>::with_len(gl, 32, 0, Some("Indices")).unwrap();
+    let header = StorageBuffer::<u32>::with_len(gl, 4, 1, None).unwrap();
+    let settings = StorageBuffer::<Vec4>::with_len(gl, 2, 2, None).unwrap();
+    let palette = StorageBuffer::<Vec4>::with_len(gl, 4, 4, None).unwrap();
+    unsafe {
+        header.bind();
+        settings.bind();
+        palette.bind();
+        indices.bind_range(16..32).unwrap();
+        program.dispatch([8, 1, 1]);
+    }
+
+    let values = indices.read();
+    assert!(values[..16].iter().all(|&value| value == 0));
+    assert_eq!(&values[16..24], &[1, 2, 3, 4, 5, 6, 7, 8]);
+    assert!(values[24..].iter().all(|&value| value == 0));
+
+    assert!(matches!(
+        unsafe { indices.bind_range(30..33) },
+        Err(StorageBufferError::BufferError(_))
+    ));
+    let mut alignment = 0;
+    unsafe { gl.GetIntegerv(gl::SHADER_STORAGE_BUFFER_OFFSET_ALIGNMENT, &mut alignment) };
+    if alignment > 4 {
+        assert!(matches!(
+            unsafe { indices.bind_range(1..2) },
+            Err(StorageBufferError::MisalignedOffset { offset: 4, .. })
+        ));
+    }
+}
+
+#[test]
+fn validates_against_reflection() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = ComputeProgram::from_source(gl, INDICES, &Defines::new(), None)
+        .expect("Failed to build compute program");
+    let interface = program.program().reflect();
+
+    let indices = StorageBuffer::<u32>::with_len(gl, 4, 0, None).unwrap();
+    indices.validate(&interface, "Indices").unwrap();
+    assert!(matches!(
+        indices.validate(&interface, "Indicies"),
+        Err(StorageBufferError::MissingBlock(_))
+    ));
+
+    let wrong_binding = StorageBuffer::<u32>::with_len(gl, 4, 5, None).unwrap();
+    assert!(matches!(
+        wrong_binding.validate(&interface, "Indices"),
+        Err(StorageBufferError::Binding {
+            expected: 0,
+            binding: 5,
+            ..
+        })
+    ));
+
+    let wrong_type = StorageBuffer::<Vec4>::with_len(gl, 4, 0, None).unwrap();
+    assert!(matches!(
+        wrong_type.validate(&interface, "Indices"),
+        Err(StorageBufferError::Layout {
+            expected: 4,
+            size: 16,
+            ..
+        })
+    ));
+
+    let header = StorageBuffer::<Vec4>::with_len(gl, 4, 1, None).unwrap();
+    assert!(matches!(
+        header.validate(&interface, "Header"),
+        Err(StorageBufferError::MixedMembers(_))
+    ));
+
+    // A block without an array is a single element
+    #[repr(C)]
+    #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, Std430)]
+    struct Settings {
+        color: Vec4,
+        flags: u32,
+        _pad: gl_playground::block_layout::Pad<12>,
+    }
+    let settings = StorageBuffer::<Settings>::with_len(gl, 1, 2, None).unwrap();
+    settings.validate(&interface, "Settings").unwrap();
+    let wrong_size = StorageBuffer::<Vec4>::with_len(gl, 1, 2, None).unwrap();
+    assert!(matches!(
+        wrong_size.validate(&interface, "Settings"),
+        Err(StorageBufferError::Layout { what: "size", .. })
+    ));
+
+    // Fixed-size arrays next to other members are part of the single element
+    #[repr(C)]
+    #[derive(Clone, Copy, bytemuck::Pod, bytemuck::Zeroable, Std430)]
+    struct Scene {
+        transform: Mat4,
+        lights: [Vec4; 4],
+    }
+    let scene = StorageBuffer::<Scene>::with_len(gl, 1, 5, None).unwrap();
+    scene.validate(&interface, "Scene").unwrap();
+    let lights_only = StorageBuffer::<Vec4>::with_len(gl, 8, 5, None).unwrap();
+    assert!(matches!(
+        lights_only.validate(&interface, "Scene"),
+        Err(StorageBufferError::Layout {
+            what: "size",
+            expected: 128,
+            ..
+        })
+    ));
+
+    let palette = StorageBuffer::<Vec4>::with_len(gl, 2, 4, None).unwrap();
+    assert!(matches!(
+        palette.validate(&interface, "Palette"),
+        Err(StorageBufferError::TooSmall {
+            expected: 64,
+            size: 32,
+            ..
+        })
+    ));
+}
@@ -0,0 +1,214 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::io::Cursor;
+
+use gl_playground::{
+    compute::ComputeProgram,
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    storage_buffer::StorageBuffer,
+    texture::{ColorSpace, PixelFormat, Texture},
+};
+use glam::Vec4;
+use image::{codecs::hdr::HdrEncoder, DynamicImage, ImageBuffer, ImageOutputFormat};
+
+/// Reads level 0 back in the format it was uploaded with
+fn read_back(gl: &gl::Gl, texture: &Texture, format: PixelFormat) -> Vec<u8> {
+    let (width, height) = texture.image_size();
+    let mut data = vec![0u8; format.texel_size() * width as usize * height as usize];
+    unsafe {
+        gl.PixelStorei(gl::PACK_ALIGNMENT, 1);
+        gl.GetTextureImage(
+            texture.id(),
+            0,
+            format.format,
+            format.data_type,
+            data.len() as i32,
+            data.as_mut_ptr().cast(),
+        );
+        gl.PixelStorei(gl::PACK_ALIGNMENT, 4);
+    }
+    data
+}
+
+fn internal_format(gl: &gl::Gl, texture: &Texture) -> u32 {
+    let mut format = 0;
+    unsafe {
+        gl.GetTextureLevelParameteriv(texture.id(), 0, gl::TEXTURE_INTERNAL_FORMAT, &mut format)
+    };
+    format as u32
+}
+
+#[test]
+fn maps_every_image_type() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    // 3 x 1, so RGB8 and R8 rows aren't 4-byte aligned
+    let images = [
+        (
+            DynamicImage::ImageLuma8(ImageBuffer::from_raw(3, 1, vec![0, 128, 255]).unwrap()),
+            gl::R8,
+        ),
+        (
+            DynamicImage::ImageLumaA8(ImageBuffer::from_raw(3, 1, vec![1, 2, 3, 4, 5, 6]).unwrap()),
+            gl::RG8,
+        ),
+        (
+            DynamicImage::ImageRgb8(ImageBuffer::from_raw(3, 1, (0..9).collect()).unwrap()),
+            gl::RGB8,
+        ),
+        (
+            DynamicImage::ImageRgba8(ImageBuffer::from_raw(3, 1, (0..12).collect()).unwrap()),
+            gl::RGBA8,
+        ),
+        (
+            DynamicImage::ImageLuma16(ImageBuffer::from_raw(3, 1, vec![0, 1000, 65535]).unwrap()),
+            gl::R16,
+        ),
+        (
+            DynamicImage::ImageLumaA16(ImageBuffer::from_raw(3, 1, (0..6).collect()).unwrap()),
+            gl::RG16,
+        ),
+        (
+            DynamicImage::ImageRgb16(ImageBuffer::from_raw(3, 1, (300..309).collect()).unwrap()),
+            gl::RGB16,
+        ),
+        (
+            DynamicImage::ImageRgba16(ImageBuffer::from_raw(3, 1, (0..12).collect()).unwrap()),
+            gl::RGBA16,
+        ),
+        (
+            DynamicImage::ImageRgb32F(
+                ImageBuffer::from_raw(3, 1, (0..9).map(|i| i as f32 * 2.5).collect()).unwrap(),
+            ),
+            gl::RGB32F,
+        ),
+        (
+            DynamicImage::ImageRgba32F(
+                ImageBuffer::from_raw(3, 1, (0..12).map(|i| -(i as f32)).collect()).unwrap(),
+            ),
+            gl::RGBA32F,
+        ),
+    ];
+
+    for (image, expected) in images {
+        let format = PixelFormat::of(&image, ColorSpace::Linear).unwrap();
+        assert_eq!(format.internal_format, expected);
+        assert_eq!(format.channels, image.color().channel_count());
+        assert_eq!(
+            format.texel_size(),
+            image.color().bytes_per_pixel() as usize
+        );
+
+        let texture = Texture::from_image(gl, &image, ColorSpace::Linear, None).unwrap();
+        assert_eq!(texture.internal_format(), expected);
+        assert_eq!(internal_format(gl, &texture), expected);
+        assert_eq!(read_back(gl, &texture, format), image.as_bytes());
+    }
+}
+
+#[test]
+fn srgb_only_applies_to_8_bit_color() {
+    let rgb = DynamicImage::new_rgb8(1, 1);
+    let rgba = DynamicImage::new_rgba8(1, 1);
+    let luma = DynamicImage::new_luma8(1, 1);
+    let rgba16 = DynamicImage::new_rgba16(1, 1);
+
+    let internal = |image: &DynamicImage, color_space| {
+        PixelFormat::of(image, color_space).unwrap().internal_format
+    };
+    assert_eq!(internal(&rgb, ColorSpace::Srgb), gl::SRGB8);
+    assert_eq!(internal(&rgba, ColorSpace::Srgb), gl::SRGB8_ALPHA8);
+    assert_eq!(internal(&rgba, ColorSpace::Linear), gl::RGBA8);
+    assert_eq!(internal(&luma, ColorSpace::Srgb), gl::R8);
+    assert_eq!(internal(&rgba16, ColorSpace::Srgb), gl::RGBA16);
+}
+
+#[test]
+fn srgb_textures_are_decoded_when_sampled() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = ComputeProgram::from_source(
+        gl,
+        "#version 450 core
+layout (local_size_x = 1) in;
+layout (binding = 0) uniform sampler2D uTexture;
+layout (std430, binding = 0) buffer Output { vec4 texel; };
+void main()
+{
+    texel = texelFetch(uTexture, ivec2(0), 0);
+}
+",
+        &Defines::new(),
+        None,
+    )
+    .expect("Failed to build compute program");
+    let output = StorageBuffer::<Vec4>::with_len(gl, 1, 0, None).unwrap();
+
+    let image =
+        DynamicImage::ImageRgba8(ImageBuffer::from_raw(1, 1, vec![128, 255, 0, 128]).unwrap());
+    let sample = |color_space| {
+        let texture = Texture::from_image(gl, &image, color_space, None).unwrap();
+        unsafe {
+            texture.bind(0);
+            output.bind();
+            program.dispatch([1, 1, 1]);
+        }
+        output.get(0).unwrap()
+    };
+
+    let linear = sample(ColorSpace::Linear);
+    assert!((linear.x - 128.0 / 255.0).abs() < 1e-3);
+    let srgb = sample(ColorSpace::Srgb);
+    assert!((srgb.x - 0.2158).abs() < 1e-3, "{srgb}");
+    assert_eq!(srgb.y, 1.0);
+    assert_eq!(srgb.z, 0.0);
+    // Alpha is always linear
+    assert!((srgb.w - 128.0 / 255.0).abs() < 1e-3);
+}
+
+#[test]
+fn loads_16_bit_files() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let heightmap =
+        DynamicImage::ImageLuma16(ImageBuffer::from_raw(2, 2, vec![0, 256, 4096, 65535]).unwrap());
+    let mut png = Vec::new();
+    heightmap
+        .write_to(&mut Cursor::new(&mut png), ImageOutputFormat::Png)
+        .unwrap();
+
+    let texture = Texture::from_memory(gl, &png, ColorSpace::Linear, Some("Heightmap")).unwrap();
+    assert_eq!(texture.internal_format(), gl::R16);
+    assert_eq!(texture.color_channels(), 1);
+    let format = PixelFormat::of(&heightmap, ColorSpace::Linear).unwrap();
+    assert_eq!(read_back(gl, &texture, format), heightmap.as_bytes());
+}
+
+#[test]
+fn keeps_hdr_files_in_floats() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let path = std::path::PathBuf::from(env!("CARGO_TARGET_TMPDIR")).join("bright.hdr");
+    let pixels = [image::Rgb([8.0f32, 0.5, 0.0]); 4];
+    HdrEncoder::new(std::fs::File::create(&path).unwrap())
+        .encode(&pixels, 2, 2)
+        .unwrap();
+
+    let texture = Texture::from_file(gl, &path, ColorSpace::Linear, None).unwrap();
+    assert_eq!(texture.internal_format(), gl::RGB32F);
+    let format = PixelFormat::of(&DynamicImage::new_rgb32f(1, 1), ColorSpace::Linear).unwrap();
+    let texels: Vec<f32> = bytemuck::pod_collect_to_vec(&read_back(gl, &texture, format));
+    assert_eq!(&texels[..3], &[8.0, 0.5, 0.0]);
+
+    // Recognized by its magic, e.g. embedded in a glTF
+    let data = std::fs::read(&path).unwrap();
+    let texture = Texture::from_memory(gl, &data, ColorSpace::Linear, None).unwrap();
+    assert_eq!(texture.internal_format(), gl::RGB32F);
+    let texels: Vec<f32> = bytemuck::pod_collect_to_vec(&read_back(gl, &texture, format));
+    assert_eq!(&texels[..3], &[8.0, 0.5, 0.0]);
+}
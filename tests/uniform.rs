@@ -0,0 +1,149 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    program::Program,
+    uniform::{TextureUnit, UniformError},
+};
+use glam::{Mat4, Quat, Vec3, Vec4};
+
+const VERTEX: &str = "#version 450 core
+layout (location = 0) in vec3 aPosition;
+uniform mat4 uModel;
+uniform vec4 uRotation;
+void main()
+{
+    gl_Position = uModel * vec4(aPosition, 1.0) + uRotation;
+}
+";
+
+const FRAGMENT: &str = "#version 450 core
+out vec4 fColor;
+uniform vec3 uColor;
+uniform vec4 uTint;
+uniform float uWeights[4];
+uniform int uMode;
+uniform uint uFlags;
+uniform bool uEnabled;
+uniform sampler2D uTexture;
+void main()
+{
+    float weight = uWeights[0] + uWeights[1] + uWeights[2] + uWeights[3];
+    fColor = vec4(uColor, 1.0) * uTint * weight * float(uMode) * float(uFlags)
+        * texture(uTexture, vec2(0.5));
+    if (!uEnabled) {
+        discard;
+    }
+}
+";
+
+fn uniform_f32s<const N: usize>(gl: &gl::Gl, program: &Program, name: &str) -> [f32; N] {
+    let location = unsafe { program.get_unifrom(name) }.unwrap();
+    let mut values = [0.0; N];
+    unsafe { gl.GetnUniformfv(program.id(), location, (N * 4) as i32, values.as_mut_ptr()) };
+    values
+}
+
+fn uniform_i32(gl: &gl::Gl, program: &Program, name: &str) -> i32 {
+    let location = unsafe { program.get_unifrom(name) }.unwrap();
+    let mut value = 0;
+    unsafe { gl.GetUniformiv(program.id(), location, &mut value) };
+    value
+}
+
+#[test]
+fn sets_glam_types_without_binding() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = Program::from_source(gl, VERTEX, FRAGMENT, &Defines::new(), Some("Uniforms"))
+        .expect("Failed to build program");
+
+    let model = Mat4::from_translation(Vec3::new(1.0, 2.0, 3.0));
+    let rotation = Quat::from_rotation_z(1.0);
+    program.try_set("uModel", model).unwrap();
+    program.try_set("uRotation", rotation).unwrap();
+    program.try_set("uColor", Vec3::new(0.1, 0.2, 0.3)).unwrap();
+    program.try_set("uTint", Vec4::splat(0.5)).unwrap();
+
+    assert_eq!(uniform_f32s(gl, &program, "uModel"), model.to_cols_array());
+    assert_eq!(uniform_f32s(gl, &program, "uRotation"), rotation.to_array());
+    assert_eq!(uniform_f32s(gl, &program, "uColor"), [0.1, 0.2, 0.3]);
+    assert_eq!(uniform_f32s(gl, &program, "uTint"), [0.5; 4]);
+
+    let mut current = 0;
+    unsafe { gl.GetIntegerv(gl::CURRENT_PROGRAM, &mut current) };
+    assert_eq!(current, 0);
+}
+
+#[test]
+fn sets_scalars_arrays_and_samplers() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = Program::from_source(gl, VERTEX, FRAGMENT, &Defines::new(), None)
+        .expect("Failed to build program");
+
+    program.try_set("uMode", 3).unwrap();
+    program.try_set("uFlags", 7u32).unwrap();
+    program.try_set("uEnabled", true).unwrap();
+    program.try_set("uTexture", TextureUnit(2)).unwrap();
+    assert_eq!(uniform_i32(gl, &program, "uMode"), 3);
+    assert_eq!(uniform_i32(gl, &program, "uFlags"), 7);
+    assert_eq!(uniform_i32(gl, &program, "uEnabled"), 1);
+    assert_eq!(uniform_i32(gl, &program, "uTexture"), 2);
+
+    program.try_set("uWeights", [1.0, 2.0, 3.0, 4.0]).unwrap();
+    assert_eq!(uniform_f32s(gl, &program, "uWeights[2]"), [3.0]);
+    // Starting at an element
+    program.try_set("uWeights[2]", &[5.0, 6.0][..]).unwrap();
+    assert_eq!(uniform_f32s(gl, &program, "uWeights[1]"), [2.0]);
+    assert_eq!(uniform_f32s(gl, &program, "uWeights[3]"), [6.0]);
+}
+
+#[test]
+fn reports_inactive_uniforms() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let program = Program::from_source(context.gl(), VERTEX, FRAGMENT, &Defines::new(), None)
+        .expect("Failed to build program");
+
+    let error = program.try_set("uColour", Vec3::ONE).unwrap_err();
+    assert!(matches!(error, UniformError::NotActive(name) if name == "uColour"));
+    // Cached, still an error the second time
+    assert!(program.try_set("uColour", Vec3::ONE).is_err());
+    program.set("uColour", Vec3::ONE);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn reports_type_mismatches() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+    let program = Program::from_source(gl, VERTEX, FRAGMENT, &Defines::new(), None)
+        .expect("Failed to build program");
+
+    match program.try_set("uColor", Vec4::ONE) {
+        Err(UniformError::TypeMismatch {
+            name,
+            expected,
+            found,
+        }) => {
+            assert_eq!(name, "uColor");
+            assert_eq!(expected, "vec3");
+            assert!(found.ends_with("Vec4"), "{}", found);
+        }
+        result => panic!("Expected a type mismatch, got {:?}", result),
+    }
+    assert!(matches!(
+        program.try_set("uTexture", 0),
+        Err(UniformError::TypeMismatch { .. })
+    ));
+    assert!(matches!(
+        program.try_set("uWeights[1]", [1.0f32; 2]),
+        Ok(())
+    ));
+
+    // Not written
+    program.set("uMode", 1.5);
+    assert_eq!(uniform_i32(gl, &program, "uMode"), 0);
+}
@@ -0,0 +1,73 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    buffer::Buffer,
+    camera::Camera,
+    compute::ComputeProgram,
+    headless::HeadlessContext,
+    preprocessor::Defines,
+    uniform_buffer::{CameraBlock, UniformBuffer},
+};
+use glam::{Mat4, Vec3};
+
+#[test]
+fn camera_block_reaches_the_shader() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let source = format!(
+        "#version 450 core
+layout (local_size_x = 1) in;
+{}
+layout (std430, binding = 1) buffer Output {{ mat4 matrices[3]; }};
+void main()
+{{
+    matrices[0] = camera.view;
+    matrices[1] = camera.proj;
+    matrices[2] = camera.proj_view;
+}}
+",
+        CameraBlock::GLSL
+    );
+    let program = ComputeProgram::from_source(gl, &source, &Defines::new(), Some("Copy camera"))
+        .expect("Failed to build compute program");
+
+    let interface = program.program().reflect();
+    let camera_block = interface.uniform_block("Camera").unwrap();
+    assert_eq!(camera_block.binding, CameraBlock::BINDING);
+    assert_eq!(
+        camera_block.data_size as usize,
+        std::mem::size_of::<CameraBlock>()
+    );
+
+    let mut camera = Camera::default();
+    let ubo = UniformBuffer::new(
+        gl,
+        &CameraBlock::from(&camera),
+        CameraBlock::BINDING,
+        Some("Camera"),
+    )
+    .expect("Failed to create uniform buffer");
+    // Only the latest value counts
+    camera.set_position(Vec3::new(1.0, 2.0, 3.0));
+    ubo.set(&CameraBlock::from(&camera));
+    assert_eq!(ubo.get(), CameraBlock::from(&camera));
+
+    let output = Buffer::<Mat4>::with_len(gl, 3, 0, Some("Output")).unwrap();
+    unsafe {
+        ubo.bind();
+        gl.BindBufferBase(gl::SHADER_STORAGE_BUFFER, 1, output.id());
+        program.dispatch([1, 1, 1]);
+        program.memory_barrier(gl::BUFFER_UPDATE_BARRIER_BIT);
+    }
+
+    assert_eq!(
+        output.read(),
+        [
+            camera.view_matrix(),
+            camera.proj_matrix(),
+            camera.proj_view_matrix()
+        ]
+    );
+}
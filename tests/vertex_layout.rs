@@ -0,0 +1,105 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use gl_playground::{
+    buffer::Buffer,
+    headless::HeadlessContext,
+    vertex::{Vertex, VertexAttribute, VertexLayout},
+    vertex_array::VertexArray,
+};
+
+#[repr(C)]
+#[derive(Copy, Clone, Debug, bytemuck::Pod, bytemuck::Zeroable, VertexLayout)]
+struct ColoredVertex {
+    position: [f32; 2],
+    #[vertex(location = 3, normalized)]
+    color: [u8; 4],
+    #[vertex(location = 5)]
+    id: u32,
+}
+
+#[test]
+fn vertex_layout_matches_struct() {
+    assert_eq!(
+        Vertex::ATTRIBUTES,
+        &[
+            VertexAttribute {
+                location: 0,
+                components: 3,
+                data_type: gl::FLOAT,
+                normalized: false,
+                integer: false,
+                offset: 0,
+            },
+            VertexAttribute {
+                location: 1,
+                components: 2,
+                data_type: gl::FLOAT,
+                normalized: false,
+                integer: false,
+                offset: 12,
+            },
+        ]
+    );
+}
+
+#[test]
+fn derive_handles_locations_and_normalization() {
+    let attributes = ColoredVertex::ATTRIBUTES;
+    assert_eq!(attributes.len(), 3);
+
+    assert_eq!(attributes[0].location, 0);
+    assert_eq!(attributes[0].components, 2);
+
+    assert_eq!(attributes[1].location, 3);
+    assert_eq!(attributes[1].components, 4);
+    assert_eq!(attributes[1].data_type, gl::UNSIGNED_BYTE);
+    assert!(attributes[1].normalized);
+    assert!(!attributes[1].integer);
+    assert_eq!(attributes[1].offset, 8);
+
+    assert_eq!(attributes[2].location, 5);
+    assert_eq!(attributes[2].data_type, gl::UNSIGNED_INT);
+    assert!(attributes[2].integer);
+    assert_eq!(attributes[2].offset, 12);
+}
+
+#[test]
+fn vertex_array_configures_from_layout() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let gl = context.gl();
+
+    let vertices = [ColoredVertex {
+        position: [0.0, 0.0],
+        color: [255, 0, 0, 255],
+        id: 7,
+    }];
+    let buffer = Buffer::from_data(gl, &vertices, 0, None).unwrap();
+
+    let vertex_array = VertexArray::from_layout::<ColoredVertex>(gl, Some("Colored"));
+    vertex_array.set_vertex_buffer(0, &buffer);
+
+    let get = |location: u32, pname: u32| {
+        let mut value = 0;
+        unsafe { gl.GetVertexArrayIndexediv(vertex_array.id(), location, pname, &mut value) };
+        value
+    };
+
+    assert_eq!(get(0, gl::VERTEX_ATTRIB_ARRAY_ENABLED), 1);
+    assert_eq!(get(1, gl::VERTEX_ATTRIB_ARRAY_ENABLED), 0);
+    assert_eq!(get(3, gl::VERTEX_ATTRIB_ARRAY_ENABLED), 1);
+    assert_eq!(get(3, gl::VERTEX_ATTRIB_ARRAY_NORMALIZED), 1);
+    assert_eq!(get(3, gl::VERTEX_ATTRIB_RELATIVE_OFFSET), 8);
+    assert_eq!(get(5, gl::VERTEX_ATTRIB_ARRAY_INTEGER), 1);
+    assert_eq!(
+        get(5, gl::VERTEX_ATTRIB_ARRAY_TYPE),
+        gl::UNSIGNED_INT as i32
+    );
+
+    let mut stride = 0;
+    unsafe {
+        gl.GetVertexArrayIndexediv(vertex_array.id(), 0, gl::VERTEX_BINDING_STRIDE, &mut stride)
+    };
+    assert_eq!(stride, 16);
+    assert_eq!(unsafe { gl.GetError() }, gl::NO_ERROR);
+}
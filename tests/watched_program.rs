@@ -0,0 +1,74 @@
+// Copyright 2023 Canvas02 <Canvas02@protonmail.com>.
+// SPDX-License-Identifier: MIT
+
+use std::{
+    fs,
+    time::{Duration, Instant},
+};
+
+use gl_playground::{headless::HeadlessContext, watched_program::WatchedProgram};
+
+const VERTEX: &str = "#version 450 core
+void main() { gl_Position = vec4(0.0); }
+";
+
+fn fragment(location: u32) -> String {
+    format!(
+        "#version 450 core
+layout (location = {location}) uniform vec4 uColor;
+layout (location = 0) out vec4 fColor;
+void main() {{ fColor = uColor; }}
+"
+    )
+}
+
+fn wait_for_reload(program: &mut WatchedProgram) -> bool {
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(5) {
+        if program.reload_if_changed() {
+            return true;
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    false
+}
+
+#[test]
+fn reloads_on_change_and_keeps_last_good_program() {
+    let dir = std::env::temp_dir().join("gl_playground_watched_program");
+    fs::create_dir_all(&dir).unwrap();
+    let vertex_path = dir.join("test.vert");
+    let fragment_path = dir.join("test.frag");
+    fs::write(&vertex_path, VERTEX).unwrap();
+    fs::write(&fragment_path, fragment(1)).unwrap();
+
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+    let mut program =
+        WatchedProgram::from_files(context.gl(), &vertex_path, &fragment_path, Some("Watched"))
+            .expect("Failed to build program");
+    assert_eq!(program.uniform("uColor"), Some(1));
+    assert!(!program.reload_if_changed());
+
+    fs::write(&fragment_path, fragment(4)).unwrap();
+    assert!(wait_for_reload(&mut program), "Program wasn't reloaded");
+    // Re-resolved after the reload
+    assert_eq!(program.uniform("uColor"), Some(4));
+    assert_eq!(program.program().label(), Some("Watched"));
+
+    let good_id = program.program().id();
+    fs::write(&fragment_path, "#version 450 core\nvoid main() { nope; }\n").unwrap();
+    assert!(!wait_for_reload(&mut program));
+    assert!(program.reload().is_err());
+    assert_eq!(program.program().id(), good_id);
+    assert_eq!(program.uniform("uColor"), Some(4));
+}
+
+#[test]
+fn reports_missing_files() {
+    let context = HeadlessContext::new((1, 1)).expect("Failed to create headless context");
+
+    assert!(
+        WatchedProgram::from_files(context.gl(), "missing.vert", "missing.frag", None).is_err()
+    );
+}